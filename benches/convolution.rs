@@ -0,0 +1,39 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use nalgebra::DMatrix;
+use rand::Rng;
+use rust_game_of_life::convolution::*;
+
+const MATRIX_SIDE: usize = 256;
+const KERNEL_SIDES: [usize; 8] = [3, 5, 7, 9, 11, 13, 21, 31];
+
+// Both paths correlate the same matrix with square kernels of growing side to find their crossover point.
+// Universes keep the spectra of their kernels, so the FFT path is measured with the spectrum computed once as well
+fn convolution_benchmark(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("convolution");
+    group.sample_size(10);
+
+    let mut random_generator = rand::thread_rng();
+    let matrix = DMatrix::from_fn(MATRIX_SIDE, MATRIX_SIDE, |_, _| random_generator.gen_range(0.0..1.0));
+
+    for kernel_side in KERNEL_SIDES {
+        let kernel = DMatrix::from_fn(kernel_side, kernel_side, |_, _| random_generator.gen_range(0.0..1.0));
+
+        group.bench_with_input(BenchmarkId::new("direct", kernel_side), &kernel_side, |bencher, _| {
+            bencher.iter(|| matrix.correlate(&kernel, ConvolutionMode::Same, &Boundary::Wrap));
+        });
+
+        group.bench_with_input(BenchmarkId::new("fft", kernel_side), &kernel_side, |bencher, _| {
+            bencher.iter(|| correlate_fft(&matrix, &kernel, ConvolutionMode::Same, &Boundary::Wrap));
+        });
+
+        let kernel_spectrum = KernelSpectrum::new(&kernel, matrix.shape(), ConvolutionMode::Same);
+        group.bench_with_input(BenchmarkId::new("cached_fft", kernel_side), &kernel_side, |bencher, _| {
+            bencher.iter(|| kernel_spectrum.correlate(&matrix, &Boundary::Wrap));
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, convolution_benchmark);
+criterion_main!(benches);
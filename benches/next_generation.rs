@@ -0,0 +1,35 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rust_game_of_life::bit_packed_universe::*;
+use rust_game_of_life::topology::*;
+use rust_game_of_life::universe::*;
+
+const UNIVERSE_SIDES: [usize; 3] = [64, 256, 1024];
+
+// Both engines evolve the same random soup, so only the stepping itself is compared
+fn next_generation_benchmark(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("next_generation");
+    group.sample_size(10);
+
+    for universe_side in UNIVERSE_SIDES {
+        let mut universe = Universe::new_random(universe_side, universe_side, Topology::Torus);
+        let mut bit_packed_universe = BitPackedUniverse::from_universe(&universe);
+
+        group.bench_with_input(BenchmarkId::new("convolution", universe_side), &universe_side, |bencher, _| {
+            bencher.iter(|| universe.next_generation());
+        });
+
+        universe.set_parallel(true);
+        group.bench_with_input(BenchmarkId::new("convolution_parallel", universe_side), &universe_side, |bencher, _| {
+            bencher.iter(|| universe.next_generation());
+        });
+
+        group.bench_with_input(BenchmarkId::new("bit_packed", universe_side), &universe_side, |bencher, _| {
+            bencher.iter(|| bit_packed_universe.next_generation());
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, next_generation_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,237 @@
+use std::env;
+use crate::cell_age::*;
+use crate::constants::*;
+use crate::continuous_universe::*;
+use crate::cycle_detector::*;
+use crate::rule::*;
+use crate::rule_table::*;
+use crate::soup_search::*;
+use crate::stochastic::*;
+use crate::topology::*;
+use crate::update_mode::*;
+use crate::volume_universe::*;
+
+const UNIVERSE_WIDTH_ARGUMENT: &str = "--width";
+const UNIVERSE_HEIGHT_ARGUMENT: &str = "--height";
+const RULE_ARGUMENT: &str = "--rule";
+const TOPOLOGY_ARGUMENT: &str = "--topology";
+const PARALLEL_ARGUMENT: &str = "--parallel";
+const CONTINUOUS_RULE_ARGUMENT: &str = "--continuous-rule";
+const RULE_TABLE_ARGUMENT: &str = "--rule-table";
+const WIREWORLD_RULE_TABLE_NAME: &str = "wireworld";
+const SEED_ARGUMENT: &str = "--seed";
+const INITIAL_DENSITY_ARGUMENT: &str = "--density";
+const BIRTH_PROBABILITY_ARGUMENT: &str = "--birth-probability";
+const SURVIVAL_PROBABILITY_ARGUMENT: &str = "--survival-probability";
+const UPDATE_MODE_ARGUMENT: &str = "--update-mode";
+const UNIVERSE_DEPTH_ARGUMENT: &str = "--depth";
+const VOLUME_RULE_ARGUMENT: &str = "--volume-rule";
+const ISOMETRIC_ARGUMENT: &str = "--isometric";
+const HISTORY_CAPACITY_ARGUMENT: &str = "--history";
+const MAX_CYCLE_PERIOD_ARGUMENT: &str = "--max-period";
+const SOUP_SEARCH_ARGUMENT: &str = "--soup-search";
+const MAX_GENERATIONS_ARGUMENT: &str = "--max-generations";
+const STATS_PATH_ARGUMENT: &str = "--stats";
+const SPARKLINE_ARGUMENT: &str = "--sparkline";
+const AGE_COLOURS_ARGUMENT: &str = "--age-colours";
+const GHOST_TRAILS_ARGUMENT: &str = "--ghost-trails";
+
+pub struct Arguments {
+    pub universe_width: usize,
+    pub universe_height: usize,
+    pub rule: Rule,
+    pub topology: Topology,
+    pub is_parallel: bool,
+    pub continuous_rule: Option<ContinuousRule>,
+    pub rule_table: Option<RuleTable>,
+    pub seed: Option<u64>,
+    pub initial_density: f64,
+    pub birth_probability: f64,
+    pub survival_probability: f64,
+    pub update_mode: UpdateMode,
+    pub universe_depth: usize,
+    pub volume_rule: Option<VolumeRule>,
+    pub is_isometric: bool,
+    pub history_capacity: usize,
+    pub max_cycle_period: usize,
+    pub number_of_soups: Option<usize>,
+    pub max_generations: usize,
+    pub stats_path: Option<String>,
+    pub is_sparkline_shown: bool,
+    pub age_colour_ramp: Option<ColourRamp>,
+    pub ghost_trail_length: usize,
+}
+
+impl Default for Arguments {
+    fn default() -> Self {
+        Self {
+            universe_width: UNIVERSE_WIDTH,
+            universe_height: UNIVERSE_HEIGHT,
+            rule: Rule::default(),
+            topology: Topology::default(),
+            is_parallel: false,
+            continuous_rule: None,
+            rule_table: None,
+            seed: None,
+            initial_density: DEFAULT_INITIAL_DENSITY,
+            birth_probability: 1.0,
+            survival_probability: 1.0,
+            update_mode: UpdateMode::default(),
+            universe_depth: UNIVERSE_DEPTH,
+            volume_rule: None,
+            is_isometric: false,
+            history_capacity: DEFAULT_HISTORY_CAPACITY,
+            max_cycle_period: DEFAULT_MAX_CYCLE_PERIOD,
+            number_of_soups: None,
+            max_generations: DEFAULT_SOUP_MAX_GENERATIONS,
+            stats_path: None,
+            is_sparkline_shown: false,
+            age_colour_ramp: None,
+            ghost_trail_length: 0,
+        }
+    }
+}
+
+fn expect_value(argument_name: &str, argument_value: Option<String>) -> String {
+    argument_value.unwrap_or_else(|| panic!("Argument '{}' expects a value.", argument_name))
+}
+
+fn parse_dimension(argument_name: &str, argument_value: Option<String>) -> usize {
+    let argument_value = expect_value(argument_name, argument_value);
+
+    match argument_value.parse::<usize>() {
+        Ok(dimension) if dimension > 0 => dimension,
+        _ => panic!("Argument '{}' expects a positive integer, received '{}'.", argument_name, argument_value),
+    }
+}
+
+fn parse_capacity(argument_name: &str, argument_value: Option<String>) -> usize {
+    let argument_value = expect_value(argument_name, argument_value);
+
+    match argument_value.parse::<usize>() {
+        Ok(capacity) => capacity,
+        _ => panic!("Argument '{}' expects a non-negative integer, received '{}'.", argument_name, argument_value),
+    }
+}
+
+fn parse_rule(argument_name: &str, argument_value: Option<String>) -> Rule {
+    let argument_value = expect_value(argument_name, argument_value);
+
+    match argument_value.parse::<Rule>() {
+        Ok(rule) => rule,
+        Err(error) => panic!("Argument '{}' expects a rulestring like 'B3/S23', received '{}': {}.", argument_name, argument_value, error),
+    }
+}
+
+fn parse_seed(argument_name: &str, argument_value: Option<String>) -> u64 {
+    let argument_value = expect_value(argument_name, argument_value);
+
+    match argument_value.parse::<u64>() {
+        Ok(seed) => seed,
+        _ => panic!("Argument '{}' expects a non-negative integer, received '{}'.", argument_name, argument_value),
+    }
+}
+
+fn parse_probability(argument_name: &str, argument_value: Option<String>) -> f64 {
+    let argument_value = expect_value(argument_name, argument_value);
+
+    match argument_value.parse::<f64>() {
+        Ok(probability) if (0.0..=1.0).contains(&probability) => probability,
+        _ => panic!("Argument '{}' expects a number from 0 to 1, received '{}'.", argument_name, argument_value),
+    }
+}
+
+fn parse_update_mode(argument_name: &str, argument_value: Option<String>) -> UpdateMode {
+    let argument_value = expect_value(argument_name, argument_value);
+
+    match argument_value.parse::<UpdateMode>() {
+        Ok(update_mode) => update_mode,
+        Err(error) => panic!("Argument '{}' expects an update mode like 'alpha-asynchronous:0.5' or 'margolus:critters', received '{}': {}.", argument_name, argument_value, error),
+    }
+}
+
+fn parse_volume_rule(argument_name: &str, argument_value: Option<String>) -> VolumeRule {
+    let argument_value = expect_value(argument_name, argument_value);
+
+    match argument_value.parse::<VolumeRule>() {
+        Ok(rule) => rule,
+        Err(error) => panic!("Argument '{}' expects a rulestring like '4555', received '{}': {}.", argument_name, argument_value, error),
+    }
+}
+
+fn parse_topology(argument_name: &str, argument_value: Option<String>) -> Topology {
+    let argument_value = expect_value(argument_name, argument_value);
+
+    match argument_value.parse::<Topology>() {
+        Ok(topology) => topology,
+        Err(error) => panic!("Argument '{}' expects a topology like 'torus', received '{}': {}.", argument_name, argument_value, error),
+    }
+}
+
+fn parse_continuous_rule(argument_name: &str, argument_value: Option<String>) -> ContinuousRule {
+    let argument_value = expect_value(argument_name, argument_value);
+
+    match argument_value.parse::<ContinuousRule>() {
+        Ok(rule) => rule,
+        Err(error) => panic!("Argument '{}' expects a rulestring like 'R=13;T=10;m=0.15;s=0.015;b=1', received '{}': {}.", argument_name, argument_value, error),
+    }
+}
+
+fn parse_colour_ramp(argument_name: &str, argument_value: Option<String>) -> ColourRamp {
+    let argument_value = expect_value(argument_name, argument_value);
+
+    match argument_value.parse::<ColourRamp>() {
+        Ok(colour_ramp) => colour_ramp,
+        Err(error) => panic!("Argument '{}' expects a colour ramp like '{}', received '{}': {}.", argument_name, DEFAULT_AGE_COLOUR_RAMP, argument_value, error),
+    }
+}
+
+// Rule table is either the built-in Wireworld or loaded from a Golly `.rule` file
+fn parse_rule_table(argument_name: &str, argument_value: Option<String>) -> RuleTable {
+    let argument_value = expect_value(argument_name, argument_value);
+    if argument_value.to_lowercase() == WIREWORLD_RULE_TABLE_NAME {
+        return RuleTable::wireworld();
+    }
+
+    match RuleTable::from_file(&argument_value) {
+        Ok(rule_table) => rule_table,
+        Err(error) => panic!("Argument '{}' expects '{}' or a path to a '.rule' file, received '{}': {}.", argument_name, WIREWORLD_RULE_TABLE_NAME, argument_value, error),
+    }
+}
+
+// Parse command line arguments like `--width 400 --height 300 --rule B36/S23 --topology klein-bottle --parallel`
+pub fn parse_arguments() -> Arguments {
+    let mut arguments = Arguments::default();
+    let mut command_line = env::args().skip(1);
+
+    while let Some(argument_name) = command_line.next() {
+        match argument_name.as_str() {
+            UNIVERSE_WIDTH_ARGUMENT => arguments.universe_width = parse_dimension(&argument_name, command_line.next()),
+            UNIVERSE_HEIGHT_ARGUMENT => arguments.universe_height = parse_dimension(&argument_name, command_line.next()),
+            RULE_ARGUMENT => arguments.rule = parse_rule(&argument_name, command_line.next()),
+            TOPOLOGY_ARGUMENT => arguments.topology = parse_topology(&argument_name, command_line.next()),
+            PARALLEL_ARGUMENT => arguments.is_parallel = true,
+            CONTINUOUS_RULE_ARGUMENT => arguments.continuous_rule = Some(parse_continuous_rule(&argument_name, command_line.next())),
+            RULE_TABLE_ARGUMENT => arguments.rule_table = Some(parse_rule_table(&argument_name, command_line.next())),
+            SEED_ARGUMENT => arguments.seed = Some(parse_seed(&argument_name, command_line.next())),
+            INITIAL_DENSITY_ARGUMENT => arguments.initial_density = parse_probability(&argument_name, command_line.next()),
+            BIRTH_PROBABILITY_ARGUMENT => arguments.birth_probability = parse_probability(&argument_name, command_line.next()),
+            SURVIVAL_PROBABILITY_ARGUMENT => arguments.survival_probability = parse_probability(&argument_name, command_line.next()),
+            UPDATE_MODE_ARGUMENT => arguments.update_mode = parse_update_mode(&argument_name, command_line.next()),
+            UNIVERSE_DEPTH_ARGUMENT => arguments.universe_depth = parse_dimension(&argument_name, command_line.next()),
+            VOLUME_RULE_ARGUMENT => arguments.volume_rule = Some(parse_volume_rule(&argument_name, command_line.next())),
+            ISOMETRIC_ARGUMENT => arguments.is_isometric = true,
+            HISTORY_CAPACITY_ARGUMENT => arguments.history_capacity = parse_capacity(&argument_name, command_line.next()),
+            MAX_CYCLE_PERIOD_ARGUMENT => arguments.max_cycle_period = parse_capacity(&argument_name, command_line.next()),
+            SOUP_SEARCH_ARGUMENT => arguments.number_of_soups = Some(parse_dimension(&argument_name, command_line.next())),
+            MAX_GENERATIONS_ARGUMENT => arguments.max_generations = parse_dimension(&argument_name, command_line.next()),
+            STATS_PATH_ARGUMENT => arguments.stats_path = Some(expect_value(&argument_name, command_line.next())),
+            SPARKLINE_ARGUMENT => arguments.is_sparkline_shown = true,
+            AGE_COLOURS_ARGUMENT => arguments.age_colour_ramp = Some(parse_colour_ramp(&argument_name, command_line.next())),
+            GHOST_TRAILS_ARGUMENT => arguments.ghost_trail_length = parse_dimension(&argument_name, command_line.next()),
+            _ => panic!("Unrecognized argument '{}'.", argument_name),
+        }
+    }
+
+    arguments
+}
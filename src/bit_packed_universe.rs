@@ -0,0 +1,269 @@
+use crate::rule::*;
+use crate::universe::*;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+type Word = u64;
+
+// Add three one-bit numbers given for 64 cells at once, returns the sum and the carry
+fn add_full(first: Word, second: Word, third: Word) -> (Word, Word) {
+    let partial_sum = first ^ second;
+    (partial_sum ^ third, (first & second) | (partial_sum & third))
+}
+
+fn add_half(first: Word, second: Word) -> (Word, Word) {
+    (first ^ second, first & second)
+}
+
+// Count alive neighbours of 64 cells at once, every count is spread over four words bit by bit
+fn count_neighbours(neighbours: [Word; MAX_NUMBER_OF_NEIGHBOURS]) -> [Word; 4] {
+    let [north_west, north, north_east, west, east, south_west, south, south_east] = neighbours;
+
+    let (upper_ones, upper_twos) = add_full(north_west, north, north_east);
+    let (middle_ones, middle_twos) = add_full(west, east, south_west);
+    let (lower_ones, lower_twos) = add_half(south, south_east);
+
+    let (ones, ones_carry) = add_full(upper_ones, middle_ones, lower_ones);
+    let (partial_twos, twos_carry) = add_full(upper_twos, middle_twos, lower_twos);
+    let (twos, partial_twos_carry) = add_half(partial_twos, ones_carry);
+    let (fours, eights) = add_half(twos_carry, partial_twos_carry);
+
+    [ones, twos, fours, eights]
+}
+
+// Cells whose number of alive neighbours is equal to the given one
+fn get_cells_with_neighbour_count(neighbour_count_bits: &[Word; 4], number_of_neighbours: usize) -> Word {
+    neighbour_count_bits
+        .iter()
+        .enumerate()
+        .fold(Word::MAX, |cells, (bit_index, count_bit)| {
+            if number_of_neighbours & (1 << bit_index) != 0 { cells & count_bit } else { cells & !count_bit }
+        })
+}
+
+// Toroidal universe of two-state totalistic Life-like rules which packs 64 cells into each word
+// and evolves them all at once with bitwise operations instead of convolving cell by cell
+pub struct BitPackedUniverse {
+    universe_height: usize,
+    universe_width: usize,
+    words_per_row: usize,
+    rule: Rule,
+    next_generation: Vec<Word>,
+}
+
+impl BitPackedUniverse {
+    pub fn new(universe_width: usize, universe_height: usize) -> Self {
+        if universe_width == 0 || universe_height == 0 {
+            panic!("'BitPackedUniverse' expects non-zero dimensions, received {}x{}.", universe_width, universe_height);
+        }
+
+        let words_per_row = universe_width.div_ceil(WORD_BITS);
+
+        BitPackedUniverse {
+            universe_height,
+            universe_width,
+            words_per_row,
+            rule: Rule::default(),
+            next_generation: vec![0; words_per_row * universe_height],
+        }
+    }
+
+    // Copy populated cells of the universe, decaying ones don't exist in two-state rules
+    pub fn from_universe(universe: &Universe) -> Self {
+        let (universe_height, universe_width) = universe.shape();
+        let mut bit_packed_universe = BitPackedUniverse::new(universe_width, universe_height);
+        bit_packed_universe.set_rule(universe.rule().clone());
+
+        for row_index in 0..universe_height {
+            for column_index in 0..universe_width {
+                if universe.get_cell_state(row_index, column_index) == CELL_IS_POPULATED {
+                    bit_packed_universe.populate_cell(row_index, column_index);
+                }
+            }
+        }
+
+        bit_packed_universe
+    }
+
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    pub fn set_rule(&mut self, rule: Rule) {
+        if !rule.is_life_like() || !rule.is_totalistic() || rule.number_of_states() != LIFE_NUMBER_OF_STATES {
+            panic!("'BitPackedUniverse' expects a two-state totalistic Life-like rule, received {}.", rule);
+        }
+
+        self.rule = rule;
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        (self.universe_height, self.universe_width)
+    }
+
+    fn get_word_position(&self, cell_row: usize, cell_column: usize) -> (usize, Word) {
+        if cell_row >= self.universe_height || cell_column >= self.universe_width {
+            panic!("Cell ({}, {}) is out of the universe of shape {:?}.", cell_row, cell_column, self.shape());
+        }
+
+        (cell_row * self.words_per_row + cell_column / WORD_BITS, 1 << (cell_column % WORD_BITS))
+    }
+
+    pub fn populate_cell(&mut self, cell_row: usize, cell_column: usize) {
+        let (word_index, cell_bit) = self.get_word_position(cell_row, cell_column);
+        self.next_generation[word_index] |= cell_bit;
+    }
+
+    pub fn free_cell(&mut self, cell_row: usize, cell_column: usize) {
+        let (word_index, cell_bit) = self.get_word_position(cell_row, cell_column);
+        self.next_generation[word_index] &= !cell_bit;
+    }
+
+    pub fn get_cell_state(&self, cell_row: usize, cell_column: usize) -> u8 {
+        let (word_index, cell_bit) = self.get_word_position(cell_row, cell_column);
+        if self.next_generation[word_index] & cell_bit != 0 { CELL_IS_POPULATED } else { CELL_IS_FREE }
+    }
+
+    fn get_row(&self, row_index: usize) -> &[Word] {
+        &self.next_generation[row_index * self.words_per_row..(row_index + 1) * self.words_per_row]
+    }
+
+    // Number of cells stored in the word, the last word of a row may be filled partially
+    fn get_number_of_word_cells(&self, word_index: usize) -> usize {
+        (self.universe_width - word_index * WORD_BITS).min(WORD_BITS)
+    }
+
+    fn get_row_cell(&self, row: &[Word], cell_column: usize) -> Word {
+        (row[cell_column / WORD_BITS] >> (cell_column % WORD_BITS)) & 1
+    }
+
+    // Shift the word so that each cell gets the state of its west, centre and east neighbours,
+    // cells shifted in from the adjacent words wrap around the row
+    fn get_shifted_words(&self, row: &[Word], word_index: usize) -> [Word; 3] {
+        let word = row[word_index];
+        let number_of_word_cells = self.get_number_of_word_cells(word_index);
+        let word_mask = if number_of_word_cells == WORD_BITS { Word::MAX } else { (1 << number_of_word_cells) - 1 };
+
+        let first_column = word_index * WORD_BITS;
+        let west_column = (first_column + self.universe_width - 1) % self.universe_width;
+        let east_column = (first_column + number_of_word_cells) % self.universe_width;
+
+        let west_neighbours = ((word << 1) & word_mask) | self.get_row_cell(row, west_column);
+        let east_neighbours = (word >> 1) | (self.get_row_cell(row, east_column) << (number_of_word_cells - 1));
+
+        [west_neighbours, word, east_neighbours]
+    }
+
+    pub fn next_generation(&mut self) {
+        let birth_counts: Vec<bool> = (0..=MAX_NUMBER_OF_NEIGHBOURS).map(|count| self.rule.is_birth((1 << count) - 1)).collect();
+        let survival_counts: Vec<bool> = (0..=MAX_NUMBER_OF_NEIGHBOURS).map(|count| self.rule.is_survival((1 << count) - 1)).collect();
+
+        let mut next_generation = vec![0; self.next_generation.len()];
+
+        for row_index in 0..self.universe_height {
+            let upper_row = self.get_row((row_index + self.universe_height - 1) % self.universe_height);
+            let middle_row = self.get_row(row_index);
+            let lower_row = self.get_row((row_index + 1) % self.universe_height);
+
+            for word_index in 0..self.words_per_row {
+                let [north_west, north, north_east] = self.get_shifted_words(upper_row, word_index);
+                let [west, current_cells, east] = self.get_shifted_words(middle_row, word_index);
+                let [south_west, south, south_east] = self.get_shifted_words(lower_row, word_index);
+
+                let neighbour_count_bits = count_neighbours([north_west, north, north_east, west, east, south_west, south, south_east]);
+
+                let mut born_cells = 0;
+                let mut surviving_cells = 0;
+                for number_of_neighbours in 0..=MAX_NUMBER_OF_NEIGHBOURS {
+                    if !birth_counts[number_of_neighbours] && !survival_counts[number_of_neighbours] {
+                        continue;
+                    }
+
+                    let cells = get_cells_with_neighbour_count(&neighbour_count_bits, number_of_neighbours);
+                    if birth_counts[number_of_neighbours] {
+                        born_cells |= cells;
+                    }
+                    if survival_counts[number_of_neighbours] {
+                        surviving_cells |= cells;
+                    }
+                }
+
+                let number_of_word_cells = self.get_number_of_word_cells(word_index);
+                let word_mask = if number_of_word_cells == WORD_BITS { Word::MAX } else { (1 << number_of_word_cells) - 1 };
+
+                next_generation[row_index * self.words_per_row + word_index] =
+                    ((!current_cells & born_cells) | (current_cells & surviving_cells)) & word_mask;
+            }
+        }
+
+        self.next_generation = next_generation;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::topology::*;
+
+    fn is_same_generation(universe: &Universe, bit_packed_universe: &BitPackedUniverse) -> bool {
+        let (universe_height, universe_width) = universe.shape();
+        (0..universe_height).all(|row_index| {
+            (0..universe_width).all(|column_index| {
+                universe.get_cell_state(row_index, column_index) == bit_packed_universe.get_cell_state(row_index, column_index)
+            })
+        })
+    }
+
+    #[test]
+    fn neighbours_are_counted_bit_by_bit() {
+        for number_of_neighbours in 0..=MAX_NUMBER_OF_NEIGHBOURS {
+            let mut neighbours = [0; MAX_NUMBER_OF_NEIGHBOURS];
+            for neighbour in neighbours.iter_mut().take(number_of_neighbours) {
+                *neighbour = Word::MAX;
+            }
+
+            let neighbour_count_bits = count_neighbours(neighbours);
+            assert_eq!(get_cells_with_neighbour_count(&neighbour_count_bits, number_of_neighbours), Word::MAX);
+            assert_eq!(get_cells_with_neighbour_count(&neighbour_count_bits, (number_of_neighbours + 1) % 9), 0);
+        }
+    }
+
+    #[test]
+    fn cells_are_populated_and_freed() {
+        let mut universe = BitPackedUniverse::new(130, 3);
+        universe.populate_cell(2, 129);
+        universe.populate_cell(1, 64);
+        assert_eq!(universe.get_cell_state(2, 129), CELL_IS_POPULATED);
+        assert_eq!(universe.get_cell_state(1, 64), CELL_IS_POPULATED);
+        assert_eq!(universe.get_cell_state(1, 63), CELL_IS_FREE);
+
+        universe.free_cell(2, 129);
+        assert_eq!(universe.get_cell_state(2, 129), CELL_IS_FREE);
+    }
+
+    #[test]
+    fn generations_match_convolution_on_random_soups() {
+        let universe_shapes = [(50, 50), (64, 64), (130, 20), (1, 5), (200, 1)];
+
+        for rulestring in ["B3/S23", "B36/S23", "B2/S", "B3678/S34678"] {
+            for (universe_width, universe_height) in universe_shapes {
+                let mut universe = Universe::new_random(universe_width, universe_height, Topology::Torus);
+                universe.set_rule(rulestring.parse().unwrap());
+                let mut bit_packed_universe = BitPackedUniverse::from_universe(&universe);
+
+                for _ in 0..20 {
+                    universe.next_generation();
+                    bit_packed_universe.next_generation();
+                    assert!(is_same_generation(&universe, &bit_packed_universe));
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn non_totalistic_rule_is_rejected() {
+        let mut universe = BitPackedUniverse::new(64, 64);
+        universe.set_rule("B2-a/S12".parse().unwrap());
+    }
+}
@@ -0,0 +1,182 @@
+use nalgebra::DMatrix;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use crate::universe::*;
+
+pub const DEFAULT_MAX_RAMP_AGE: u32 = 64;
+pub const DEFAULT_AGE_COLOUR_RAMP: &str = "ffff80,ff8000,c00040,4040ff:64";
+
+const NO_AGE: u32 = u32::MAX;
+const COLOUR_SEPARATOR: char = ',';
+const MAX_AGE_SEPARATOR: char = ':';
+
+// Age counts the generations a cell has been non-free since it was born or drawn, starting from 0.
+// Once it gets free the generations since its death are counted instead, until it is born again
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellAges {
+    ages: DMatrix<u32>,
+    times_since_death: DMatrix<u32>,
+}
+
+impl CellAges {
+    pub fn new(plane: &UniversePlane) -> Self {
+        CellAges {
+            ages: plane.map(|cell_state| if cell_state == CELL_IS_FREE { NO_AGE } else { 0 }),
+            times_since_death: DMatrix::from_element(plane.nrows(), plane.ncols(), NO_AGE),
+        }
+    }
+
+    pub fn get_age(&self, position: (usize, usize)) -> Option<u32> {
+        Some(self.ages[position]).filter(|age| *age != NO_AGE)
+    }
+
+    pub fn get_time_since_death(&self, position: (usize, usize)) -> Option<u32> {
+        Some(self.times_since_death[position]).filter(|time_since_death| *time_since_death != NO_AGE)
+    }
+
+    // Cells drawn since the last generation are newborn, erased ones have just died
+    pub fn record_edits(&mut self, plane: &UniversePlane) {
+        for ((cell_state, age), time_since_death) in plane.iter().zip(self.ages.iter_mut()).zip(self.times_since_death.iter_mut()) {
+            if *cell_state != CELL_IS_FREE && *age == NO_AGE {
+                *age = 0;
+                *time_since_death = NO_AGE;
+            } else if *cell_state == CELL_IS_FREE && *age != NO_AGE {
+                *age = NO_AGE;
+                *time_since_death = 0;
+            }
+        }
+    }
+
+    // Age every cell by the generation which has just passed
+    pub fn advance(&mut self, plane: &UniversePlane) {
+        for ((cell_state, age), time_since_death) in plane.iter().zip(self.ages.iter_mut()).zip(self.times_since_death.iter_mut()) {
+            if *cell_state != CELL_IS_FREE {
+                *age = if *age == NO_AGE { 0 } else { (*age + 1).min(NO_AGE - 1) };
+                *time_since_death = NO_AGE;
+            } else if *age != NO_AGE {
+                *age = NO_AGE;
+                *time_since_death = 0;
+            } else if *time_since_death != NO_AGE {
+                *time_since_death = (*time_since_death + 1).min(NO_AGE - 1);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColourRampParseError(String);
+
+impl fmt::Display for ColourRampParseError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "'{}' is not a list of at least two colours like 'ffff80{}4040ff' optionally followed by '{}<max age>'",
+            self.0, COLOUR_SEPARATOR, MAX_AGE_SEPARATOR)
+    }
+}
+
+impl Error for ColourRampParseError {}
+
+// Colours spread evenly over the ages from 0 to the max age, older cells keep the last colour
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColourRamp {
+    colours: Vec<(u8, u8, u8)>,
+    max_age: u32,
+}
+
+impl ColourRamp {
+    pub fn new(colours: Vec<(u8, u8, u8)>, max_age: u32) -> Self {
+        if colours.len() < 2 || max_age == 0 {
+            panic!("'ColourRamp' expects at least two colours and a non-zero max age, received {} colours and max age {}.", colours.len(), max_age);
+        }
+
+        ColourRamp { colours, max_age }
+    }
+
+    pub fn max_age(&self) -> u32 {
+        self.max_age
+    }
+
+    pub fn get_colour(&self, age: u32) -> (u8, u8, u8) {
+        let ramp_position = (age.min(self.max_age) as f32 / self.max_age as f32) * (self.colours.len() - 1) as f32;
+        let lower_colour_index = (ramp_position as usize).min(self.colours.len() - 2);
+        let progress = ramp_position - lower_colour_index as f32;
+
+        let (lower_red, lower_green, lower_blue) = self.colours[lower_colour_index];
+        let (upper_red, upper_green, upper_blue) = self.colours[lower_colour_index + 1];
+        let mix = |lower: u8, upper: u8| (lower as f32 + (upper as f32 - lower as f32) * progress).round() as u8;
+        (mix(lower_red, upper_red), mix(lower_green, upper_green), mix(lower_blue, upper_blue))
+    }
+}
+
+impl Default for ColourRamp {
+    fn default() -> Self {
+        DEFAULT_AGE_COLOUR_RAMP.parse().unwrap()
+    }
+}
+
+// Parse hexadecimal colours like `ffff80,ff8000,4040ff:100`, the max age defaults to 64
+impl FromStr for ColourRamp {
+    type Err = ColourRampParseError;
+
+    fn from_str(colour_ramp: &str) -> Result<Self, Self::Err> {
+        let parse_error = || ColourRampParseError(String::from(colour_ramp));
+        let (colours, max_age) = match colour_ramp.trim().split_once(MAX_AGE_SEPARATOR) {
+            Some((colours, max_age)) => (colours, max_age.trim().parse::<u32>().map_err(|_| parse_error())?),
+            None => (colour_ramp.trim(), DEFAULT_MAX_RAMP_AGE),
+        };
+
+        let colours = colours
+            .split(COLOUR_SEPARATOR)
+            .map(|colour| {
+                let colour = colour.trim().trim_start_matches('#');
+                let channel = |channel_start: usize| colour.get(channel_start..channel_start + 2).and_then(|channel| u8::from_str_radix(channel, 16).ok());
+                match (colour.len(), channel(0), channel(2), channel(4)) {
+                    (6, Some(red), Some(green), Some(blue)) => Ok((red, green, blue)),
+                    _ => Err(parse_error()),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if colours.len() < 2 || max_age == 0 {
+            return Err(parse_error());
+        }
+
+        Ok(ColourRamp::new(colours, max_age))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::dmatrix;
+
+    #[test]
+    fn ages_and_times_since_death_are_counted() {
+        let mut cell_ages = CellAges::new(&dmatrix![1, 0, 0]);
+        cell_ages.advance(&dmatrix![1, 1, 0]);
+        cell_ages.advance(&dmatrix![1, 0, 0]);
+        assert_eq!((cell_ages.get_age((0, 0)), cell_ages.get_age((0, 1)), cell_ages.get_age((0, 2))), (Some(2), None, None));
+        assert_eq!((cell_ages.get_time_since_death((0, 0)), cell_ages.get_time_since_death((0, 1)), cell_ages.get_time_since_death((0, 2))), (None, Some(0), None));
+
+        cell_ages.record_edits(&dmatrix![0, 0, 1]);
+        cell_ages.advance(&dmatrix![0, 0, 1]);
+        assert_eq!((cell_ages.get_age((0, 0)), cell_ages.get_age((0, 2))), (None, Some(1)));
+        assert_eq!((cell_ages.get_time_since_death((0, 0)), cell_ages.get_time_since_death((0, 1))), (Some(1), Some(1)));
+    }
+
+    #[test]
+    fn colour_ramp_blends_between_colours() {
+        let colour_ramp: ColourRamp = "#000000, ff8040, ffffff:10".parse().unwrap();
+        assert_eq!(colour_ramp.max_age(), 10);
+        assert_eq!(colour_ramp.get_colour(0), (0, 0, 0));
+        assert_eq!(colour_ramp.get_colour(5), (255, 128, 64));
+        assert_eq!(colour_ramp.get_colour(6), (255, 153, 102));
+        assert_eq!(colour_ramp.get_colour(100), (255, 255, 255));
+        assert_eq!(ColourRamp::default().max_age(), DEFAULT_MAX_RAMP_AGE);
+
+        assert!("ff0000".parse::<ColourRamp>().is_err());
+        assert!("ff0000,00ff0".parse::<ColourRamp>().is_err());
+        assert!("ff0000,00ff00:0".parse::<ColourRamp>().is_err());
+        assert!("ff0000,00ffzz".parse::<ColourRamp>().is_err());
+    }
+}
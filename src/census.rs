@@ -0,0 +1,339 @@
+use nalgebra::DMatrix;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use crate::cycle_detector::*;
+use crate::rule::*;
+use crate::topology::*;
+use crate::universe::*;
+
+pub const DEFAULT_CENSUS_MAX_GENERATIONS: usize = 1000;
+pub const UNCLASSIFIED_OBJECT_CODE: &str = "zz_UNCLASSIFIED";
+
+const STILL_LIFE_PREFIX: &str = "xs";
+const OSCILLATOR_PREFIX: &str = "xp";
+const SPACESHIP_PREFIX: &str = "xq";
+const WECHSLER_STRIP_HEIGHT: usize = 5;
+const WECHSLER_STRIP_SEPARATOR: char = 'z';
+const WECHSLER_DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+const WECHSLER_TWO_ZEROS: char = 'w';
+const WECHSLER_THREE_ZEROS: char = 'x';
+const WECHSLER_MANY_ZEROS: char = 'y';
+const MIN_MANY_ZEROS: usize = 4;
+const MAX_MANY_ZEROS: usize = 39;
+
+const OBJECT_NAMES: [(&str, &str); 12] = [
+    ("xs4_33", "block"),
+    ("xs6_696", "beehive"),
+    ("xs7_2596", "loaf"),
+    ("xs5_253", "boat"),
+    ("xs6_356", "ship"),
+    ("xs4_252", "tub"),
+    ("xs8_6996", "pond"),
+    ("xp2_7", "blinker"),
+    ("xp2_7e", "toad"),
+    ("xp2_318c", "beacon"),
+    ("xq4_153", "glider"),
+    ("xq4_6frc", "lightweight spaceship"),
+];
+
+// Cells closer than this to each other may raise a cell between them, so they can't evolve apart
+pub fn get_interaction_distance(rule: &Rule) -> isize {
+    let kernel_half = rule.kernels().iter().map(|rule_kernel| rule_kernel.nrows().max(rule_kernel.ncols()) / 2).max().unwrap_or(1);
+    2 * kernel_half as isize
+}
+
+// Non-free cells within the interaction distance of each other, together with the cells as they look unwrapped,
+// which keeps an object whole even if it lies across the joined edges
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniverseObject {
+    positions: Vec<(usize, usize)>,
+    upper_left_position: (isize, isize),
+    cells: UniversePlane,
+}
+
+impl UniverseObject {
+    pub fn positions(&self) -> &[(usize, usize)] {
+        &self.positions
+    }
+
+    // Corner of the cells as reached from the first position, it lays beyond the plane when the object wraps around
+    pub fn upper_left_position(&self) -> (isize, isize) {
+        self.upper_left_position
+    }
+
+    pub fn cells(&self) -> &UniversePlane {
+        &self.cells
+    }
+
+    pub fn population(&self) -> usize {
+        self.positions.len()
+    }
+}
+
+// Objects are gathered through the positions seen across the edges rather than through the cells of the plane,
+// so crossing a twisted edge mirrors the rest of the object just like the topology does
+fn collect_object(plane: &UniversePlane, topology: Topology, position: (usize, usize), interaction_distance: isize, is_visited: &mut DMatrix<bool>) -> UniverseObject {
+    let mut positions = Vec::new();
+    let mut unwrapped_positions = Vec::new();
+    let mut unvisited_positions = VecDeque::from([(position.0 as isize, position.1 as isize)]);
+    is_visited[position] = true;
+
+    while let Some((row, column)) = unvisited_positions.pop_front() {
+        unwrapped_positions.push((row, column));
+        positions.push(topology.get_plane_position(row, column, plane.shape()).unwrap());
+
+        for row_offset in -interaction_distance..=interaction_distance {
+            for column_offset in -interaction_distance..=interaction_distance {
+                let (neighbour_row, neighbour_column) = (row + row_offset, column + column_offset);
+                if let Some(neighbour_position) = topology.get_plane_position(neighbour_row, neighbour_column, plane.shape()) {
+                    if !is_visited[neighbour_position] && plane[neighbour_position] != CELL_IS_FREE {
+                        is_visited[neighbour_position] = true;
+                        unvisited_positions.push_back((neighbour_row, neighbour_column));
+                    }
+                }
+            }
+        }
+    }
+
+    let first_row = unwrapped_positions.iter().map(|(row, _)| *row).min().unwrap();
+    let first_column = unwrapped_positions.iter().map(|(_, column)| *column).min().unwrap();
+    let number_of_rows = (unwrapped_positions.iter().map(|(row, _)| *row).max().unwrap() - first_row + 1) as usize;
+    let number_of_columns = (unwrapped_positions.iter().map(|(_, column)| *column).max().unwrap() - first_column + 1) as usize;
+
+    let mut cells = UniversePlane::from_element(number_of_rows, number_of_columns, CELL_IS_FREE);
+    for (position, (row, column)) in positions.iter().zip(unwrapped_positions) {
+        cells[((row - first_row) as usize, (column - first_column) as usize)] = plane[*position];
+    }
+
+    UniverseObject { positions, upper_left_position: (first_row, first_column), cells }
+}
+
+pub fn find_object(plane: &UniversePlane, topology: Topology, position: (usize, usize), interaction_distance: isize) -> Option<UniverseObject> {
+    if plane[position] == CELL_IS_FREE {
+        return None;
+    }
+
+    let mut is_visited = DMatrix::from_element(plane.nrows(), plane.ncols(), false);
+    Some(collect_object(plane, topology, position, interaction_distance, &mut is_visited))
+}
+
+pub fn segment_objects(plane: &UniversePlane, topology: Topology, interaction_distance: isize) -> Vec<UniverseObject> {
+    let mut is_visited = DMatrix::from_element(plane.nrows(), plane.ncols(), false);
+    let mut objects = Vec::new();
+    for row_index in 0..plane.nrows() {
+        for column_index in 0..plane.ncols() {
+            if !is_visited[(row_index, column_index)] && plane[(row_index, column_index)] != CELL_IS_FREE {
+                objects.push(collect_object(plane, topology, (row_index, column_index), interaction_distance, &mut is_visited));
+            }
+        }
+    }
+
+    objects
+}
+
+// Runs of zero columns are shortened: two are `w`, three are `x`, and `y` followed by a digit stands for 4 to 39
+fn encode_zero_columns(mut number_of_zeros: usize, code: &mut String) {
+    while number_of_zeros >= MIN_MANY_ZEROS {
+        let number_of_many_zeros = number_of_zeros.min(MAX_MANY_ZEROS);
+        code.push(WECHSLER_MANY_ZEROS);
+        code.push(WECHSLER_DIGITS[number_of_many_zeros - MIN_MANY_ZEROS] as char);
+        number_of_zeros -= number_of_many_zeros;
+    }
+
+    match number_of_zeros {
+        3 => code.push(WECHSLER_THREE_ZEROS),
+        2 => code.push(WECHSLER_TWO_ZEROS),
+        1 => code.push(WECHSLER_DIGITS[0] as char),
+        _ => (),
+    }
+}
+
+// Extended Wechsler format: the rows are cut into strips of five separated by `z`, every column of a strip
+// is written as a digit from 0 to v whose bits are its non-free cells from the top, trailing zero columns are left out
+pub fn encode_wechsler(cells: &UniversePlane) -> String {
+    let strip_codes: Vec<String> = (0..cells.nrows())
+        .step_by(WECHSLER_STRIP_HEIGHT)
+        .map(|strip_start| {
+            let strip_end = (strip_start + WECHSLER_STRIP_HEIGHT).min(cells.nrows());
+            let mut column_digits: Vec<usize> = (0..cells.ncols())
+                .map(|column_index| (strip_start..strip_end)
+                    .filter(|row_index| cells[(*row_index, column_index)] != CELL_IS_FREE)
+                    .map(|row_index| 1 << (row_index - strip_start))
+                    .sum())
+                .collect();
+            while column_digits.last() == Some(&0) {
+                column_digits.pop();
+            }
+
+            let mut strip_code = String::new();
+            let mut number_of_zeros = 0;
+            for column_digit in column_digits {
+                if column_digit == 0 {
+                    number_of_zeros += 1;
+                    continue;
+                }
+
+                encode_zero_columns(number_of_zeros, &mut strip_code);
+                number_of_zeros = 0;
+                strip_code.push(WECHSLER_DIGITS[column_digit] as char);
+            }
+
+            strip_code
+        })
+        .collect();
+
+    strip_codes.join(&WECHSLER_STRIP_SEPARATOR.to_string())
+}
+
+fn rotate_clockwise(cells: &UniversePlane) -> UniversePlane {
+    UniversePlane::from_fn(cells.ncols(), cells.nrows(), |row_index, column_index| cells[(cells.nrows() - 1 - column_index, row_index)])
+}
+
+// Shortest code of all the rotations and reflections of all the phases, the first in alphabetical order among equally short ones
+pub fn get_canonical_wechsler(phases: &[UniversePlane]) -> String {
+    phases
+        .iter()
+        .flat_map(|phase| {
+            let mut orientations = Vec::with_capacity(8);
+            let mut orientation = phase.clone();
+            for _ in 0..4 {
+                orientations.push(orientation.transpose());
+                orientation = rotate_clockwise(&orientation);
+                orientations.push(orientation.clone());
+            }
+
+            orientations
+        })
+        .map(|orientation| encode_wechsler(&orientation))
+        .min_by(|code, other_code| code.len().cmp(&other_code.len()).then_with(|| code.cmp(other_code)))
+        .unwrap_or_default()
+}
+
+// Code in the style of apgsearch: still lifes are `xs` with their population, oscillators `xp` and spaceships `xq`
+// with their period, followed by the canonical code of their cells
+pub fn get_object_code(cycle: Cycle, phases: &[UniversePlane]) -> String {
+    let (prefix, number) = if cycle.is_spaceship() {
+        (SPACESHIP_PREFIX, cycle.period())
+    } else if cycle.is_still_life() {
+        let population = phases.first().map_or(0, |phase| phase.iter().filter(|cell_state| **cell_state != CELL_IS_FREE).count());
+        (STILL_LIFE_PREFIX, population)
+    } else {
+        (OSCILLATOR_PREFIX, cycle.period())
+    };
+
+    format!("{}{}_{}", prefix, number, get_canonical_wechsler(phases))
+}
+
+pub fn is_still_life_code(object_code: &str) -> bool {
+    object_code.starts_with(STILL_LIFE_PREFIX)
+}
+
+pub fn get_object_name(object_code: &str) -> Option<&'static str> {
+    OBJECT_NAMES.iter().find(|(code, _)| *code == object_code).map(|(_, name)| *name)
+}
+
+// Number of objects of every code, objects which never settled are counted as unclassified
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Census {
+    object_counts: BTreeMap<String, usize>,
+}
+
+impl Census {
+    pub fn new() -> Self {
+        Census::default()
+    }
+
+    pub fn add(&mut self, object_code: &str) {
+        *self.object_counts.entry(String::from(object_code)).or_insert(0) += 1;
+    }
+
+    pub fn merge(&mut self, census: &Census) {
+        for (object_code, count) in census.object_counts.iter() {
+            *self.object_counts.entry(object_code.clone()).or_insert(0) += count;
+        }
+    }
+
+    pub fn count(&self, object_code: &str) -> usize {
+        self.object_counts.get(object_code).copied().unwrap_or(0)
+    }
+
+    pub fn number_of_objects(&self) -> usize {
+        self.object_counts.values().sum()
+    }
+
+    // Most common objects first, equally common ones in the order of their codes
+    pub fn iter(&self) -> impl Iterator<Item = (&str, usize)> {
+        let mut object_counts: Vec<(&str, usize)> = self.object_counts.iter().map(|(code, count)| (code.as_str(), *count)).collect();
+        object_counts.sort_by(|(code, count), (other_code, other_count)| other_count.cmp(count).then_with(|| code.cmp(other_code)));
+        object_counts.into_iter()
+    }
+
+    // Census on a single line, objects are given by their names when they have one
+    pub fn summary(&self) -> String {
+        if self.object_counts.is_empty() {
+            return String::from("no objects");
+        }
+
+        let object_counts: Vec<String> = self.iter()
+            .map(|(object_code, count)| format!("{} {}", count, get_object_name(object_code).unwrap_or(object_code)))
+            .collect();
+        object_counts.join(", ")
+    }
+}
+
+impl fmt::Display for Census {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for (object_code, count) in self.iter() {
+            match get_object_name(object_code) {
+                Some(object_name) => writeln!(formatter, "{} {} ({})", count, object_code, object_name)?,
+                None => writeln!(formatter, "{} {}", count, object_code)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::dmatrix;
+
+    #[test]
+    fn wechsler_code_shortens_zero_columns() {
+        assert_eq!(encode_wechsler(&dmatrix![1, 1; 1, 1]), "33");
+        assert_eq!(encode_wechsler(&dmatrix![1, 0, 0, 1, 0, 0, 0, 0, 1, 0]), "1w1y01");
+        assert_eq!(encode_wechsler(&dmatrix![1; 0; 0; 0; 0; 0; 1]), "1z2");
+        assert_eq!(get_canonical_wechsler(&[dmatrix![1, 1, 1]]), "7");
+    }
+
+    #[test]
+    fn objects_are_whole_across_joined_edges() {
+        let mut plane = UniversePlane::from_element(10, 10, CELL_IS_FREE);
+        for position in [(9, 9), (0, 9), (9, 0), (0, 0), (5, 5), (5, 6)] {
+            plane[position] = CELL_IS_POPULATED;
+        }
+
+        let objects = segment_objects(&plane, Topology::Torus, 2);
+        assert_eq!(objects.iter().map(|object| object.population()).collect::<Vec<_>>(), vec![4, 2]);
+        assert_eq!(objects[0].cells(), &dmatrix![1, 1; 1, 1]);
+        assert_eq!(segment_objects(&plane, Topology::Plane, 2).len(), 5);
+        assert_eq!(find_object(&plane, Topology::Torus, (5, 6), 2).map(|object| object.population()), Some(2));
+        assert_eq!(find_object(&plane, Topology::Torus, (4, 4), 2), None);
+    }
+
+    #[test]
+    fn census_counts_most_common_first() {
+        let mut census = Census::new();
+        for object_code in ["xp2_7", "xs4_33", "xs4_33", "xs6_696"] {
+            census.add(object_code);
+        }
+
+        assert_eq!(census.number_of_objects(), 4);
+        assert_eq!(census.count("xs4_33"), 2);
+        assert_eq!(census.count("xq4_153"), 0);
+        assert_eq!(census.to_string(), "2 xs4_33 (block)\n1 xp2_7 (blinker)\n1 xs6_696 (beehive)\n");
+        assert_eq!(census.summary(), "2 block, 1 blinker, 1 beehive");
+        assert_eq!(Census::new().summary(), "no objects");
+    }
+}
@@ -3,8 +3,8 @@ use specs::{Component, VecStorage, World, WorldExt};
 #[derive(Debug, Component)]
 #[storage(VecStorage)]
 pub struct Position {
-    pub x: u8,
-    pub y: u8,
+    pub x: usize,
+    pub y: usize,
 }
 
 #[derive(Component)]
@@ -1,7 +1,14 @@
 pub const TILE_WIDTH: f32 = 10.0;
+pub const MAX_WINDOW_WIDTH: f32 = 1200.0;
+pub const MAX_WINDOW_HEIGHT: f32 = 900.0;
 pub const UNIVERSE_WIDTH: usize = 50;
 pub const UNIVERSE_HEIGHT: usize = 50;
-pub const RULE_KERNEL_WIDTH: usize = 3;
-pub const RULE_KERNEL_HEIGHT: usize = 3;
+pub const UNIVERSE_DEPTH: usize = 16;
 pub const ALIVE_CELL_TILE_PATH: &str = "/images/alive_cell.png";
 pub const DESIRED_FPS: u32 = 15;
+pub const DEFAULT_HISTORY_CAPACITY: usize = 500;
+pub const TIMELINE_HEIGHT: f32 = 16.0;
+pub const SPARKLINE_LENGTH: usize = 200;
+pub const SPARKLINE_WIDTH: f32 = 200.0;
+pub const SPARKLINE_HEIGHT: f32 = 40.0;
+pub const INTERACTIVE_PREDECESSOR_SEARCH_STEP_LIMIT: u64 = 100_000;
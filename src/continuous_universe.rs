@@ -0,0 +1,440 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use nalgebra::DMatrix;
+use rand::distributions::Uniform;
+use crate::convolution::*;
+use crate::topology::*;
+
+pub type ContinuousPlane = DMatrix<f64>;
+
+pub const CELL_IS_FULL: f64 = 1.0;
+pub const CELL_IS_EMPTY: f64 = 0.0;
+
+const CONTINUOUS_RULE_PARTS_SEPARATOR: char = ';';
+const CONTINUOUS_RULE_VALUE_SEPARATOR: char = '=';
+const PEAKS_SEPARATOR: char = ',';
+const FRACTION_SEPARATOR: char = '/';
+const RANGE_KEY: &str = "R";
+const TIME_RESOLUTION_KEY: &str = "T";
+const GROWTH_MEAN_KEY: &str = "m";
+const GROWTH_DEVIATION_KEY: &str = "s";
+const PEAKS_KEY: &str = "b";
+
+// Parameters of Orbium, the glider of Lenia
+const ORBIUM_RANGE: usize = 13;
+const ORBIUM_TIME_RESOLUTION: u32 = 10;
+const ORBIUM_GROWTH_MEAN: f64 = 0.15;
+const ORBIUM_GROWTH_DEVIATION: f64 = 0.015;
+
+// Sharpness of the exponential bump which makes up each ring of the kernel
+const KERNEL_CORE_SHARPNESS: f64 = 4.0;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContinuousRuleParseError {
+    MissingParameter(&'static str),
+    InvalidParameter(String),
+    UnknownParameter(String),
+}
+
+impl fmt::Display for ContinuousRuleParseError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ContinuousRuleParseError::MissingParameter(key) => write!(formatter, "parameter '{}' is missing", key),
+            ContinuousRuleParseError::InvalidParameter(parameter) => write!(formatter, "parameter '{}' is invalid", parameter),
+            ContinuousRuleParseError::UnknownParameter(parameter) => write!(formatter, "parameter '{}' is unknown", parameter),
+        }
+    }
+}
+
+impl Error for ContinuousRuleParseError {}
+
+// Lenia rule: neighbourhood of the cell is the weighted average over concentric rings within the range,
+// which the gaussian growth function turns into the change of the cell value over one time step
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContinuousRule {
+    range: usize,
+    time_resolution: u32,
+    growth_mean: f64,
+    growth_deviation: f64,
+    peaks: Vec<f64>,
+}
+
+impl ContinuousRule {
+    // Each peak is the height of the ring, from the innermost one to the outermost one
+    pub fn new(range: usize, time_resolution: u32, growth_mean: f64, growth_deviation: f64, peaks: Vec<f64>) -> Self {
+        if range == 0 || time_resolution == 0 {
+            panic!("'ContinuousRule' expects a positive range and time resolution, received {} and {}.", range, time_resolution);
+        }
+
+        if growth_deviation <= 0.0 {
+            panic!("'ContinuousRule' expects a positive growth deviation, received {}.", growth_deviation);
+        }
+
+        if peaks.is_empty() || peaks.iter().any(|peak| !(0.0..=1.0).contains(peak)) || peaks.iter().all(|peak| *peak == 0.0) {
+            panic!("'ContinuousRule' expects peaks within [0, 1] with at least one non-zero, received {:?}.", peaks);
+        }
+
+        ContinuousRule { range, time_resolution, growth_mean, growth_deviation, peaks }
+    }
+
+    pub fn orbium() -> Self {
+        ContinuousRule::new(ORBIUM_RANGE, ORBIUM_TIME_RESOLUTION, ORBIUM_GROWTH_MEAN, ORBIUM_GROWTH_DEVIATION, vec![1.0])
+    }
+
+    pub fn range(&self) -> usize {
+        self.range
+    }
+
+    pub fn time_step(&self) -> f64 {
+        1.0 / (self.time_resolution as f64)
+    }
+
+    fn get_kernel_core(radius: f64) -> f64 {
+        if radius <= 0.0 || radius >= 1.0 {
+            return 0.0;
+        }
+
+        (KERNEL_CORE_SHARPNESS - 1.0 / (radius * (1.0 - radius))).exp()
+    }
+
+    // Rings split the range evenly, the kernel is normalised so that the neighbourhood stays within [0, 1]
+    pub fn kernel(&self) -> DMatrix<f64> {
+        let kernel_side = 2 * self.range + 1;
+        let number_of_rings = self.peaks.len() as f64;
+
+        let kernel = DMatrix::from_fn(kernel_side, kernel_side, |row_index, column_index| {
+            let row_offset = row_index as f64 - self.range as f64;
+            let column_offset = column_index as f64 - self.range as f64;
+            let distance = (row_offset * row_offset + column_offset * column_offset).sqrt() / (self.range as f64);
+            if distance >= 1.0 {
+                return 0.0;
+            }
+
+            let ring_position = distance * number_of_rings;
+            let peak = self.peaks[(ring_position as usize).min(self.peaks.len() - 1)];
+            peak * ContinuousRule::get_kernel_core(ring_position.fract())
+        });
+
+        let kernel_sum = kernel.sum();
+        kernel / kernel_sum
+    }
+
+    // Gaussian bell which grows cells of neighbourhood close to the mean and shrinks the others
+    pub fn get_growth(&self, neighbourhood: f64) -> f64 {
+        let deviation = (neighbourhood - self.growth_mean) / self.growth_deviation;
+        2.0 * (-deviation * deviation / 2.0).exp() - 1.0
+    }
+}
+
+impl Default for ContinuousRule {
+    fn default() -> Self {
+        ContinuousRule::orbium()
+    }
+}
+
+fn parse_number<T: FromStr>(parameter: &str, value: &str) -> Result<T, ContinuousRuleParseError> {
+    value.parse::<T>().map_err(|_| ContinuousRuleParseError::InvalidParameter(String::from(parameter)))
+}
+
+// Peaks are either decimal numbers or fractions like `1/3`
+fn parse_peak(parameter: &str, peak: &str) -> Result<f64, ContinuousRuleParseError> {
+    match peak.split_once(FRACTION_SEPARATOR) {
+        Some((numerator, denominator)) => {
+            let denominator: f64 = parse_number(parameter, denominator)?;
+            if denominator == 0.0 {
+                return Err(ContinuousRuleParseError::InvalidParameter(String::from(parameter)));
+            }
+
+            Ok(parse_number::<f64>(parameter, numerator)? / denominator)
+        }
+        None => parse_number(parameter, peak),
+    }
+}
+
+// Parse the rule written like Lenia does, e.g. `R=13;T=10;m=0.15;s=0.015;b=1`
+impl FromStr for ContinuousRule {
+    type Err = ContinuousRuleParseError;
+
+    fn from_str(rulestring: &str) -> Result<Self, Self::Err> {
+        let (mut range, mut time_resolution, mut growth_mean, mut growth_deviation, mut peaks) = (None, None, None, None, None);
+
+        for parameter in rulestring.trim().split(CONTINUOUS_RULE_PARTS_SEPARATOR).filter(|parameter| !parameter.trim().is_empty()) {
+            let (key, value) = parameter
+                .split_once(CONTINUOUS_RULE_VALUE_SEPARATOR)
+                .ok_or_else(|| ContinuousRuleParseError::InvalidParameter(String::from(parameter)))?;
+
+            match key.trim() {
+                RANGE_KEY => range = Some(parse_number::<usize>(parameter, value.trim())?),
+                TIME_RESOLUTION_KEY => time_resolution = Some(parse_number::<u32>(parameter, value.trim())?),
+                GROWTH_MEAN_KEY => growth_mean = Some(parse_number::<f64>(parameter, value.trim())?),
+                GROWTH_DEVIATION_KEY => growth_deviation = Some(parse_number::<f64>(parameter, value.trim())?),
+                PEAKS_KEY => peaks = Some(value.split(PEAKS_SEPARATOR).map(|peak| parse_peak(parameter, peak.trim())).collect::<Result<Vec<f64>, _>>()?),
+                _ => return Err(ContinuousRuleParseError::UnknownParameter(String::from(parameter))),
+            }
+        }
+
+        let range = range.ok_or(ContinuousRuleParseError::MissingParameter(RANGE_KEY))?;
+        let time_resolution = time_resolution.ok_or(ContinuousRuleParseError::MissingParameter(TIME_RESOLUTION_KEY))?;
+        let growth_mean = growth_mean.ok_or(ContinuousRuleParseError::MissingParameter(GROWTH_MEAN_KEY))?;
+        let growth_deviation = growth_deviation.ok_or(ContinuousRuleParseError::MissingParameter(GROWTH_DEVIATION_KEY))?;
+        let peaks = peaks.unwrap_or_else(|| vec![1.0]);
+
+        let is_valid = range > 0 && time_resolution > 0 && growth_deviation > 0.0
+            && peaks.iter().all(|peak| (0.0..=1.0).contains(peak)) && peaks.iter().any(|peak| *peak > 0.0);
+        if !is_valid {
+            return Err(ContinuousRuleParseError::InvalidParameter(String::from(rulestring)));
+        }
+
+        Ok(ContinuousRule::new(range, time_resolution, growth_mean, growth_deviation, peaks))
+    }
+}
+
+impl fmt::Display for ContinuousRule {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let peaks: Vec<String> = self.peaks.iter().map(|peak| peak.to_string()).collect();
+
+        write!(formatter, "{}={};{}={};{}={};{}={};{}={}",
+            RANGE_KEY, self.range,
+            TIME_RESOLUTION_KEY, self.time_resolution,
+            GROWTH_MEAN_KEY, self.growth_mean,
+            GROWTH_DEVIATION_KEY, self.growth_deviation,
+            PEAKS_KEY, peaks.join(&PEAKS_SEPARATOR.to_string()))
+    }
+}
+
+// Universe of cells holding values within [0, 1] which evolve in small time steps
+pub struct ContinuousUniverse {
+    universe_height: usize,
+    universe_width: usize,
+    topology: Topology,
+    rule: ContinuousRule,
+    rule_kernel: DMatrix<f64>,
+    next_generation: ContinuousPlane,
+}
+
+impl ContinuousUniverse {
+    fn from_initial_generation(topology: Topology, initial_generation: ContinuousPlane) -> Self {
+        let (universe_height, universe_width) = initial_generation.shape();
+        if universe_width == 0 || universe_height == 0 {
+            panic!("'ContinuousUniverse' expects non-zero dimensions, received {}x{}.", universe_width, universe_height);
+        }
+
+        let rule = ContinuousRule::default();
+
+        ContinuousUniverse {
+            universe_height,
+            universe_width,
+            topology,
+            rule_kernel: rule.kernel(),
+            rule,
+            next_generation: initial_generation,
+        }
+    }
+
+    pub fn new(universe_width: usize, universe_height: usize, topology: Topology) -> Self {
+        ContinuousUniverse::from_initial_generation(topology, ContinuousPlane::from_element(universe_height, universe_width, CELL_IS_EMPTY))
+    }
+
+    pub fn new_random(universe_width: usize, universe_height: usize, topology: Topology) -> Self {
+        let mut random_generator = rand::thread_rng();
+        let uniform_range = Uniform::new_inclusive(CELL_IS_EMPTY, CELL_IS_FULL);
+        let initial_generation = ContinuousPlane::from_distribution(universe_height, universe_width, &uniform_range, &mut random_generator);
+
+        ContinuousUniverse::from_initial_generation(topology, initial_generation)
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn rule(&self) -> &ContinuousRule {
+        &self.rule
+    }
+
+    pub fn set_rule(&mut self, rule: ContinuousRule) {
+        self.rule_kernel = rule.kernel();
+        self.rule = rule;
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        (self.universe_height, self.universe_width)
+    }
+
+    fn get_plane_position(&self, cell_row: usize, cell_column: usize) -> (usize, usize) {
+        if cell_row >= self.universe_height || cell_column >= self.universe_width {
+            panic!("Cell ({}, {}) is out of the universe of shape {:?}.", cell_row, cell_column, self.shape());
+        }
+
+        (cell_row, cell_column)
+    }
+
+    pub fn populate_cell(&mut self, cell_row: usize, cell_column: usize) {
+        self.set_cell_value(cell_row, cell_column, CELL_IS_FULL);
+    }
+
+    pub fn free_cell(&mut self, cell_row: usize, cell_column: usize) {
+        self.set_cell_value(cell_row, cell_column, CELL_IS_EMPTY);
+    }
+
+    pub fn set_cell_value(&mut self, cell_row: usize, cell_column: usize, cell_value: f64) {
+        if !(CELL_IS_EMPTY..=CELL_IS_FULL).contains(&cell_value) {
+            panic!("Cell value {} is out of [{}, {}].", cell_value, CELL_IS_EMPTY, CELL_IS_FULL);
+        }
+
+        let position = self.get_plane_position(cell_row, cell_column);
+        self.next_generation[position] = cell_value;
+    }
+
+    pub fn get_cell_value(&self, cell_row: usize, cell_column: usize) -> f64 {
+        let position = self.get_plane_position(cell_row, cell_column);
+        self.next_generation[position]
+    }
+
+    // Sum of all cell values, which Lenia calls the mass
+    pub fn get_mass(&self) -> f64 {
+        self.next_generation.sum()
+    }
+
+    pub fn next_generation(&mut self) {
+        let neighbourhoods = correlate_fastest(&self.next_generation, &self.rule_kernel, ConvolutionMode::Same, &self.topology);
+
+        let rule = &self.rule;
+        let time_step = rule.time_step();
+        self.next_generation.zip_apply(&neighbourhoods, |cell_value, neighbourhood| {
+            *cell_value = (*cell_value + time_step * rule.get_growth(neighbourhood)).clamp(CELL_IS_EMPTY, CELL_IS_FULL);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORBIUM_CELLS: [[f64; 20]; 20] = [
+        [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.1, 0.14, 0.1, 0.0, 0.0, 0.03, 0.03, 0.0, 0.0, 0.3, 0.0, 0.0, 0.0, 0.0],
+        [0.0, 0.0, 0.0, 0.0, 0.0, 0.08, 0.24, 0.3, 0.3, 0.18, 0.14, 0.15, 0.16, 0.15, 0.09, 0.2, 0.0, 0.0, 0.0, 0.0],
+        [0.0, 0.0, 0.0, 0.0, 0.0, 0.15, 0.34, 0.44, 0.46, 0.38, 0.18, 0.14, 0.11, 0.13, 0.19, 0.18, 0.45, 0.0, 0.0, 0.0],
+        [0.0, 0.0, 0.0, 0.0, 0.06, 0.13, 0.39, 0.5, 0.5, 0.37, 0.06, 0.0, 0.0, 0.0, 0.02, 0.16, 0.68, 0.0, 0.0, 0.0],
+        [0.0, 0.0, 0.0, 0.11, 0.17, 0.17, 0.33, 0.4, 0.38, 0.28, 0.14, 0.0, 0.0, 0.0, 0.0, 0.0, 0.18, 0.42, 0.0, 0.0],
+        [0.0, 0.0, 0.09, 0.18, 0.13, 0.06, 0.08, 0.26, 0.32, 0.32, 0.27, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.82, 0.0, 0.0],
+        [0.27, 0.0, 0.16, 0.12, 0.0, 0.0, 0.0, 0.25, 0.38, 0.44, 0.45, 0.34, 0.0, 0.0, 0.0, 0.0, 0.0, 0.22, 0.17, 0.0],
+        [0.0, 0.07, 0.2, 0.02, 0.0, 0.0, 0.0, 0.31, 0.48, 0.57, 0.6, 0.57, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.49, 0.0],
+        [0.0, 0.59, 0.19, 0.0, 0.0, 0.0, 0.0, 0.2, 0.57, 0.69, 0.76, 0.76, 0.49, 0.0, 0.0, 0.0, 0.0, 0.0, 0.36, 0.0],
+        [0.0, 0.58, 0.19, 0.0, 0.0, 0.0, 0.0, 0.0, 0.67, 0.83, 0.9, 0.92, 0.87, 0.12, 0.0, 0.0, 0.0, 0.0, 0.22, 0.07],
+        [0.0, 0.0, 0.46, 0.0, 0.0, 0.0, 0.0, 0.0, 0.7, 0.93, 1.0, 1.0, 1.0, 0.61, 0.0, 0.0, 0.0, 0.0, 0.18, 0.11],
+        [0.0, 0.0, 0.82, 0.0, 0.0, 0.0, 0.0, 0.0, 0.47, 1.0, 1.0, 0.98, 1.0, 0.96, 0.27, 0.0, 0.0, 0.0, 0.19, 0.1],
+        [0.0, 0.0, 0.46, 0.0, 0.0, 0.0, 0.0, 0.0, 0.25, 1.0, 1.0, 0.84, 0.92, 0.97, 0.54, 0.14, 0.04, 0.1, 0.21, 0.05],
+        [0.0, 0.0, 0.0, 0.4, 0.0, 0.0, 0.0, 0.0, 0.09, 0.8, 1.0, 0.82, 0.8, 0.85, 0.63, 0.31, 0.18, 0.19, 0.2, 0.01],
+        [0.0, 0.0, 0.0, 0.36, 0.1, 0.0, 0.0, 0.0, 0.05, 0.54, 0.86, 0.79, 0.74, 0.72, 0.6, 0.39, 0.28, 0.24, 0.13, 0.0],
+        [0.0, 0.0, 0.0, 0.01, 0.3, 0.07, 0.0, 0.0, 0.08, 0.36, 0.64, 0.7, 0.64, 0.6, 0.51, 0.39, 0.29, 0.19, 0.04, 0.0],
+        [0.0, 0.0, 0.0, 0.0, 0.1, 0.24, 0.14, 0.1, 0.15, 0.29, 0.45, 0.53, 0.52, 0.46, 0.4, 0.31, 0.21, 0.08, 0.0, 0.0],
+        [0.0, 0.0, 0.0, 0.0, 0.0, 0.08, 0.21, 0.21, 0.22, 0.29, 0.36, 0.39, 0.37, 0.33, 0.26, 0.18, 0.09, 0.0, 0.0, 0.0],
+        [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.03, 0.13, 0.19, 0.22, 0.24, 0.24, 0.23, 0.18, 0.13, 0.05, 0.0, 0.0, 0.0, 0.0],
+        [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.02, 0.06, 0.08, 0.09, 0.07, 0.05, 0.01, 0.0, 0.0, 0.0, 0.0, 0.0],
+    ];
+
+    const UNIVERSE_SIDE: usize = 64;
+
+    fn create_orbium_universe() -> ContinuousUniverse {
+        let mut universe = ContinuousUniverse::new(UNIVERSE_SIDE, UNIVERSE_SIDE, Topology::Torus);
+        universe.set_rule(ContinuousRule::orbium());
+
+        let orbium_offset = (UNIVERSE_SIDE - ORBIUM_CELLS.len()) / 2;
+        for (row_index, orbium_row) in ORBIUM_CELLS.iter().enumerate() {
+            for (column_index, cell_value) in orbium_row.iter().enumerate() {
+                universe.set_cell_value(orbium_offset + row_index, orbium_offset + column_index, *cell_value);
+            }
+        }
+
+        universe
+    }
+
+    // Mass-weighted mean position of the cells, valid while the pattern doesn't cross the edges
+    fn get_centre_of_mass(universe: &ContinuousUniverse) -> (f64, f64) {
+        let (universe_height, universe_width) = universe.shape();
+        let mut centre_of_mass = (0.0, 0.0);
+
+        for row_index in 0..universe_height {
+            for column_index in 0..universe_width {
+                let cell_value = universe.get_cell_value(row_index, column_index);
+                centre_of_mass.0 += cell_value * row_index as f64;
+                centre_of_mass.1 += cell_value * column_index as f64;
+            }
+        }
+
+        (centre_of_mass.0 / universe.get_mass(), centre_of_mass.1 / universe.get_mass())
+    }
+
+    #[test]
+    fn kernel_is_normalised_ring() {
+        let kernel = ContinuousRule::orbium().kernel();
+        assert_eq!(kernel.shape(), (27, 27));
+        assert!((kernel.sum() - 1.0).abs() < 1e-12);
+
+        // Single ring peaks halfway to the range and vanishes in the centre and beyond the range
+        assert_eq!(kernel[(13, 13)], 0.0);
+        assert_eq!(kernel[(0, 0)], 0.0);
+        assert!(kernel[(13, 6)] > kernel[(13, 3)] && kernel[(13, 6)] > kernel[(13, 10)]);
+        assert_eq!(kernel, kernel.transpose());
+    }
+
+    #[test]
+    fn growth_is_gaussian_bell() {
+        let rule = ContinuousRule::orbium();
+        assert_eq!(rule.get_growth(0.15), 1.0);
+        assert!((rule.get_growth(0.15 + 0.015) - rule.get_growth(0.15 - 0.015)).abs() < 1e-12);
+        assert!(rule.get_growth(0.0) < -0.99);
+    }
+
+    #[test]
+    fn orbium_glides_without_losing_its_mass() {
+        let mut universe = create_orbium_universe();
+        let initial_mass = universe.get_mass();
+        let initial_centre_of_mass = get_centre_of_mass(&universe);
+
+        // Orbium crosses about a dozen cells in two time units, not reaching the edges yet
+        for _ in 0..20 {
+            universe.next_generation();
+        }
+
+        let centre_of_mass = get_centre_of_mass(&universe);
+        let displacement = (centre_of_mass.0 - initial_centre_of_mass.0).hypot(centre_of_mass.1 - initial_centre_of_mass.1);
+        assert!(displacement > 8.0, "Orbium moved by {} cells only.", displacement);
+
+        for _ in 20..300 {
+            universe.next_generation();
+
+            let mass = universe.get_mass();
+            assert!((mass - initial_mass).abs() < 0.1 * initial_mass, "Orbium mass changed from {} to {}.", initial_mass, mass);
+        }
+    }
+
+    #[test]
+    fn orbium_dies_out_with_too_high_growth_mean() {
+        let mut universe = create_orbium_universe();
+        universe.set_rule(ContinuousRule::new(13, 10, 0.35, 0.015, vec![1.0]));
+
+        for _ in 0..100 {
+            universe.next_generation();
+        }
+
+        assert!(universe.get_mass() < 1e-6);
+    }
+
+    #[test]
+    fn rule_is_parsed_from_lenia_notation() {
+        assert_eq!("R=13;T=10;m=0.15;s=0.015;b=1".parse::<ContinuousRule>(), Ok(ContinuousRule::orbium()));
+        assert_eq!(ContinuousRule::orbium().to_string(), "R=13;T=10;m=0.15;s=0.015;b=1");
+
+        let rule: ContinuousRule = "R=12;T=2;m=0.16;s=0.02;b=1,1/2".parse().unwrap();
+        assert_eq!(rule.peaks, vec![1.0, 0.5]);
+        assert_eq!(rule.time_step(), 0.5);
+        assert_eq!(rule.to_string().parse::<ContinuousRule>(), Ok(rule));
+
+        assert_eq!("R=13;T=10;m=0.15".parse::<ContinuousRule>(), Err(ContinuousRuleParseError::MissingParameter(GROWTH_DEVIATION_KEY)));
+        assert!("R=13;T=10;m=0.15;s=0.015;x=1".parse::<ContinuousRule>().is_err());
+        assert!("R=13;T=10;m=0.15;s=0;b=1".parse::<ContinuousRule>().is_err());
+        assert!("R=13;T=10;m=0.15;s=0.015;b=1/0".parse::<ContinuousRule>().is_err());
+    }
+}
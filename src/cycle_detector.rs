@@ -0,0 +1,238 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use crate::topology::*;
+use crate::universe::*;
+
+pub const DEFAULT_MAX_CYCLE_PERIOD: usize = 100;
+
+// Generation repeating itself after `period` generations, shifted by `displacement` rows and columns,
+// the first generation of the cycle is counted from the start of the detection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    period: usize,
+    displacement: (isize, isize),
+    start_generation: usize,
+}
+
+impl Cycle {
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    pub fn displacement(&self) -> (isize, isize) {
+        self.displacement
+    }
+
+    pub fn start_generation(&self) -> usize {
+        self.start_generation
+    }
+
+    pub fn is_still_life(&self) -> bool {
+        self.period == 1 && !self.is_spaceship()
+    }
+
+    pub fn is_spaceship(&self) -> bool {
+        self.displacement != (0, 0)
+    }
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "p{} ({}, {})", self.period, self.displacement.0, self.displacement.1)
+    }
+}
+
+// Populated part of a generation cut out of the plane, with the position of its upper left corner.
+// Generations of Margolus mode only repeat along with their block partition, so its offset is kept too
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct NormalisedGeneration {
+    hash: u64,
+    offset: (usize, usize),
+    block_partition_offset: usize,
+    cells: UniversePlane,
+}
+
+impl NormalisedGeneration {
+    fn is_repeated_by(&self, generation: &NormalisedGeneration) -> bool {
+        self.hash == generation.hash && self.block_partition_offset == generation.block_partition_offset && self.cells == generation.cells
+    }
+}
+
+// Find the first line and the number of lines which hold all the populated ones. Joined edges let the pattern
+// wrap around, so it starts right after the longest run of empty lines, the first one when several are equally long
+pub fn get_populated_span(is_line_populated: &[bool], is_joined: bool) -> (usize, usize) {
+    let number_of_lines = is_line_populated.len();
+    let populated_lines: Vec<usize> = (0..number_of_lines).filter(|line| is_line_populated[*line]).collect();
+    let (first_line, last_line) = match (populated_lines.first(), populated_lines.last()) {
+        (Some(first_line), Some(last_line)) => (*first_line, *last_line),
+        _ => return (0, 0),
+    };
+
+    if !is_joined {
+        return (first_line, last_line - first_line + 1);
+    }
+
+    let mut longest_gap = (first_line + number_of_lines - last_line - 1, first_line);
+    for (line, next_line) in populated_lines.iter().zip(populated_lines.iter().skip(1)) {
+        let gap = next_line - line - 1;
+        if gap > longest_gap.0 {
+            longest_gap = (gap, *next_line);
+        }
+    }
+
+    (longest_gap.1, number_of_lines - longest_gap.0)
+}
+
+// Cut the populated cells out of the plane, together with the position of their upper left corner
+pub fn crop_populated_cells(plane: &UniversePlane, topology: Topology) -> ((usize, usize), UniversePlane) {
+    let (plane_height, plane_width) = plane.shape();
+    let is_row_populated: Vec<bool> = plane.row_iter().map(|row| row.iter().any(|cell_state| *cell_state != CELL_IS_FREE)).collect();
+    let is_column_populated: Vec<bool> = plane.column_iter().map(|column| column.iter().any(|cell_state| *cell_state != CELL_IS_FREE)).collect();
+
+    let (first_row, number_of_rows) = get_populated_span(&is_row_populated, topology.is_vertically_joined());
+    let (first_column, number_of_columns) = get_populated_span(&is_column_populated, topology.is_horizontally_joined());
+    let cells = UniversePlane::from_fn(number_of_rows, number_of_columns, |row_index, column_index| {
+        plane[((first_row + row_index) % plane_height, (first_column + column_index) % plane_width)]
+    });
+
+    ((first_row, first_column), cells)
+}
+
+fn normalise_generation(plane: &UniversePlane, block_partition_offset: usize, topology: Topology) -> NormalisedGeneration {
+    let (offset, cells) = crop_populated_cells(plane, topology);
+
+    let mut hasher = DefaultHasher::new();
+    block_partition_offset.hash(&mut hasher);
+    cells.shape().hash(&mut hasher);
+    cells.as_slice().hash(&mut hasher);
+
+    NormalisedGeneration { hash: hasher.finish(), offset, block_partition_offset, cells }
+}
+
+// Shift along a joined axis is taken the short way around
+fn get_displacement(previous_offset: usize, offset: usize, number_of_lines: usize, is_joined: bool) -> isize {
+    let displacement = offset as isize - previous_offset as isize;
+    if !is_joined {
+        return displacement;
+    }
+
+    let displacement = displacement.rem_euclid(number_of_lines as isize);
+    if displacement > number_of_lines as isize / 2 { displacement - number_of_lines as isize } else { displacement }
+}
+
+// Keeps the hashes of the last generations up to the longest period looked for. Generations are compared with their
+// populated cells moved to the corner, so that spaceships are caught as well as oscillators. Patterns crossing
+// the twisted edges come back mirrored, they are only caught once they repeat without moving.
+// Generations have to follow from each other deterministically, repeats of random updates mean nothing
+#[derive(Debug, Clone)]
+pub struct CycleDetector {
+    topology: Topology,
+    max_period: usize,
+    plane_shape: (usize, usize),
+    generations: VecDeque<NormalisedGeneration>,
+    number_of_generations: usize,
+    cycle: Option<Cycle>,
+}
+
+impl CycleDetector {
+    pub fn new(max_period: usize, topology: Topology) -> Self {
+        if max_period == 0 {
+            panic!("'CycleDetector' expects a non-zero maximum period.");
+        }
+
+        CycleDetector {
+            topology,
+            max_period,
+            plane_shape: (0, 0),
+            generations: VecDeque::with_capacity(max_period + 1),
+            number_of_generations: 0,
+            cycle: None,
+        }
+    }
+
+    pub fn max_period(&self) -> usize {
+        self.max_period
+    }
+
+    // Generations observed since the detection started or since the last edit
+    pub fn number_of_generations(&self) -> usize {
+        self.number_of_generations
+    }
+
+    pub fn cycle(&self) -> Option<Cycle> {
+        self.cycle
+    }
+
+    pub fn reset(&mut self) {
+        self.generations.clear();
+        self.number_of_generations = 0;
+        self.cycle = None;
+    }
+
+    // Generation about to be stepped has to be the last one observed, otherwise it was edited
+    // and the detection starts again from it
+    pub fn follow(&mut self, plane: &UniversePlane, block_partition_offset: usize) {
+        let normalised_generation = normalise_generation(plane, block_partition_offset, self.topology);
+        if plane.shape() == self.plane_shape && self.generations.back() == Some(&normalised_generation) {
+            return;
+        }
+
+        self.reset();
+        self.plane_shape = plane.shape();
+        self.generations.push_back(normalised_generation);
+        self.number_of_generations = 1;
+    }
+
+    // Observe the next generation, the shortest period is found first and kept once the generations repeat
+    pub fn observe(&mut self, plane: &UniversePlane, block_partition_offset: usize) -> Option<Cycle> {
+        if plane.shape() != self.plane_shape {
+            self.follow(plane, block_partition_offset);
+            return None;
+        }
+
+        let normalised_generation = normalise_generation(plane, block_partition_offset, self.topology);
+        if self.cycle.is_none() {
+            let (plane_height, plane_width) = self.plane_shape;
+            self.cycle = self.generations
+                .iter()
+                .rev()
+                .position(|generation| generation.is_repeated_by(&normalised_generation))
+                .map(|generation_index| {
+                    let previous_offset = self.generations[self.generations.len() - 1 - generation_index].offset;
+                    Cycle {
+                        period: generation_index + 1,
+                        displacement: (
+                            get_displacement(previous_offset.0, normalised_generation.offset.0, plane_height, self.topology.is_vertically_joined()),
+                            get_displacement(previous_offset.1, normalised_generation.offset.1, plane_width, self.topology.is_horizontally_joined()),
+                        ),
+                        start_generation: self.number_of_generations - 1 - generation_index,
+                    }
+                });
+        }
+
+        if self.generations.len() == self.max_period {
+            self.generations.pop_front();
+        }
+
+        self.generations.push_back(normalised_generation);
+        self.number_of_generations += 1;
+        self.cycle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn populated_span_wraps_around_joined_edges() {
+        assert_eq!(get_populated_span(&[false, true, true, false, true, false], false), (1, 4));
+        assert_eq!(get_populated_span(&[true, false, false, false, true, true], true), (4, 3));
+        assert_eq!(get_populated_span(&[true, false, false, false, true, true], false), (0, 6));
+        assert_eq!(get_populated_span(&[false, false], true), (0, 0));
+        assert_eq!(get_displacement(9, 0, 10, true), 1);
+        assert_eq!(get_displacement(9, 0, 10, false), -9);
+    }
+}
@@ -0,0 +1,137 @@
+use std::fmt;
+use crate::cycle_detector::*;
+use crate::topology::*;
+use crate::universe::*;
+
+pub const GENERATION_STATS_CSV_HEADER: &str = "generation,population,births,deaths,changed_cells,density,first_row,first_column,number_of_rows,number_of_columns";
+
+// Smallest block of rows and columns holding all the non-free cells, it may wrap around the joined edges
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub first_row: usize,
+    pub first_column: usize,
+    pub number_of_rows: usize,
+    pub number_of_columns: usize,
+}
+
+impl BoundingBox {
+    pub fn from_plane(plane: &UniversePlane, topology: Topology) -> Option<Self> {
+        let is_row_populated: Vec<bool> = plane.row_iter().map(|row| row.iter().any(|cell_state| *cell_state != CELL_IS_FREE)).collect();
+        let is_column_populated: Vec<bool> = plane.column_iter().map(|column| column.iter().any(|cell_state| *cell_state != CELL_IS_FREE)).collect();
+
+        let (first_row, number_of_rows) = get_populated_span(&is_row_populated, topology.is_vertically_joined());
+        let (first_column, number_of_columns) = get_populated_span(&is_column_populated, topology.is_horizontally_joined());
+        (number_of_rows > 0).then_some(BoundingBox { first_row, first_column, number_of_rows, number_of_columns })
+    }
+}
+
+// What one step changed: cells which got non-free are births, cells which got free are deaths,
+// so decaying cells of Generations rules die only once they reach the free state
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GenerationStats {
+    generation: usize,
+    population: usize,
+    births: usize,
+    deaths: usize,
+    changed_cells: usize,
+    density: f64,
+    bounding_box: Option<BoundingBox>,
+}
+
+impl GenerationStats {
+    pub fn from_generations(generation: usize, previous_generation: &UniversePlane, next_generation: &UniversePlane, topology: Topology) -> Self {
+        if previous_generation.shape() != next_generation.shape() {
+            panic!("'GenerationStats' expects generations of the same shape, received {:?} and {:?}.", previous_generation.shape(), next_generation.shape());
+        }
+
+        let mut generation_stats = GenerationStats {
+            generation,
+            population: 0,
+            births: 0,
+            deaths: 0,
+            changed_cells: 0,
+            density: 0.0,
+            bounding_box: BoundingBox::from_plane(next_generation, topology),
+        };
+
+        for (previous_state, next_state) in previous_generation.iter().zip(next_generation.iter()) {
+            generation_stats.population += (*next_state != CELL_IS_FREE) as usize;
+            generation_stats.births += (*previous_state == CELL_IS_FREE && *next_state != CELL_IS_FREE) as usize;
+            generation_stats.deaths += (*previous_state != CELL_IS_FREE && *next_state == CELL_IS_FREE) as usize;
+            generation_stats.changed_cells += (previous_state != next_state) as usize;
+        }
+
+        generation_stats.density = generation_stats.population as f64 / next_generation.len() as f64;
+        generation_stats
+    }
+
+    // Counted from the generation at which the statistics started to be collected
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn population(&self) -> usize {
+        self.population
+    }
+
+    pub fn births(&self) -> usize {
+        self.births
+    }
+
+    pub fn deaths(&self) -> usize {
+        self.deaths
+    }
+
+    pub fn changed_cells(&self) -> usize {
+        self.changed_cells
+    }
+
+    pub fn density(&self) -> f64 {
+        self.density
+    }
+
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        self.bounding_box
+    }
+}
+
+// Record in the order of `GENERATION_STATS_CSV_HEADER`, the bounding box of an empty universe is left empty
+impl fmt::Display for GenerationStats {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{},{},{},{},{},{}", self.generation, self.population, self.births, self.deaths, self.changed_cells, self.density)?;
+        match self.bounding_box {
+            Some(bounding_box) => write!(formatter, ",{},{},{},{}", bounding_box.first_row, bounding_box.first_column, bounding_box.number_of_rows, bounding_box.number_of_columns),
+            None => write!(formatter, ",,,,"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::dmatrix;
+
+    #[test]
+    fn stats_count_changes_between_generations() {
+        let previous_generation: UniversePlane = dmatrix![0, 1, 0, 0; 0, 1, 0, 0; 0, 1, 0, 0; 0, 0, 0, 0];
+        let next_generation: UniversePlane = dmatrix![0, 0, 0, 0; 1, 1, 1, 0; 0, 0, 0, 0; 0, 0, 0, 0];
+        let generation_stats = GenerationStats::from_generations(3, &previous_generation, &next_generation, Topology::Torus);
+
+        assert_eq!((generation_stats.population(), generation_stats.births(), generation_stats.deaths(), generation_stats.changed_cells()), (3, 2, 2, 4));
+        assert_eq!(generation_stats.density(), 0.1875);
+        assert_eq!(generation_stats.bounding_box(), Some(BoundingBox { first_row: 1, first_column: 0, number_of_rows: 1, number_of_columns: 3 }));
+        assert_eq!(generation_stats.to_string(), "3,3,2,2,4,0.1875,1,0,1,3");
+
+        let empty_generation = UniversePlane::from_element(4, 4, CELL_IS_FREE);
+        let generation_stats = GenerationStats::from_generations(4, &next_generation, &empty_generation, Topology::Torus);
+        assert_eq!(generation_stats.to_string(), "4,0,0,3,3,0,,,,");
+        assert_eq!(GENERATION_STATS_CSV_HEADER.split(',').count(), generation_stats.to_string().split(',').count());
+    }
+
+    #[test]
+    fn bounding_box_wraps_around_joined_edges() {
+        let plane: UniversePlane = dmatrix![1, 0, 0, 1; 0, 0, 0, 0; 0, 0, 0, 0; 1, 0, 0, 0];
+        assert_eq!(BoundingBox::from_plane(&plane, Topology::Torus), Some(BoundingBox { first_row: 3, first_column: 3, number_of_rows: 2, number_of_columns: 2 }));
+        assert_eq!(BoundingBox::from_plane(&plane, Topology::Plane), Some(BoundingBox { first_row: 0, first_column: 0, number_of_rows: 4, number_of_columns: 4 }));
+    }
+}
@@ -0,0 +1,237 @@
+use nalgebra::DMatrix;
+use crate::topology::*;
+
+const HEXAGONAL_NOTATION: char = 'H';
+const TRIANGULAR_NOTATION: char = 'L';
+
+// Hexagons are squeezed to the height of 4/3 tiles, so that their rows are one tile apart
+const HEXAGON_VERTEX_OFFSETS: [(f32, f32); 6] = [(0.0, -2.0 / 3.0), (0.5, -1.0 / 3.0), (0.5, 1.0 / 3.0), (0.0, 2.0 / 3.0), (-0.5, 1.0 / 3.0), (-0.5, -1.0 / 3.0)];
+
+// Shape of the cells tiling the universe.
+//
+// Hexagonal grid is stored in offset coordinates: odd rows are shifted right by half a cell,
+// so that each cell touches two cells of the rows above and below it besides the west and east ones.
+// Triangular grid alternates triangles pointing up and down, the cell (0, 0) points up,
+// and each cell touches twelve cells by the edges or the vertices
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Grid {
+    #[default]
+    Square,
+    Hexagonal,
+    Triangular,
+}
+
+impl Grid {
+    pub fn number_of_neighbours(&self) -> usize {
+        match self {
+            Grid::Square => 8,
+            Grid::Hexagonal => 6,
+            Grid::Triangular => 12,
+        }
+    }
+
+    // Cells of the same parity see their neighbours at the same offsets
+    pub fn number_of_parities(&self) -> usize {
+        match self {
+            Grid::Square => 1,
+            Grid::Hexagonal | Grid::Triangular => 2,
+        }
+    }
+
+    pub fn get_cell_parity(&self, row: usize, column: usize) -> usize {
+        match self {
+            Grid::Square => 0,
+            Grid::Hexagonal => row % 2,
+            Grid::Triangular => (row + column) % 2,
+        }
+    }
+
+    // One kernel per parity weighting each neighbour by one
+    pub fn kernels(&self, is_middle_cell_included: bool) -> Vec<DMatrix<u32>> {
+        let middle = is_middle_cell_included as u32;
+
+        match self {
+            Grid::Square => vec![DMatrix::from_row_slice(3, 3, &[
+                1, 1,      1,
+                1, middle, 1,
+                1, 1,      1,
+            ])],
+            Grid::Hexagonal => vec![
+                DMatrix::from_row_slice(3, 3, &[
+                    1, 1,      0,
+                    1, middle, 1,
+                    1, 1,      0,
+                ]),
+                DMatrix::from_row_slice(3, 3, &[
+                    0, 1,      1,
+                    1, middle, 1,
+                    0, 1,      1,
+                ]),
+            ],
+            Grid::Triangular => vec![
+                DMatrix::from_row_slice(3, 5, &[
+                    0, 1, 1,      1, 0,
+                    1, 1, middle, 1, 1,
+                    1, 1, 1,      1, 1,
+                ]),
+                DMatrix::from_row_slice(3, 5, &[
+                    1, 1, 1,      1, 1,
+                    1, 1, middle, 1, 1,
+                    0, 1, 1,      1, 0,
+                ]),
+            ],
+        }
+    }
+
+    // Joined edges must not break the alternation of parities, so they join rows (or columns)
+    // of different parities, twisted edges would mirror the cells which isn't supported at all
+    pub fn is_compatible_with(&self, topology: Topology, universe_shape: (usize, usize)) -> bool {
+        if *self == Grid::Square {
+            return true;
+        }
+
+        let (universe_height, universe_width) = universe_shape;
+        let is_vertically_joined = topology.is_vertically_joined();
+        let is_horizontally_joined = topology.is_horizontally_joined();
+
+        match topology {
+            Topology::KleinBottle | Topology::CrossSurface => false,
+            _ => match self {
+                Grid::Hexagonal => !is_vertically_joined || universe_height % 2 == 0,
+                _ => (!is_vertically_joined || universe_height % 2 == 0) && (!is_horizontally_joined || universe_width % 2 == 0),
+            },
+        }
+    }
+
+    pub fn notation(&self) -> Option<char> {
+        match self {
+            Grid::Square => None,
+            Grid::Hexagonal => Some(HEXAGONAL_NOTATION),
+            Grid::Triangular => Some(TRIANGULAR_NOTATION),
+        }
+    }
+
+    pub fn from_notation(notation: char) -> Option<Self> {
+        match notation {
+            HEXAGONAL_NOTATION => Some(Grid::Hexagonal),
+            TRIANGULAR_NOTATION => Some(Grid::Triangular),
+            _ => None,
+        }
+    }
+
+    // Width and height of the universe drawn on the screen, measured in tiles
+    pub fn get_plane_size(&self, universe_shape: (usize, usize)) -> (f32, f32) {
+        let (universe_height, universe_width) = universe_shape;
+        let (universe_height, universe_width) = (universe_height as f32, universe_width as f32);
+
+        match self {
+            Grid::Square => (universe_width, universe_height),
+            Grid::Hexagonal => (universe_width + 0.5, universe_height + 1.0 / 3.0),
+            Grid::Triangular => ((universe_width + 1.0) / 2.0, universe_height),
+        }
+    }
+
+    // Vertices of the cell drawn on the screen, measured in tiles
+    pub fn get_cell_polygon(&self, row: usize, column: usize) -> Vec<(f32, f32)> {
+        let (row, column) = (row as f32, column as f32);
+
+        match self {
+            Grid::Square => vec![(column, row), (column + 1.0, row), (column + 1.0, row + 1.0), (column, row + 1.0)],
+            Grid::Hexagonal => {
+                let centre_x = column + 0.5 + 0.5 * (row % 2.0);
+                let centre_y = row + 2.0 / 3.0;
+                HEXAGON_VERTEX_OFFSETS.iter().map(|(x_offset, y_offset)| (centre_x + x_offset, centre_y + y_offset)).collect()
+            }
+            Grid::Triangular => {
+                let left_x = column / 2.0;
+                if self.get_cell_parity(row as usize, column as usize) == 0 {
+                    vec![(left_x, row + 1.0), (left_x + 0.5, row), (left_x + 1.0, row + 1.0)]
+                } else {
+                    vec![(left_x, row), (left_x + 1.0, row), (left_x + 0.5, row + 1.0)]
+                }
+            }
+        }
+    }
+
+    // Cell under the point on the screen measured in tiles, approximated by the bounding boxes of the cells
+    pub fn get_cell_at(&self, x: f32, y: f32) -> (isize, isize) {
+        let row = y.floor() as isize;
+
+        match self {
+            Grid::Square => (row, x.floor() as isize),
+            Grid::Hexagonal => (row, (x - 0.5 * (row.rem_euclid(2) as f32)).floor() as isize),
+            Grid::Triangular => (row, (2.0 * x - 0.5).floor() as isize),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kernels_count_grid_neighbours() {
+        for grid in [Grid::Square, Grid::Hexagonal, Grid::Triangular] {
+            let kernels = grid.kernels(false);
+            assert_eq!(kernels.len(), grid.number_of_parities());
+
+            for kernel in kernels {
+                assert_eq!(kernel.sum() as usize, grid.number_of_neighbours());
+            }
+        }
+    }
+
+    // Neighbourhood is symmetric: if one cell sees another, the other one sees it too
+    #[test]
+    fn neighbours_see_each_other() {
+        for grid in [Grid::Hexagonal, Grid::Triangular] {
+            let kernels = grid.kernels(false);
+            let sees = |row: usize, column: usize, other_row: usize, other_column: usize| {
+                let kernel = &kernels[grid.get_cell_parity(row, column)];
+                let (half_height, half_width) = (kernel.nrows() / 2, kernel.ncols() / 2);
+                let row_index = (other_row + half_height).checked_sub(row);
+                let column_index = (other_column + half_width).checked_sub(column);
+
+                match (row_index, column_index) {
+                    (Some(row_index), Some(column_index)) if row_index < kernel.nrows() && column_index < kernel.ncols() => kernel[(row_index, column_index)] == 1,
+                    _ => false,
+                }
+            };
+
+            for row in 2..6 {
+                for column in 2..6 {
+                    for other_row in 0..8 {
+                        for other_column in 0..8 {
+                            assert_eq!(sees(row, column, other_row, other_column), sees(other_row, other_column, row, column));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn torus_needs_even_sides_for_alternating_grids() {
+        assert!(Grid::Square.is_compatible_with(Topology::KleinBottle, (3, 3)));
+        assert!(Grid::Hexagonal.is_compatible_with(Topology::Torus, (4, 3)));
+        assert!(!Grid::Hexagonal.is_compatible_with(Topology::Torus, (3, 4)));
+        assert!(Grid::Hexagonal.is_compatible_with(Topology::Plane, (3, 3)));
+        assert!(!Grid::Triangular.is_compatible_with(Topology::Torus, (4, 3)));
+        assert!(Grid::Triangular.is_compatible_with(Topology::VerticalCylinder, (4, 3)));
+        assert!(!Grid::Triangular.is_compatible_with(Topology::CrossSurface, (4, 4)));
+    }
+
+    #[test]
+    fn cell_under_polygon_centre_is_found() {
+        for grid in [Grid::Square, Grid::Hexagonal, Grid::Triangular] {
+            for row in 0..4 {
+                for column in 0..4 {
+                    let polygon = grid.get_cell_polygon(row, column);
+                    let centre_x = polygon.iter().map(|(x, _)| x).sum::<f32>() / polygon.len() as f32;
+                    let centre_y = polygon.iter().map(|(_, y)| y).sum::<f32>() / polygon.len() as f32;
+                    assert_eq!(grid.get_cell_at(centre_x, centre_y), (row as isize, column as isize));
+                }
+            }
+        }
+    }
+}
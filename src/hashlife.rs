@@ -0,0 +1,669 @@
+use std::collections::HashMap;
+use crate::infinite_universe::*;
+use crate::rule::*;
+use crate::universe::*;
+
+// Nodes are kept in the cache until it grows beyond this number, then unreachable ones are dropped
+pub const DEFAULT_MAX_NUMBER_OF_NODES: usize = 1 << 21;
+
+// Base case is the 4x4 node, the largest one is limited by 64-bit cell coordinates
+const MIN_LEVEL: u8 = 2;
+const MAX_LEVEL: u8 = 62;
+
+const FREE_LEAF: NodeId = 0;
+const POPULATED_LEAF: NodeId = 1;
+
+const NORTH_WEST: usize = 0;
+const NORTH_EAST: usize = 1;
+const SOUTH_WEST: usize = 2;
+const SOUTH_EAST: usize = 3;
+
+type NodeId = u32;
+type Children = [NodeId; 4];
+
+// Square of 2^level cells made of four squares of the previous level,
+// leaves (level 0) are single cells
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    level: u8,
+    children: Children,
+    population: u64,
+}
+
+// Universe without borders which memoises the evolution of every distinct square of cells
+// in a quadtree, so repetitive patterns can be advanced by astronomical numbers of generations.
+// Only two-state Life-like rules are supported
+pub struct HashLifeUniverse {
+    rule: Rule,
+    nodes: Vec<Node>,
+    node_ids: HashMap<Children, NodeId>,
+    empty_node_ids: Vec<NodeId>,
+    results: HashMap<(NodeId, u8), NodeId>,
+    root: NodeId,
+    root_origin: (i64, i64),
+    generation: u64,
+    max_number_of_nodes: usize,
+}
+
+impl HashLifeUniverse {
+    pub fn new() -> Self {
+        let mut universe = HashLifeUniverse {
+            rule: Rule::default(),
+            nodes: Vec::new(),
+            node_ids: HashMap::new(),
+            empty_node_ids: Vec::new(),
+            results: HashMap::new(),
+            root: FREE_LEAF,
+            root_origin: (0, 0),
+            generation: 0,
+            max_number_of_nodes: DEFAULT_MAX_NUMBER_OF_NODES,
+        };
+
+        universe.clear_nodes();
+        universe.root = universe.get_empty_node(MIN_LEVEL);
+        universe.root_origin = HashLifeUniverse::get_centered_origin(MIN_LEVEL);
+        universe
+    }
+
+    fn clear_nodes(&mut self) {
+        self.nodes.clear();
+        self.node_ids.clear();
+        self.empty_node_ids.clear();
+        self.results.clear();
+
+        self.nodes.push(Node { level: 0, children: [FREE_LEAF; 4], population: 0 });
+        self.nodes.push(Node { level: 0, children: [FREE_LEAF; 4], population: 1 });
+        self.empty_node_ids.push(FREE_LEAF);
+    }
+
+    fn get_centered_origin(level: u8) -> (i64, i64) {
+        let half_side = if level == 0 { 0 } else { 1i64 << (level - 1) };
+        (-half_side, -half_side)
+    }
+
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    // Memoised results depend on the rule, so they are forgotten along with the old rule
+    pub fn set_rule(&mut self, rule: Rule) {
+        if !rule.is_life_like() || rule.number_of_states() != LIFE_NUMBER_OF_STATES {
+            panic!("'HashLifeUniverse' expects a two-state Life-like rule, received {}.", rule);
+        }
+
+        if rule.is_birth(0) {
+            panic!("'HashLifeUniverse' expects a rule without birth on zero neighbours, received {}.", rule);
+        }
+
+        self.rule = rule;
+        self.results.clear();
+    }
+
+    pub fn max_number_of_nodes(&self) -> usize {
+        self.max_number_of_nodes
+    }
+
+    pub fn set_max_number_of_nodes(&mut self, max_number_of_nodes: usize) {
+        self.max_number_of_nodes = max_number_of_nodes;
+    }
+
+    pub fn number_of_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn population(&self) -> u64 {
+        self.nodes[self.root as usize].population
+    }
+
+    fn get_node(&mut self, children: Children) -> NodeId {
+        if let Some(node_id) = self.node_ids.get(&children) {
+            return *node_id;
+        }
+
+        let level = self.nodes[children[NORTH_WEST] as usize].level + 1;
+        let population = children
+            .iter()
+            .fold(0u64, |population, child| population.saturating_add(self.nodes[*child as usize].population));
+
+        let node_id = self.nodes.len() as NodeId;
+        self.nodes.push(Node { level, children, population });
+        self.node_ids.insert(children, node_id);
+        node_id
+    }
+
+    fn get_empty_node(&mut self, level: u8) -> NodeId {
+        while self.empty_node_ids.len() <= level as usize {
+            let empty_child = *self.empty_node_ids.last().unwrap();
+            let empty_node = self.get_node([empty_child; 4]);
+            self.empty_node_ids.push(empty_node);
+        }
+
+        self.empty_node_ids[level as usize]
+    }
+
+    fn children(&self, node_id: NodeId) -> Children {
+        self.nodes[node_id as usize].children
+    }
+
+    fn level(&self, node_id: NodeId) -> u8 {
+        self.nodes[node_id as usize].level
+    }
+
+    fn root_side(&self) -> i64 {
+        1i64 << self.level(self.root)
+    }
+
+    // Surround the root with free cells keeping the pattern in the middle
+    fn expand_root(&mut self) {
+        let root_level = self.level(self.root);
+        if root_level >= MAX_LEVEL {
+            panic!("'HashLifeUniverse' cannot grow beyond {} levels.", MAX_LEVEL);
+        }
+
+        let empty_node = self.get_empty_node(root_level - 1);
+        let [north_west, north_east, south_west, south_east] = self.children(self.root);
+
+        let expanded_children = [
+            self.get_node([empty_node, empty_node, empty_node, north_west]),
+            self.get_node([empty_node, empty_node, north_east, empty_node]),
+            self.get_node([empty_node, south_west, empty_node, empty_node]),
+            self.get_node([south_east, empty_node, empty_node, empty_node]),
+        ];
+
+        let quarter_side = 1i64 << (root_level - 1);
+        self.root = self.get_node(expanded_children);
+        self.root_origin = (self.root_origin.0 - quarter_side, self.root_origin.1 - quarter_side);
+    }
+
+    fn is_within_root(&self, cell_row: i64, cell_column: i64) -> bool {
+        let root_side = self.root_side();
+        let (row_offset, column_offset) = (cell_row - self.root_origin.0, cell_column - self.root_origin.1);
+
+        (0..root_side).contains(&row_offset) && (0..root_side).contains(&column_offset)
+    }
+
+    fn set_node_cell(&mut self, node_id: NodeId, row_offset: i64, column_offset: i64, leaf: NodeId) -> NodeId {
+        let level = self.level(node_id);
+        if level == 0 {
+            return leaf;
+        }
+
+        let half_side = 1i64 << (level - 1);
+        let child_index = 2 * (row_offset >= half_side) as usize + (column_offset >= half_side) as usize;
+
+        let mut children = self.children(node_id);
+        children[child_index] = self.set_node_cell(children[child_index], row_offset % half_side, column_offset % half_side, leaf);
+        self.get_node(children)
+    }
+
+    fn set_cell(&mut self, cell_row: i64, cell_column: i64, leaf: NodeId) {
+        while !self.is_within_root(cell_row, cell_column) {
+            self.expand_root();
+        }
+
+        let (row_offset, column_offset) = (cell_row - self.root_origin.0, cell_column - self.root_origin.1);
+        self.root = self.set_node_cell(self.root, row_offset, column_offset, leaf);
+    }
+
+    pub fn populate_cell(&mut self, cell_row: i64, cell_column: i64) {
+        self.set_cell(cell_row, cell_column, POPULATED_LEAF);
+    }
+
+    pub fn free_cell(&mut self, cell_row: i64, cell_column: i64) {
+        self.set_cell(cell_row, cell_column, FREE_LEAF);
+    }
+
+    pub fn get_cell_state(&self, cell_row: i64, cell_column: i64) -> u8 {
+        if !self.is_within_root(cell_row, cell_column) {
+            return CELL_IS_FREE;
+        }
+
+        let (mut row_offset, mut column_offset) = (cell_row - self.root_origin.0, cell_column - self.root_origin.1);
+        let mut node_id = self.root;
+
+        while self.level(node_id) > 0 {
+            if self.nodes[node_id as usize].population == 0 {
+                return CELL_IS_FREE;
+            }
+
+            let half_side = 1i64 << (self.level(node_id) - 1);
+            let child_index = 2 * (row_offset >= half_side) as usize + (column_offset >= half_side) as usize;
+
+            node_id = self.children(node_id)[child_index];
+            row_offset %= half_side;
+            column_offset %= half_side;
+        }
+
+        if node_id == POPULATED_LEAF { CELL_IS_POPULATED } else { CELL_IS_FREE }
+    }
+
+    fn collect_populated_cells(&self, node_id: NodeId, origin: (i64, i64), populated_cells: &mut Vec<(i64, i64)>) {
+        let node = &self.nodes[node_id as usize];
+        if node.population == 0 {
+            return;
+        }
+
+        if node.level == 0 {
+            populated_cells.push(origin);
+            return;
+        }
+
+        let half_side = 1i64 << (node.level - 1);
+        for (child_index, child) in node.children.iter().enumerate() {
+            let child_origin = (origin.0 + half_side * (child_index / 2) as i64, origin.1 + half_side * (child_index % 2) as i64);
+            self.collect_populated_cells(*child, child_origin, populated_cells);
+        }
+    }
+
+    pub fn get_populated_cells(&self) -> Vec<(i64, i64)> {
+        let mut populated_cells = Vec::new();
+        self.collect_populated_cells(self.root, self.root_origin, &mut populated_cells);
+        populated_cells
+    }
+
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        let populated_cells = self.get_populated_cells();
+        let (first_row, first_column) = *populated_cells.first()?;
+
+        let mut bounding_box = BoundingBox::new(first_row, first_column);
+        for (cell_row, cell_column) in populated_cells {
+            bounding_box.include(cell_row, cell_column);
+        }
+
+        Some(bounding_box)
+    }
+
+    // Evolve the central 2x2 cells of the 4x4 node by a single generation cell by cell
+    fn advance_base_node(&mut self, node_id: NodeId) -> NodeId {
+        let get_cell = |row_index: usize, column_index: usize| {
+            let child = self.children(node_id)[2 * (row_index / 2) + column_index / 2];
+            self.children(child)[2 * (row_index % 2) + column_index % 2]
+        };
+
+        let neighbours = [
+            (-1, -1, NORTH_WEST_NEIGHBOUR), (-1, 0, NORTH_NEIGHBOUR), (-1, 1, NORTH_EAST_NEIGHBOUR),
+            (0, -1, WEST_NEIGHBOUR),                                  (0, 1, EAST_NEIGHBOUR),
+            (1, -1, SOUTH_WEST_NEIGHBOUR),  (1, 0, SOUTH_NEIGHBOUR),  (1, 1, SOUTH_EAST_NEIGHBOUR),
+        ];
+
+        let mut next_cells = [FREE_LEAF; 4];
+        for (next_cell_index, next_cell) in next_cells.iter_mut().enumerate() {
+            let row_index = 1 + next_cell_index / 2;
+            let column_index = 1 + next_cell_index % 2;
+
+            let alive_neighbourhood = neighbours
+                .iter()
+                .filter(|(row_offset, column_offset, _)| {
+                    get_cell((row_index as isize + row_offset) as usize, (column_index as isize + column_offset) as usize) == POPULATED_LEAF
+                })
+                .fold(0, |neighbourhood, (_, _, neighbour)| neighbourhood | neighbour);
+
+            let current_cell_state = if get_cell(row_index, column_index) == POPULATED_LEAF { CELL_IS_POPULATED } else { CELL_IS_FREE };
+            if Universe::get_next_cell_state(&self.rule, current_cell_state, alive_neighbourhood) == CELL_IS_POPULATED {
+                *next_cell = POPULATED_LEAF;
+            }
+        }
+
+        self.get_node(next_cells)
+    }
+
+    fn get_centered_node(&mut self, node_id: NodeId) -> NodeId {
+        let [north_west, north_east, south_west, south_east] = self.children(node_id);
+        self.get_node([
+            self.children(north_west)[SOUTH_EAST],
+            self.children(north_east)[SOUTH_WEST],
+            self.children(south_west)[NORTH_EAST],
+            self.children(south_east)[NORTH_WEST],
+        ])
+    }
+
+    fn get_horizontally_centered_node(&mut self, west: NodeId, east: NodeId) -> NodeId {
+        let (west_children, east_children) = (self.children(west), self.children(east));
+        self.get_node([west_children[NORTH_EAST], east_children[NORTH_WEST], west_children[SOUTH_EAST], east_children[SOUTH_WEST]])
+    }
+
+    fn get_vertically_centered_node(&mut self, north: NodeId, south: NodeId) -> NodeId {
+        let (north_children, south_children) = (self.children(north), self.children(south));
+        self.get_node([north_children[SOUTH_WEST], north_children[SOUTH_EAST], south_children[NORTH_WEST], south_children[NORTH_EAST]])
+    }
+
+    // Central half of the node advanced by 2^step_log generations, where step_log <= level - 2,
+    // none once the node cache outgrows the given number of nodes
+    fn advance_node(&mut self, node_id: NodeId, step_log: u8, max_number_of_nodes: usize) -> Option<NodeId> {
+        let level = self.level(node_id);
+        if self.nodes[node_id as usize].population == 0 {
+            return Some(self.get_empty_node(level - 1));
+        }
+
+        if let Some(result) = self.results.get(&(node_id, step_log)) {
+            return Some(*result);
+        }
+
+        if self.nodes.len() > max_number_of_nodes {
+            return None;
+        }
+
+        let result = if level == MIN_LEVEL {
+            self.advance_base_node(node_id)
+        } else {
+            let [north_west, north_east, south_west, south_east] = self.children(node_id);
+
+            // Nine overlapping subnodes of the previous level cover the node
+            let subnodes = [
+                north_west,
+                self.get_horizontally_centered_node(north_west, north_east),
+                north_east,
+                self.get_vertically_centered_node(north_west, south_west),
+                self.get_centered_node(node_id),
+                self.get_vertically_centered_node(north_east, south_east),
+                south_west,
+                self.get_horizontally_centered_node(south_west, south_east),
+                south_east,
+            ];
+
+            // Full step is made in two halves, shorter ones just take the centres first
+            let is_full_step = step_log == level - 2;
+            let mut partial_results = [FREE_LEAF; 9];
+            for (partial_result, subnode) in partial_results.iter_mut().zip(subnodes) {
+                *partial_result = if is_full_step { self.advance_node(subnode, step_log - 1, max_number_of_nodes)? } else { self.get_centered_node(subnode) };
+            }
+
+            let remaining_step_log = if is_full_step { step_log - 1 } else { step_log };
+            let mut result_children = [FREE_LEAF; 4];
+            for (child_index, result_child) in result_children.iter_mut().enumerate() {
+                let (row_index, column_index) = (child_index / 2, child_index % 2);
+                let quadrant = [
+                    partial_results[3 * row_index + column_index],
+                    partial_results[3 * row_index + column_index + 1],
+                    partial_results[3 * (row_index + 1) + column_index],
+                    partial_results[3 * (row_index + 1) + column_index + 1],
+                ];
+
+                let quadrant_node = self.get_node(quadrant);
+                *result_child = self.advance_node(quadrant_node, remaining_step_log, max_number_of_nodes)?;
+            }
+
+            self.get_node(result_children)
+        };
+
+        self.results.insert((node_id, step_log), result);
+        Some(result)
+    }
+
+    // Pattern lays within the central half of the root, so nothing is lost when the root gets shrunk
+    fn is_root_padded(&self) -> bool {
+        let root_population = self.population();
+        let [north_west, north_east, south_west, south_east] = self.children(self.root);
+
+        let central_population = self.nodes[self.children(north_west)[SOUTH_EAST] as usize].population
+            + self.nodes[self.children(north_east)[SOUTH_WEST] as usize].population
+            + self.nodes[self.children(south_west)[NORTH_EAST] as usize].population
+            + self.nodes[self.children(south_east)[NORTH_WEST] as usize].population;
+
+        central_population == root_population
+    }
+
+    fn copy_node(&self, node_id: NodeId, universe: &mut HashLifeUniverse, copied_node_ids: &mut HashMap<NodeId, NodeId>) -> NodeId {
+        if node_id == FREE_LEAF || node_id == POPULATED_LEAF {
+            return node_id;
+        }
+
+        if let Some(copied_node_id) = copied_node_ids.get(&node_id) {
+            return *copied_node_id;
+        }
+
+        let mut children = self.children(node_id);
+        for child in children.iter_mut() {
+            *child = self.copy_node(*child, universe, copied_node_ids);
+        }
+
+        let copied_node_id = universe.get_node(children);
+        copied_node_ids.insert(node_id, copied_node_id);
+        copied_node_id
+    }
+
+    // Drop every node unreachable from the root along with all memoised results
+    pub fn collect_garbage(&mut self) {
+        let mut universe = HashLifeUniverse::new();
+        universe.clear_nodes();
+
+        let mut copied_node_ids = HashMap::new();
+        let root = self.copy_node(self.root, &mut universe, &mut copied_node_ids);
+
+        self.nodes = universe.nodes;
+        self.node_ids = universe.node_ids;
+        self.empty_node_ids = universe.empty_node_ids;
+        self.results = universe.results;
+        self.root = root;
+    }
+
+    // Tell whether the root was advanced before the node cache outgrew the given number of nodes
+    fn advance_root(&mut self, step_log: u8, max_number_of_nodes: usize) -> bool {
+        // Pattern cannot spread faster than a cell per generation, so it stays within the result
+        while self.level(self.root) < step_log + 3 || !self.is_root_padded() {
+            self.expand_root();
+        }
+        self.expand_root();
+
+        let root_level = self.level(self.root);
+        let quarter_side = 1i64 << (root_level - 2);
+
+        match self.advance_node(self.root, step_log, max_number_of_nodes) {
+            Some(root) => {
+                self.root = root;
+                self.root_origin = (self.root_origin.0 + quarter_side, self.root_origin.1 + quarter_side);
+                self.generation += 1 << step_log;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Advance the universe by 2^step_log generations at once. Step which outgrows the node cache is given up
+    // and made again in two halves once the garbage is collected, so the cache stays within the cap
+    // unless a single generation of the pattern needs more nodes
+    pub fn step_pow2(&mut self, step_log: u8) {
+        // Root grows a level above the step plus three before it is advanced, which has to stay within the max level
+        if step_log > MAX_LEVEL - 4 {
+            panic!("'step_pow2' expects a step of at most 2^{} generations, received 2^{}.", MAX_LEVEL - 4, step_log);
+        }
+
+        let mut remaining_step_logs = vec![step_log];
+        while let Some(step_log) = remaining_step_logs.pop() {
+            if self.nodes.len() > self.max_number_of_nodes {
+                self.collect_garbage();
+            }
+
+            let max_number_of_nodes = if step_log == 0 { usize::MAX } else { self.max_number_of_nodes };
+            if !self.advance_root(step_log, max_number_of_nodes) {
+                self.collect_garbage();
+                remaining_step_logs.extend([step_log - 1, step_log - 1]);
+            }
+        }
+
+        if self.nodes.len() > self.max_number_of_nodes {
+            self.collect_garbage();
+        }
+    }
+
+    pub fn next_generation(&mut self) {
+        self.step_pow2(0);
+    }
+}
+
+impl Default for HashLifeUniverse {
+    fn default() -> Self {
+        HashLifeUniverse::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLIDER_CELLS: [(i64, i64); 5] = [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)];
+    const SOUP_SIDE: i64 = 16;
+
+    fn build_random_soup(hashlife_universe: &mut HashLifeUniverse, infinite_universe: &mut InfiniteUniverse) {
+        for row_index in 0..SOUP_SIDE {
+            for column_index in 0..SOUP_SIDE {
+                if rand::random::<bool>() {
+                    hashlife_universe.populate_cell(row_index, column_index);
+                    infinite_universe.populate_cell(row_index, column_index);
+                }
+            }
+        }
+    }
+
+    fn get_populated_cells(infinite_universe: &InfiniteUniverse) -> Vec<(i64, i64)> {
+        let mut populated_cells = Vec::new();
+        if let Some(bounding_box) = infinite_universe.bounding_box() {
+            for row_index in bounding_box.min_row..=bounding_box.max_row {
+                for column_index in bounding_box.min_column..=bounding_box.max_column {
+                    if infinite_universe.get_cell_state(row_index, column_index) == CELL_IS_POPULATED {
+                        populated_cells.push((row_index, column_index));
+                    }
+                }
+            }
+        }
+
+        populated_cells
+    }
+
+    fn is_same_generation(hashlife_universe: &HashLifeUniverse, infinite_universe: &InfiniteUniverse) -> bool {
+        let mut hashlife_cells = hashlife_universe.get_populated_cells();
+        hashlife_cells.sort_unstable();
+
+        hashlife_cells == get_populated_cells(infinite_universe)
+    }
+
+    #[test]
+    fn cells_are_populated_and_freed_anywhere() {
+        let mut universe = HashLifeUniverse::new();
+        universe.populate_cell(-1000, 3);
+        universe.populate_cell(7, 1 << 40);
+        assert_eq!(universe.population(), 2);
+        assert_eq!(universe.get_cell_state(-1000, 3), CELL_IS_POPULATED);
+        assert_eq!(universe.get_cell_state(7, 1 << 40), CELL_IS_POPULATED);
+        assert_eq!(universe.get_cell_state(7, 3), CELL_IS_FREE);
+
+        universe.free_cell(-1000, 3);
+        assert_eq!(universe.population(), 1);
+        assert_eq!(universe.bounding_box(), Some(BoundingBox { min_row: 7, min_column: 1 << 40, max_row: 7, max_column: 1 << 40 }));
+    }
+
+    #[test]
+    fn single_generations_match_infinite_universe_on_random_soups() {
+        for rulestring in ["B3/S23", "B36/S23", "B2-a/S12"] {
+            let mut hashlife_universe = HashLifeUniverse::new();
+            hashlife_universe.set_rule(rulestring.parse().unwrap());
+            let mut infinite_universe = InfiniteUniverse::new();
+            infinite_universe.set_rule(rulestring.parse().unwrap());
+            build_random_soup(&mut hashlife_universe, &mut infinite_universe);
+
+            for generation in 1..=20 {
+                hashlife_universe.next_generation();
+                infinite_universe.next_generation();
+
+                assert_eq!(hashlife_universe.generation(), generation);
+                assert!(is_same_generation(&hashlife_universe, &infinite_universe));
+            }
+        }
+    }
+
+    #[test]
+    fn power_of_two_steps_match_infinite_universe_on_random_soups() {
+        let mut hashlife_universe = HashLifeUniverse::new();
+        let mut infinite_universe = InfiniteUniverse::new();
+        build_random_soup(&mut hashlife_universe, &mut infinite_universe);
+
+        for step_log in 0..6 {
+            hashlife_universe.step_pow2(step_log);
+            for _ in 0..(1 << step_log) {
+                infinite_universe.next_generation();
+            }
+
+            assert!(is_same_generation(&hashlife_universe, &infinite_universe));
+        }
+
+        assert_eq!(hashlife_universe.generation(), 63);
+    }
+
+    #[test]
+    fn glider_travels_after_huge_step() {
+        let mut universe = HashLifeUniverse::new();
+        for (row_index, column_index) in GLIDER_CELLS {
+            universe.populate_cell(row_index, column_index);
+        }
+
+        let step_log = 20;
+        universe.step_pow2(step_log);
+
+        // Glider moves by one cell diagonally every four generations
+        let glider_shift = 1i64 << (step_log - 2);
+        let mut populated_cells = universe.get_populated_cells();
+        populated_cells.sort_unstable();
+
+        let expected_cells: Vec<(i64, i64)> = GLIDER_CELLS
+            .iter()
+            .map(|(row_index, column_index)| (row_index + glider_shift, column_index + glider_shift))
+            .collect();
+        assert_eq!(populated_cells, expected_cells);
+        assert_eq!(universe.generation(), 1 << step_log);
+    }
+
+    #[test]
+    fn garbage_collection_keeps_node_cache_within_cap() {
+        let mut hashlife_universe = HashLifeUniverse::new();
+        let mut infinite_universe = InfiniteUniverse::new();
+        build_random_soup(&mut hashlife_universe, &mut infinite_universe);
+
+        let max_number_of_nodes = 2000;
+        hashlife_universe.set_max_number_of_nodes(max_number_of_nodes);
+
+        for _ in 0..8 {
+            hashlife_universe.step_pow2(3);
+            assert!(hashlife_universe.number_of_nodes() <= max_number_of_nodes);
+
+            for _ in 0..8 {
+                infinite_universe.next_generation();
+            }
+            assert!(is_same_generation(&hashlife_universe, &infinite_universe));
+        }
+        assert_eq!(hashlife_universe.generation(), 64);
+    }
+
+    #[test]
+    fn block_survives_longest_step() {
+        let mut universe = HashLifeUniverse::new();
+        let block_cells = [(0, 0), (0, 1), (1, 0), (1, 1)];
+        for (row_index, column_index) in block_cells {
+            universe.populate_cell(row_index, column_index);
+        }
+
+        universe.step_pow2(MAX_LEVEL - 4);
+        let mut populated_cells = universe.get_populated_cells();
+        populated_cells.sort_unstable();
+        assert_eq!(populated_cells, block_cells);
+        assert_eq!(universe.generation(), 1 << (MAX_LEVEL - 4));
+    }
+
+    #[test]
+    #[should_panic(expected = "'step_pow2' expects a step of at most")]
+    fn step_beyond_max_level_is_rejected() {
+        let mut universe = HashLifeUniverse::new();
+        universe.populate_cell(0, 0);
+        universe.step_pow2(MAX_LEVEL - 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn generations_rule_is_rejected() {
+        let mut universe = HashLifeUniverse::new();
+        universe.set_rule("/2/3".parse().unwrap());
+    }
+}
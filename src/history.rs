@@ -0,0 +1,277 @@
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryEntryKind {
+    Generation,
+    Edit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CellChange {
+    cell_index: u32,
+    previous_state: u8,
+    next_state: u8,
+}
+
+// Entry keeps only the cells which changed, together with both of their states,
+// so that it can be undone and redone without recomputing anything. Block partitions of Margolus mode
+// alternate between generations, so the partition offsets before and after the entry are kept as well
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    kind: HistoryEntryKind,
+    cell_changes: Vec<CellChange>,
+    block_partition_offsets: (usize, usize),
+}
+
+impl HistoryEntry {
+    pub fn from_difference(kind: HistoryEntryKind, previous_cells: &[u8], next_cells: &[u8], block_partition_offsets: (usize, usize)) -> Self {
+        if previous_cells.len() != next_cells.len() {
+            panic!("'HistoryEntry' expects cells of the same length, received {} and {}.", previous_cells.len(), next_cells.len());
+        }
+
+        let cell_changes = previous_cells
+            .iter()
+            .zip(next_cells)
+            .enumerate()
+            .filter(|(_, (previous_state, next_state))| previous_state != next_state)
+            .map(|(cell_index, (previous_state, next_state))| CellChange { cell_index: cell_index as u32, previous_state: *previous_state, next_state: *next_state })
+            .collect();
+
+        HistoryEntry { kind, cell_changes, block_partition_offsets }
+    }
+
+    pub fn kind(&self) -> HistoryEntryKind {
+        self.kind
+    }
+
+    pub fn number_of_changes(&self) -> usize {
+        self.cell_changes.len()
+    }
+
+    // Block partition offset the entry was made from and the one it left
+    pub fn block_partition_offsets(&self) -> (usize, usize) {
+        self.block_partition_offsets
+    }
+
+    fn undo(&self, cells: &mut [u8]) {
+        for cell_change in self.cell_changes.iter() {
+            cells[cell_change.cell_index as usize] = cell_change.previous_state;
+        }
+    }
+
+    fn redo(&self, cells: &mut [u8]) {
+        for cell_change in self.cell_changes.iter() {
+            cells[cell_change.cell_index as usize] = cell_change.next_state;
+        }
+    }
+}
+
+// Bounded timeline of generations and edits: the oldest entries are dropped once the capacity is reached,
+// and the entries after the position are the ones which were undone and can still be redone.
+// History remembers the cells as it last saw them, so any change made since then is recorded as an edit
+#[derive(Debug, Clone)]
+pub struct History {
+    capacity: usize,
+    entries: VecDeque<HistoryEntry>,
+    position: usize,
+    recorded_cells: Vec<u8>,
+    recorded_block_partition_offset: usize,
+}
+
+impl History {
+    pub fn new(capacity: usize, cells: &[u8], block_partition_offset: usize) -> Self {
+        if capacity == 0 {
+            panic!("'History' expects a non-zero capacity.");
+        }
+
+        History {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+            position: 0,
+            recorded_cells: cells.to_vec(),
+            recorded_block_partition_offset: block_partition_offset,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Number of entries which are currently applied
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.entries.iter()
+    }
+
+    // Recording a new entry forgets the undone ones, generations are recorded even if nothing changed
+    // to keep the timeline in step with time, while empty edits are skipped. Partition offset changed
+    // without a generation, like when the update mode is set, is taken as it is
+    pub fn record(&mut self, kind: HistoryEntryKind, cells: &[u8], block_partition_offset: usize) {
+        let entry = HistoryEntry::from_difference(kind, &self.recorded_cells, cells, (self.recorded_block_partition_offset, block_partition_offset));
+        self.recorded_block_partition_offset = block_partition_offset;
+        if kind == HistoryEntryKind::Edit && entry.number_of_changes() == 0 {
+            return;
+        }
+
+        self.entries.truncate(self.position);
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+
+        entry.redo(&mut self.recorded_cells);
+        self.entries.push_back(entry);
+        self.position = self.entries.len();
+    }
+
+    // Cells have to be the recorded ones, pending edits are recorded beforehand
+    fn undo_recorded(&mut self, cells: &mut [u8], block_partition_offset: &mut usize) -> Option<HistoryEntryKind> {
+        if self.position == 0 {
+            return None;
+        }
+
+        self.position -= 1;
+        let entry = &self.entries[self.position];
+        entry.undo(cells);
+        entry.undo(&mut self.recorded_cells);
+        (*block_partition_offset, _) = entry.block_partition_offsets();
+        self.recorded_block_partition_offset = *block_partition_offset;
+        Some(entry.kind())
+    }
+
+    fn redo_recorded(&mut self, cells: &mut [u8], block_partition_offset: &mut usize) -> Option<HistoryEntryKind> {
+        if self.position == self.entries.len() {
+            return None;
+        }
+
+        let entry = &self.entries[self.position];
+        entry.redo(cells);
+        entry.redo(&mut self.recorded_cells);
+        (_, *block_partition_offset) = entry.block_partition_offsets();
+        self.recorded_block_partition_offset = *block_partition_offset;
+        self.position += 1;
+        Some(entry.kind())
+    }
+
+    // Pending edits are recorded first, so undoing starts with them
+    pub fn undo(&mut self, cells: &mut [u8], block_partition_offset: &mut usize) -> Option<HistoryEntryKind> {
+        self.record(HistoryEntryKind::Edit, cells, *block_partition_offset);
+        self.undo_recorded(cells, block_partition_offset)
+    }
+
+    // Pending edits would be lost by redoing, so they end the timeline instead
+    pub fn redo(&mut self, cells: &mut [u8], block_partition_offset: &mut usize) -> Option<HistoryEntryKind> {
+        self.record(HistoryEntryKind::Edit, cells, *block_partition_offset);
+        self.redo_recorded(cells, block_partition_offset)
+    }
+
+    // Pending edits are recorded once, then the entries are applied one by one
+    pub fn rewind_to(&mut self, position: usize, cells: &mut [u8], block_partition_offset: &mut usize) {
+        if position > self.entries.len() {
+            panic!("History of {} entries cannot be rewound to position {}.", self.entries.len(), position);
+        }
+
+        self.record(HistoryEntryKind::Edit, cells, *block_partition_offset);
+        while self.position > position && self.undo_recorded(cells, block_partition_offset).is_some() {}
+        while self.position < position && self.redo_recorded(cells, block_partition_offset).is_some() {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_are_undone_and_redone() {
+        let mut cells = vec![0, 0, 0, 0];
+        let mut block_partition_offset = 0;
+        let mut history = History::new(10, &cells, 0);
+
+        cells[1] = 1;
+        history.record(HistoryEntryKind::Generation, &cells, 0);
+        cells[2] = 1;
+        cells[1] = 0;
+        history.record(HistoryEntryKind::Generation, &cells, 0);
+        assert_eq!(history.entries().map(|entry| entry.number_of_changes()).collect::<Vec<_>>(), vec![1, 2]);
+
+        assert_eq!(history.undo(&mut cells, &mut block_partition_offset), Some(HistoryEntryKind::Generation));
+        assert_eq!(cells, vec![0, 1, 0, 0]);
+        assert_eq!(history.undo(&mut cells, &mut block_partition_offset), Some(HistoryEntryKind::Generation));
+        assert_eq!(cells, vec![0, 0, 0, 0]);
+        assert_eq!(history.undo(&mut cells, &mut block_partition_offset), None);
+
+        history.rewind_to(2, &mut cells, &mut block_partition_offset);
+        assert_eq!(cells, vec![0, 0, 1, 0]);
+        assert_eq!(history.redo(&mut cells, &mut block_partition_offset), None);
+    }
+
+    #[test]
+    fn pending_edits_are_undone_first_and_end_the_timeline() {
+        let mut cells = vec![0, 0, 0];
+        let mut block_partition_offset = 0;
+        let mut history = History::new(10, &cells, 0);
+        cells[0] = 1;
+        history.record(HistoryEntryKind::Generation, &cells, 0);
+        history.undo(&mut cells, &mut block_partition_offset);
+
+        cells[2] = 2;
+        assert_eq!(history.redo(&mut cells, &mut block_partition_offset), None);
+        assert_eq!(history.len(), 1);
+        assert_eq!(history.undo(&mut cells, &mut block_partition_offset), Some(HistoryEntryKind::Edit));
+        assert_eq!(cells, vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn oldest_entries_are_dropped() {
+        let mut cells = vec![0];
+        let mut block_partition_offset = 0;
+        let mut history = History::new(3, &cells, 0);
+        for generation in 1..=5 {
+            cells[0] = generation;
+            history.record(HistoryEntryKind::Generation, &cells, 0);
+        }
+
+        assert_eq!(history.len(), 3);
+        history.rewind_to(0, &mut cells, &mut block_partition_offset);
+        assert_eq!(cells, vec![2]);
+    }
+
+    #[test]
+    fn block_partition_offsets_are_restored() {
+        let mut cells = vec![0, 0];
+        let mut block_partition_offset = 0;
+        let mut history = History::new(10, &cells, block_partition_offset);
+        for generation in 1..=3 {
+            cells[0] = generation;
+            block_partition_offset = 1 - block_partition_offset;
+            history.record(HistoryEntryKind::Generation, &cells, block_partition_offset);
+        }
+
+        // Update mode set anew starts from the first partition
+        block_partition_offset = 0;
+        cells[1] = 1;
+        history.record(HistoryEntryKind::Edit, &cells, block_partition_offset);
+        cells[0] = 4;
+        block_partition_offset = 1;
+        history.record(HistoryEntryKind::Generation, &cells, block_partition_offset);
+
+        history.undo(&mut cells, &mut block_partition_offset);
+        assert_eq!((cells.clone(), block_partition_offset), (vec![3, 1], 0));
+        history.undo(&mut cells, &mut block_partition_offset);
+        assert_eq!((cells.clone(), block_partition_offset), (vec![3, 0], 1));
+        history.rewind_to(1, &mut cells, &mut block_partition_offset);
+        assert_eq!((cells.clone(), block_partition_offset), (vec![1, 0], 1));
+        history.rewind_to(5, &mut cells, &mut block_partition_offset);
+        assert_eq!((cells, block_partition_offset), (vec![4, 1], 1));
+    }
+}
@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+use nalgebra::DMatrix;
+use crate::grid::*;
+use crate::rule::*;
+use crate::topology::*;
+use crate::universe::*;
+
+pub const CHUNK_SIDE: usize = 64;
+
+pub type ChunkPosition = (i64, i64);
+type Chunk = DMatrix<u8>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub min_row: i64,
+    pub min_column: i64,
+    pub max_row: i64,
+    pub max_column: i64,
+}
+
+impl BoundingBox {
+    pub fn new(cell_row: i64, cell_column: i64) -> Self {
+        BoundingBox { min_row: cell_row, min_column: cell_column, max_row: cell_row, max_column: cell_column }
+    }
+
+    pub fn include(&mut self, cell_row: i64, cell_column: i64) {
+        self.min_row = self.min_row.min(cell_row);
+        self.min_column = self.min_column.min(cell_column);
+        self.max_row = self.max_row.max(cell_row);
+        self.max_column = self.max_column.max(cell_column);
+    }
+
+    pub fn shape(&self) -> (u64, u64) {
+        ((self.max_row - self.min_row + 1) as u64, (self.max_column - self.min_column + 1) as u64)
+    }
+}
+
+// Split cell position into the position of its chunk and the position within the chunk
+fn get_chunk_position(cell_row: i64, cell_column: i64) -> (ChunkPosition, (usize, usize)) {
+    let chunk_side = CHUNK_SIDE as i64;
+    let chunk_position = (cell_row.div_euclid(chunk_side), cell_column.div_euclid(chunk_side));
+    let position_within_chunk = (cell_row.rem_euclid(chunk_side) as usize, cell_column.rem_euclid(chunk_side) as usize);
+
+    (chunk_position, position_within_chunk)
+}
+
+fn is_chunk_free(chunk: &Chunk) -> bool {
+    chunk.iter().all(|cell_state| *cell_state == CELL_IS_FREE)
+}
+
+// Universe without borders: the plane is split into square chunks and only the ones
+// with at least one non-free cell are stored, so it grows and shrinks along with the pattern
+pub struct InfiniteUniverse {
+    rule: Rule,
+    rule_kernel: RuleKernel,
+    chunks: HashMap<ChunkPosition, Chunk>,
+}
+
+impl InfiniteUniverse {
+    pub fn new() -> Self {
+        let rule = Rule::default();
+
+        InfiniteUniverse {
+            rule_kernel: rule.kernel(),
+            rule,
+            chunks: HashMap::new(),
+        }
+    }
+
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    // Rules giving birth to cells without alive neighbours would populate the whole plane at once
+    pub fn set_rule(&mut self, rule: Rule) {
+        if rule.grid() != Grid::Square {
+            panic!("'InfiniteUniverse' expects a rule of the square grid, received {}.", rule);
+        }
+
+        if rule.is_birth(0) {
+            panic!("'InfiniteUniverse' expects a rule without birth on zero neighbours, received {}.", rule);
+        }
+
+        let number_of_states = rule.number_of_states();
+        for chunk in self.chunks.values_mut() {
+            chunk.apply(|cell_state| {
+                if *cell_state >= number_of_states {
+                    *cell_state = CELL_IS_FREE;
+                }
+            });
+        }
+        self.chunks.retain(|_, chunk| !is_chunk_free(chunk));
+
+        self.rule_kernel = rule.kernel();
+        self.rule = rule;
+    }
+
+    pub fn number_of_states(&self) -> u8 {
+        self.rule.number_of_states()
+    }
+
+    pub fn number_of_chunks(&self) -> usize {
+        self.chunks.len()
+    }
+
+    fn store_cell_state(&mut self, cell_row: i64, cell_column: i64, cell_state: u8) {
+        let (chunk_position, position_within_chunk) = get_chunk_position(cell_row, cell_column);
+
+        if cell_state == CELL_IS_FREE {
+            if let Some(chunk) = self.chunks.get_mut(&chunk_position) {
+                chunk[position_within_chunk] = CELL_IS_FREE;
+                if is_chunk_free(chunk) {
+                    self.chunks.remove(&chunk_position);
+                }
+            }
+            return;
+        }
+
+        let chunk = self.chunks
+            .entry(chunk_position)
+            .or_insert_with(|| Chunk::from_element(CHUNK_SIDE, CHUNK_SIDE, CELL_IS_FREE));
+        chunk[position_within_chunk] = cell_state;
+    }
+
+    pub fn populate_cell(&mut self, cell_row: i64, cell_column: i64) {
+        self.store_cell_state(cell_row, cell_column, CELL_IS_POPULATED);
+    }
+
+    pub fn free_cell(&mut self, cell_row: i64, cell_column: i64) {
+        self.store_cell_state(cell_row, cell_column, CELL_IS_FREE);
+    }
+
+    pub fn set_cell_state(&mut self, cell_row: i64, cell_column: i64, cell_state: u8) {
+        if cell_state >= self.number_of_states() {
+            panic!("Cell state {} doesn't exist in rule {}.", cell_state, self.rule);
+        }
+
+        self.store_cell_state(cell_row, cell_column, cell_state);
+    }
+
+    pub fn get_cell_state(&self, cell_row: i64, cell_column: i64) -> u8 {
+        let (chunk_position, position_within_chunk) = get_chunk_position(cell_row, cell_column);
+
+        match self.chunks.get(&chunk_position) {
+            Some(chunk) => chunk[position_within_chunk],
+            None => CELL_IS_FREE,
+        }
+    }
+
+    // Smallest rectangle containing every non-free cell, empty universe has none
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        let mut bounding_box: Option<BoundingBox> = None;
+
+        for (chunk_position, chunk) in &self.chunks {
+            let chunk_row = chunk_position.0 * CHUNK_SIDE as i64;
+            let chunk_column = chunk_position.1 * CHUNK_SIDE as i64;
+
+            for row_index in 0..CHUNK_SIDE {
+                for column_index in 0..CHUNK_SIDE {
+                    if chunk[(row_index, column_index)] == CELL_IS_FREE {
+                        continue;
+                    }
+
+                    let cell_row = chunk_row + row_index as i64;
+                    let cell_column = chunk_column + column_index as i64;
+
+                    match bounding_box.as_mut() {
+                        Some(bounding_box) => bounding_box.include(cell_row, cell_column),
+                        None => bounding_box = Some(BoundingBox::new(cell_row, cell_column)),
+                    }
+                }
+            }
+        }
+
+        bounding_box
+    }
+
+    // Chunk together with the halo of the kernel half taken from its neighbouring chunks
+    fn get_alive_cells_around(&self, chunk_position: ChunkPosition, kernel_half: usize) -> DMatrix<u32> {
+        let chunk_row = chunk_position.0 * CHUNK_SIDE as i64 - kernel_half as i64;
+        let chunk_column = chunk_position.1 * CHUNK_SIDE as i64 - kernel_half as i64;
+        let padded_chunk_side = CHUNK_SIDE + 2 * kernel_half;
+
+        DMatrix::from_fn(padded_chunk_side, padded_chunk_side, |row_index, column_index| {
+            let cell_state = self.get_cell_state(chunk_row + row_index as i64, chunk_column + column_index as i64);
+            (cell_state == CELL_IS_POPULATED) as u32
+        })
+    }
+
+    pub fn next_generation(&mut self) {
+        let kernel_half = self.rule_kernel.nrows() / 2;
+        let chunk_reach = kernel_half.div_ceil(CHUNK_SIDE) as i64;
+
+        // Cells may be born in free chunks within the reach of the kernel from the stored ones
+        let mut chunk_positions: Vec<ChunkPosition> = Vec::new();
+        for (chunk_row, chunk_column) in self.chunks.keys() {
+            for row_offset in -chunk_reach..=chunk_reach {
+                for column_offset in -chunk_reach..=chunk_reach {
+                    chunk_positions.push((chunk_row + row_offset, chunk_column + column_offset));
+                }
+            }
+        }
+        chunk_positions.sort_unstable();
+        chunk_positions.dedup();
+
+        let mut next_chunks = HashMap::new();
+        for chunk_position in chunk_positions {
+            let alive_cells = self.get_alive_cells_around(chunk_position, kernel_half);
+            if !self.chunks.contains_key(&chunk_position) && alive_cells.iter().all(|alive_cell| *alive_cell == 0) {
+                continue;
+            }
+
+            // Halo is cut from the padded chunk, so it doesn't matter that its borders are dead
+            let alive_neighbourhoods = count_alive_neighbourhoods(&alive_cells, &self.rule_kernel, Topology::Plane);
+
+            let next_chunk = Chunk::from_fn(CHUNK_SIDE, CHUNK_SIDE, |row_index, column_index| {
+                let current_cell_state = match self.chunks.get(&chunk_position) {
+                    Some(chunk) => chunk[(row_index, column_index)],
+                    None => CELL_IS_FREE,
+                };
+                let alive_neighbourhood = alive_neighbourhoods[(row_index + kernel_half, column_index + kernel_half)];
+
+                Universe::get_next_cell_state(&self.rule, current_cell_state, alive_neighbourhood)
+            });
+
+            if !is_chunk_free(&next_chunk) {
+                next_chunks.insert(chunk_position, next_chunk);
+            }
+        }
+
+        self.chunks = next_chunks;
+    }
+}
+
+impl Default for InfiniteUniverse {
+    fn default() -> Self {
+        InfiniteUniverse::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLIDER_PERIOD: usize = 4;
+    const GLIDER_CELLS: [(i64, i64); 5] = [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)];
+
+    fn build_pattern(cells: &[(i64, i64)], upper_left_position: (i64, i64), universe: &mut InfiniteUniverse) {
+        for (row_offset, column_offset) in cells {
+            universe.populate_cell(upper_left_position.0 + row_offset, upper_left_position.1 + column_offset);
+        }
+    }
+
+    fn is_pattern_detected(cells: &[(i64, i64)], upper_left_position: (i64, i64), universe: &InfiniteUniverse) -> bool {
+        let is_every_cell_populated = cells.iter().all(|(row_offset, column_offset)| {
+            universe.get_cell_state(upper_left_position.0 + row_offset, upper_left_position.1 + column_offset) == CELL_IS_POPULATED
+        });
+
+        let bounding_box = universe.bounding_box();
+        is_every_cell_populated && bounding_box.map(|bounding_box| (bounding_box.min_row, bounding_box.min_column)) == Some(upper_left_position)
+    }
+
+    #[test]
+    fn empty_universe_has_no_chunks() {
+        let mut universe = InfiniteUniverse::new();
+        assert_eq!(universe.number_of_chunks(), 0);
+        assert_eq!(universe.bounding_box(), None);
+
+        universe.populate_cell(-1000, 1000);
+        assert_eq!(universe.number_of_chunks(), 1);
+        assert_eq!(universe.get_cell_state(-1000, 1000), CELL_IS_POPULATED);
+
+        universe.free_cell(-1000, 1000);
+        assert_eq!(universe.number_of_chunks(), 0);
+        assert_eq!(universe.get_cell_state(-1000, 1000), CELL_IS_FREE);
+    }
+
+    #[test]
+    fn lonely_cell_dies_and_its_chunk_is_dropped() {
+        let mut universe = InfiniteUniverse::new();
+        universe.populate_cell(5, 5);
+
+        universe.next_generation();
+        assert_eq!(universe.number_of_chunks(), 0);
+    }
+
+    #[test]
+    fn blinker_oscillates_across_chunk_corner() {
+        let mut universe = InfiniteUniverse::new();
+        build_pattern(&[(0, -1), (0, 0), (0, 1)], (0, 0), &mut universe);
+        assert_eq!(universe.bounding_box(), Some(BoundingBox { min_row: 0, min_column: -1, max_row: 0, max_column: 1 }));
+
+        universe.next_generation();
+        assert_eq!(universe.bounding_box(), Some(BoundingBox { min_row: -1, min_column: 0, max_row: 1, max_column: 0 }));
+        assert_eq!(universe.number_of_chunks(), 2);
+
+        universe.next_generation();
+        assert_eq!(universe.bounding_box(), Some(BoundingBox { min_row: 0, min_column: -1, max_row: 0, max_column: 1 }));
+    }
+
+    #[test]
+    fn glider_travels_across_chunks_without_bounds() {
+        let mut universe = InfiniteUniverse::new();
+        let initial_position = (-100, -100);
+        build_pattern(&GLIDER_CELLS, initial_position, &mut universe);
+
+        let glider_shift = 2 * CHUNK_SIDE + 10;
+        for _ in 0..(glider_shift * GLIDER_PERIOD) {
+            universe.next_generation();
+        }
+
+        let expected_position = (initial_position.0 + glider_shift as i64, initial_position.1 + glider_shift as i64);
+        assert!(is_pattern_detected(&GLIDER_CELLS, expected_position, &universe));
+        assert_eq!(universe.bounding_box().map(|bounding_box| bounding_box.shape()), Some((3, 3)));
+        assert!(universe.number_of_chunks() <= 4);
+    }
+
+    #[test]
+    fn universe_evolves_like_large_torus_away_from_its_borders() {
+        const TORUS_SIDE: usize = 200;
+        const SOUP_SIDE: usize = 24;
+        const NUMBER_OF_GENERATIONS: usize = 15;
+
+        for rulestring in ["B3/S23", "/2/3", "R5,C0,M1,S34..58,B34..45,NM"] {
+            let rule: Rule = rulestring.parse().unwrap();
+
+            let mut torus_universe = Universe::new(TORUS_SIDE, TORUS_SIDE, Topology::Torus);
+            torus_universe.set_rule(rule.clone());
+
+            let mut infinite_universe = InfiniteUniverse::new();
+            infinite_universe.set_rule(rule);
+
+            let soup_offset = (TORUS_SIDE - SOUP_SIDE) / 2;
+            for row_index in soup_offset..(soup_offset + SOUP_SIDE) {
+                for column_index in soup_offset..(soup_offset + SOUP_SIDE) {
+                    if rand::random::<bool>() {
+                        torus_universe.populate_cell(row_index, column_index);
+                        infinite_universe.populate_cell(row_index as i64, column_index as i64);
+                    }
+                }
+            }
+
+            for _ in 0..NUMBER_OF_GENERATIONS {
+                torus_universe.next_generation();
+                infinite_universe.next_generation();
+            }
+
+            for row_index in 0..TORUS_SIDE {
+                for column_index in 0..TORUS_SIDE {
+                    let torus_cell_state = torus_universe.get_cell_state(row_index, column_index);
+                    assert_eq!(infinite_universe.get_cell_state(row_index as i64, column_index as i64), torus_cell_state);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn rule_with_birth_on_zero_neighbours_is_rejected() {
+        let mut universe = InfiniteUniverse::new();
+        universe.set_rule("B0/S8".parse().unwrap());
+    }
+}
@@ -0,0 +1,21 @@
+pub mod bit_packed_universe;
+pub mod cell_age;
+pub mod census;
+pub mod constants;
+pub mod continuous_universe;
+pub mod convolution;
+pub mod cycle_detector;
+pub mod generation_stats;
+pub mod grid;
+pub mod hashlife;
+pub mod history;
+pub mod infinite_universe;
+pub mod predecessor;
+pub mod rule;
+pub mod rule_table;
+pub mod soup_search;
+pub mod stochastic;
+pub mod topology;
+pub mod universe;
+pub mod update_mode;
+pub mod volume_universe;
@@ -1,22 +1,28 @@
-use ggez::{conf, timer, Context, GameResult};
-use ggez::event::{self, EventHandler, MouseButton};
+use ggez::{conf, graphics, timer, Context, GameResult};
+use ggez::event::{self, EventHandler, KeyCode, KeyMods, MouseButton};
 use specs::{RunNow, World, WorldExt};
 use std::path;
 
+mod arguments;
 mod components;
-mod constants;
 mod entities;
 mod resources;
 mod systems;
-mod universe;
 
+use rust_game_of_life::{cell_age, census, constants, continuous_universe, cycle_detector, generation_stats, grid, history, predecessor, rule, rule_table, soup_search, stochastic, topology, universe, update_mode, volume_universe};
+use crate::arguments::*;
 use crate::resources::*;
+use crate::census::*;
 use crate::components::*;
 use crate::constants::*;
 use crate::entities::*;
+use crate::predecessor::*;
+use crate::soup_search::*;
 use crate::systems::*;
+use crate::universe::*;
 
 const UNIVERSE_UPDATE_ENABLED_STATE: MouseButton = MouseButton::Other(1111);
+const WINDOW_TITLE: &str = "Conway's Game Of Life!";
 
 trait UniverseMouseUpdater {
     fn is_universe_update_enabled(&self) -> bool { 
@@ -29,13 +35,73 @@ trait UniverseMouseUpdater {
 
     fn update_generation(&mut self) {}
 
-    fn capture_mouse_pressed_position(&self, _x: f32, _y:f32) {}
+    fn capture_mouse_pressed_position(&self, _x: f32, _y:f32, _is_dragging: bool) {}
+}
+
+// Every generation of the discrete field is stepped here, whether it runs or is stepped by hand,
+// so that the statistics miss none of them
+fn step_discrete_field(universe: &mut Universe, stats_stream: &mut StatsStream) {
+    universe.next_generation();
+    if let Some(generation_stats) = universe.generation_stats() {
+        stats_stream.record(generation_stats);
+    }
 }
 
 // This struct will hold all our game state
 struct GameState {
     world: World,
     pressed_button: MouseButton,
+    is_paused: bool,
+    is_scrubbing: bool,
+    // Outcome of the last key action shown next to the cycle until another key is pressed
+    status: Option<String>,
+    window_title: String,
+}
+
+impl GameState {
+    // Timeline slider lays under the universe whenever the discrete field keeps its history
+    fn get_timeline_area(&self) -> Option<(f32, f32)> {
+        let universe_field = self.world.read_resource::<UniverseField>();
+        if universe_field.field.history().is_none() || universe_field.continuous_field.is_some() || universe_field.volume_field.is_some() {
+            return None;
+        }
+
+        let viewport = self.world.read_resource::<Viewport>();
+        Some(viewport.window_dimensions(universe_field.field.rule().grid(), universe_field.field.shape()))
+    }
+
+    fn is_timeline_position(&self, y: f32) -> bool {
+        matches!(self.get_timeline_area(), Some((_, timeline_top)) if y >= timeline_top)
+    }
+
+    // Window title tells the period and the displacement once the universe settles into a cycle,
+    // followed by the status of the last key action
+    fn update_window_title(&mut self, context: &mut Context) {
+        let mut window_title = String::from(WINDOW_TITLE);
+        if let Some(cycle) = self.world.read_resource::<UniverseField>().field.detected_cycle() {
+            window_title = format!("{} {}", window_title, cycle);
+        }
+        if let Some(status) = &self.status {
+            window_title = format!("{} - {}", window_title, status);
+        }
+
+        if window_title != self.window_title {
+            graphics::set_window_title(context, &window_title);
+            self.window_title = window_title;
+        }
+    }
+
+    fn scrub_timeline(&mut self, x: f32) {
+        let timeline_width = match self.get_timeline_area() {
+            Some((timeline_width, _)) => timeline_width,
+            None => return,
+        };
+
+        let mut universe_field = self.world.write_resource::<UniverseField>();
+        let history_length = universe_field.field.history().map_or(0, |history| history.len());
+        let history_position = ((x / timeline_width).clamp(0.0, 1.0) * history_length as f32).round() as usize;
+        universe_field.field.rewind_to(history_position);
+    }
 }
 
 impl UniverseMouseUpdater for GameState {
@@ -52,22 +118,35 @@ impl UniverseMouseUpdater for GameState {
     }
 
     fn update_generation(&mut self) {
-        if !self.is_universe_update_enabled()
+        if !self.is_universe_update_enabled() || self.is_paused
         {
             return;
         }
 
         let mut universe_field = self.world.write_resource::<UniverseField>();
-        universe_field.field.next_generation();
+        if let Some(volume_field) = universe_field.volume_field.as_mut() {
+            volume_field.next_generation();
+            return;
+        }
+
+        match universe_field.continuous_field.as_mut() {
+            Some(continuous_field) => continuous_field.next_generation(),
+            None => step_discrete_field(&mut universe_field.field, &mut self.world.write_resource::<StatsStream>()),
+        }
     }
 
-    fn capture_mouse_pressed_position(&self, x: f32, y:f32) {
+    fn capture_mouse_pressed_position(&self, x: f32, y:f32, is_dragging: bool) {
+        let tile_width = self.world.read_resource::<Viewport>().tile_width;
+        let grid = self.world.read_resource::<UniverseField>().field.rule().grid();
+        let (row, column) = grid.get_cell_at(x / tile_width, y / tile_width);
+
         let mut input_queue = self.world.write_resource::<InputQueue>();
         input_queue.mouse_button_events.push(self.pressed_button);
         input_queue.pressed_cell_positions.push(Position {
-            x: (x / TILE_WIDTH) as u8,
-            y: (y / TILE_WIDTH) as u8,
+            x: column.max(0) as usize,
+            y: row.max(0) as usize,
         });
+        input_queue.is_drag_events.push(is_dragging);
     }
 }
 
@@ -81,6 +160,7 @@ impl EventHandler for GameState {
         while timer::check_update_time(context, DESIRED_FPS) {
             self.update_generation();
         }
+        self.update_window_title(context);
 
         // Run input system
         {
@@ -104,6 +184,14 @@ impl EventHandler for GameState {
     }
 
     fn mouse_button_down_event(&mut self, _ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        // Pressing the timeline pauses the universe and rewinds it to the pressed generation
+        if button == MouseButton::Left && self.is_timeline_position(y) {
+            self.is_paused = true;
+            self.is_scrubbing = true;
+            self.scrub_timeline(x);
+            return;
+        }
+
         // Filter mouse presses
         match button {
             MouseButton::Left => self.disable_universe_update(MouseButton::Left),
@@ -111,10 +199,17 @@ impl EventHandler for GameState {
             _ => return,
         }
 
-        self.capture_mouse_pressed_position(x, y);
+        self.capture_mouse_pressed_position(x, y, false);
     }
 
     fn mouse_button_up_event(&mut self, _ctx: &mut Context, button: MouseButton, _x: f32, _y: f32) {
+        if self.is_scrubbing {
+            self.is_scrubbing = false;
+            return;
+        }
+
+        self.world.write_resource::<InputQueue>().is_edit_finished = true;
+
         // Filter mouse presses
         match button {
             MouseButton::Left => self.enable_universe_update(),
@@ -124,12 +219,60 @@ impl EventHandler for GameState {
     }
 
     fn mouse_motion_event(&mut self, _ctx: &mut Context, x: f32, y: f32, _dx: f32, _dy: f32) {
+        if self.is_scrubbing {
+            self.scrub_timeline(x);
+            return;
+        }
+
         if self.is_universe_update_enabled()
         {
             return;
         }
 
-        self.capture_mouse_pressed_position(x, y);
+        self.capture_mouse_pressed_position(x, y, true);
+    }
+
+    // Mouse wheel moves the view of the volume field through its layers
+    fn mouse_wheel_event(&mut self, _ctx: &mut Context, _x: f32, y: f32) {
+        let universe_depth = match &self.world.read_resource::<UniverseField>().volume_field {
+            Some(volume_field) => volume_field.shape().0,
+            None => return,
+        };
+
+        self.world.write_resource::<Viewport>().scroll_layers(y.signum() as isize, universe_depth);
+    }
+
+    // Space pauses, Z or left arrow undoes the last generation or edit, Y or right arrow redoes it
+    // or steps forward once there is nothing to redo, R replaces the generation by one of its predecessors,
+    // C shows the census of the objects in the universe in the title
+    fn key_down_event(&mut self, context: &mut Context, keycode: KeyCode, _keymods: KeyMods, _repeat: bool) {
+        self.status = None;
+        let mut universe_field = self.world.write_resource::<UniverseField>();
+        match keycode {
+            KeyCode::Escape => event::quit(context),
+            KeyCode::Space => self.is_paused = !self.is_paused,
+            KeyCode::Z | KeyCode::Left => {
+                self.is_paused = true;
+                universe_field.field.step_back();
+            }
+            KeyCode::Y | KeyCode::Right => {
+                self.is_paused = true;
+                if !universe_field.field.step_forward() {
+                    step_discrete_field(&mut universe_field.field, &mut self.world.write_resource::<StatsStream>());
+                }
+            }
+            KeyCode::C => self.status = Some(universe_field.field.take_census(DEFAULT_CENSUS_MAX_GENERATIONS).summary()),
+            // Search runs on the event loop, so it is kept short and the title tells when it found nothing
+            KeyCode::R if universe_field.field.is_reversible() => {
+                self.is_paused = true;
+                self.status = match universe_field.field.reverse_generation_within(INTERACTIVE_PREDECESSOR_SEARCH_STEP_LIMIT) {
+                    PredecessorSearchResult::Predecessor(_) => None,
+                    PredecessorSearchResult::GardenOfEden => Some(String::from("Garden of Eden, no predecessor exists")),
+                    PredecessorSearchResult::SearchLimitReached => Some(String::from("no predecessor found")),
+                };
+            }
+            _ => (),
+        }
     }
 }
 
@@ -140,8 +283,8 @@ pub fn initialize_level(world: &mut World) {
         for column in 0..universe_width{
             // Create the position at which to create something on the map
             let position = Position {
-                x: column as u8,
-                y: row as u8,
+                x: column,
+                y: row,
             };
 
             create_cell(world, position);
@@ -149,26 +292,57 @@ pub fn initialize_level(world: &mut World) {
     }
 }
 
-fn generate_game_state() -> GameState {
+fn generate_game_state(arguments: &Arguments) -> GameState {
     let mut world = World::new();
     register_components(&mut world);
-    register_resources(&mut world);
+    register_resources(&mut world, arguments);
     initialize_level(&mut world);
 
     return GameState {
         world,
-        pressed_button: UNIVERSE_UPDATE_ENABLED_STATE
+        pressed_button: UNIVERSE_UPDATE_ENABLED_STATE,
+        is_paused: false,
+        is_scrubbing: false,
+        status: None,
+        window_title: String::from(WINDOW_TITLE),
     };
 }
 
 pub fn main() -> GameResult {
     // Create the game state
-    let game = generate_game_state();
+    let arguments = parse_arguments();
+
+    // Soup search runs without the window and prints its report instead
+    if let Some(number_of_soups) = arguments.number_of_soups {
+        let settings = SoupSearchSettings {
+            rule: arguments.rule.clone(),
+            topology: arguments.topology,
+            soup_width: arguments.universe_width,
+            soup_height: arguments.universe_height,
+            initial_density: arguments.initial_density,
+            max_generations: arguments.max_generations,
+            first_seed: arguments.seed.unwrap_or_else(rand::random),
+            number_of_soups,
+        };
+
+        println!("{}", run_soup_search(&settings).to_json());
+        return Ok(());
+    }
+
+    let game = generate_game_state(&arguments);
+
+    let (window_width, window_height) = match game.get_timeline_area() {
+        Some((timeline_width, timeline_top)) => (timeline_width, timeline_top + TIMELINE_HEIGHT),
+        None => {
+            let universe_field = game.world.read_resource::<UniverseField>();
+            game.world.read_resource::<Viewport>().window_dimensions(universe_field.field.rule().grid(), universe_field.field.shape())
+        }
+    };
 
     // Create a game context and event loop
     let context_builder = ggez::ContextBuilder::new("rust_game_of_life", "game_of_life")
-        .window_setup(conf::WindowSetup::default().title("Conway's Game Of Life!"))
-        .window_mode(conf::WindowMode::default().dimensions((UNIVERSE_WIDTH as f32)*TILE_WIDTH, (UNIVERSE_HEIGHT as f32)*TILE_WIDTH))
+        .window_setup(conf::WindowSetup::default().title(WINDOW_TITLE))
+        .window_mode(conf::WindowMode::default().dimensions(window_width, window_height))
         .add_resource_path(path::PathBuf::from("./resources"));
 
     let (context, event_loop) = context_builder.build()?;
@@ -0,0 +1,310 @@
+use nalgebra::DMatrix;
+use crate::rule::*;
+use crate::topology::*;
+use crate::universe::*;
+
+pub const DEFAULT_PREDECESSOR_SEARCH_STEP_LIMIT: u64 = 10_000_000;
+
+// Subsets of at most this many unknown neighbours are enumerated exactly,
+// larger ones are bounded by the range of sums they can add
+const MAX_ENUMERATED_UNKNOWN_NEIGHBOURS: usize = 8;
+
+type WeightedPositions = Vec<((usize, usize), u32)>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PredecessorSearchResult {
+    Predecessor(UniversePlane),
+    // No generation evolves into the target, which makes it a Garden of Eden
+    GardenOfEden,
+    SearchLimitReached,
+}
+
+// Target cell demanding the state which its predecessor cell and the weighted neighbours have to produce
+struct CellConstraint {
+    target_state: u8,
+    own_variable: usize,
+    weighted_variables: Vec<(usize, u32)>,
+}
+
+// Backtracking search over the predecessor cells in the order of rows, so that the constraints of the target cells
+// get fully known early. Each assignment is checked against the constraints of the target cells it takes part in,
+// which are kept only while some assignment of the unknown cells could still satisfy them
+struct PredecessorSearch {
+    next_states: Vec<[u8; 2]>,
+    variable_positions: Vec<(usize, usize)>,
+    constraints: Vec<CellConstraint>,
+    variable_constraints: Vec<Vec<usize>>,
+    variable_states: Vec<Option<u8>>,
+    plane_shape: (usize, usize),
+}
+
+impl PredecessorSearch {
+    fn new<F: Fn(usize, usize) -> bool>(rule: &Rule, topology: Topology, target: &UniversePlane, is_cell_constrained: F) -> Self {
+        let plane_shape = target.shape();
+        let (plane_height, plane_width) = plane_shape;
+        let rule_kernels = rule.kernels();
+        let grid = rule.grid();
+
+        // Cells of the kernel around each constrained target cell, with their weights merged
+        // when a small wrapped plane makes the kernel see the same cell twice
+        let mut neighbourhoods: Vec<((usize, usize), WeightedPositions)> = Vec::new();
+        let mut is_variable = DMatrix::from_element(plane_height, plane_width, false);
+        for row_index in 0..plane_height {
+            for column_index in 0..plane_width {
+                if !is_cell_constrained(row_index, column_index) {
+                    continue;
+                }
+
+                let rule_kernel = &rule_kernels[grid.get_cell_parity(row_index, column_index)];
+                let (kernel_height_half, kernel_width_half) = (rule_kernel.nrows() / 2, rule_kernel.ncols() / 2);
+                let mut weighted_positions: WeightedPositions = Vec::new();
+                for kernel_row in 0..rule_kernel.nrows() {
+                    for kernel_column in 0..rule_kernel.ncols() {
+                        let weight = rule_kernel[(kernel_row, kernel_column)];
+                        let row = row_index as isize + kernel_row as isize - kernel_height_half as isize;
+                        let column = column_index as isize + kernel_column as isize - kernel_width_half as isize;
+                        let position = match topology.get_plane_position(row, column, plane_shape) {
+                            Some(position) if weight != 0 => position,
+                            _ => continue,
+                        };
+
+                        match weighted_positions.iter_mut().find(|(weighted_position, _)| *weighted_position == position) {
+                            Some((_, merged_weight)) => *merged_weight += weight,
+                            None => weighted_positions.push((position, weight)),
+                        }
+                        is_variable[position] = true;
+                    }
+                }
+
+                is_variable[(row_index, column_index)] = true;
+                neighbourhoods.push(((row_index, column_index), weighted_positions));
+            }
+        }
+
+        let mut variable_positions = Vec::new();
+        let mut variable_indices = DMatrix::from_element(plane_height, plane_width, usize::MAX);
+        for row_index in 0..plane_height {
+            for column_index in 0..plane_width {
+                if is_variable[(row_index, column_index)] {
+                    variable_indices[(row_index, column_index)] = variable_positions.len();
+                    variable_positions.push((row_index, column_index));
+                }
+            }
+        }
+
+        let mut variable_constraints = vec![Vec::new(); variable_positions.len()];
+        let constraints: Vec<CellConstraint> = neighbourhoods
+            .into_iter()
+            .enumerate()
+            .map(|(constraint_index, (position, weighted_positions))| {
+                let own_variable = variable_indices[position];
+                let weighted_variables: Vec<(usize, u32)> = weighted_positions
+                    .into_iter()
+                    .map(|(weighted_position, weight)| (variable_indices[weighted_position], weight))
+                    .collect();
+
+                variable_constraints[own_variable].push(constraint_index);
+                for (variable, _) in weighted_variables.iter().filter(|(variable, _)| *variable != own_variable) {
+                    variable_constraints[*variable].push(constraint_index);
+                }
+
+                CellConstraint { target_state: target[position], own_variable, weighted_variables }
+            })
+            .collect();
+
+        let max_neighbourhood = rule_kernels.iter().map(|rule_kernel| rule_kernel.sum()).max().unwrap_or(0);
+        let next_states = (0..=max_neighbourhood)
+            .map(|neighbourhood| [
+                Universe::get_next_cell_state(rule, CELL_IS_FREE, neighbourhood),
+                Universe::get_next_cell_state(rule, CELL_IS_POPULATED, neighbourhood),
+            ])
+            .collect();
+
+        PredecessorSearch {
+            next_states,
+            variable_states: vec![None; variable_positions.len()],
+            variable_positions,
+            constraints,
+            variable_constraints,
+            plane_shape,
+        }
+    }
+
+    fn is_producing_target(&self, constraint: &CellConstraint, own_states: &[u8], neighbourhood: u32) -> bool {
+        own_states.iter().any(|own_state| self.next_states[neighbourhood as usize][*own_state as usize] == constraint.target_state)
+    }
+
+    fn is_satisfiable(&self, constraint: &CellConstraint) -> bool {
+        let own_states: &[u8] = match self.variable_states[constraint.own_variable] {
+            Some(CELL_IS_FREE) => &[CELL_IS_FREE],
+            Some(_) => &[CELL_IS_POPULATED],
+            None => &[CELL_IS_FREE, CELL_IS_POPULATED],
+        };
+
+        let mut known_neighbourhood = 0;
+        let mut unknown_weights = Vec::new();
+        for (variable, weight) in constraint.weighted_variables.iter() {
+            match self.variable_states[*variable] {
+                Some(variable_state) => known_neighbourhood += weight * variable_state as u32,
+                None => unknown_weights.push(*weight),
+            }
+        }
+
+        if unknown_weights.len() > MAX_ENUMERATED_UNKNOWN_NEIGHBOURS {
+            let max_neighbourhood = known_neighbourhood + unknown_weights.iter().sum::<u32>();
+            return (known_neighbourhood..=max_neighbourhood).any(|neighbourhood| self.is_producing_target(constraint, own_states, neighbourhood));
+        }
+
+        (0..1_u32 << unknown_weights.len()).any(|unknown_states| {
+            let unknown_neighbourhood: u32 = unknown_weights
+                .iter()
+                .enumerate()
+                .filter(|(weight_index, _)| unknown_states & (1 << weight_index) != 0)
+                .map(|(_, weight)| weight)
+                .sum();
+
+            self.is_producing_target(constraint, own_states, known_neighbourhood + unknown_neighbourhood)
+        })
+    }
+
+    fn assign(&mut self, variable: usize, variable_state: u8) -> bool {
+        self.variable_states[variable] = Some(variable_state);
+        self.variable_constraints[variable].iter().all(|constraint_index| self.is_satisfiable(&self.constraints[*constraint_index]))
+    }
+
+    fn get_predecessor(&self) -> UniversePlane {
+        let (plane_height, plane_width) = self.plane_shape;
+        let mut predecessor = UniversePlane::from_element(plane_height, plane_width, CELL_IS_FREE);
+        for (position, variable_state) in self.variable_positions.iter().zip(self.variable_states.iter()) {
+            predecessor[*position] = variable_state.unwrap_or(CELL_IS_FREE);
+        }
+
+        predecessor
+    }
+
+    // Free state is tried first, so that sparser predecessors are found sooner
+    fn run(&mut self, step_limit: u64) -> PredecessorSearchResult {
+        let number_of_variables = self.variable_positions.len();
+        let mut number_of_tried_states = vec![0_u8; number_of_variables];
+        let mut variable = 0;
+        let mut number_of_steps = 0;
+
+        loop {
+            if variable == number_of_variables {
+                return PredecessorSearchResult::Predecessor(self.get_predecessor());
+            }
+
+            if number_of_tried_states[variable] == 2 {
+                number_of_tried_states[variable] = 0;
+                self.variable_states[variable] = None;
+                if variable == 0 {
+                    return PredecessorSearchResult::GardenOfEden;
+                }
+
+                variable -= 1;
+                continue;
+            }
+
+            number_of_steps += 1;
+            if number_of_steps > step_limit {
+                return PredecessorSearchResult::SearchLimitReached;
+            }
+
+            let variable_state = number_of_tried_states[variable];
+            number_of_tried_states[variable] += 1;
+            if self.assign(variable, variable_state) {
+                variable += 1;
+            }
+        }
+    }
+}
+
+// Find a generation which the rule turns into the target within the cells for which `is_cell_constrained` holds,
+// the other cells of the target may be anything. Cells not seen by the constrained ones are left free
+pub fn find_predecessor<F: Fn(usize, usize) -> bool>(rule: &Rule, topology: Topology, target: &UniversePlane, is_cell_constrained: F, step_limit: u64) -> PredecessorSearchResult {
+    if rule.number_of_states() != 2 {
+        panic!("'find_predecessor' expects a rule of two states, received {}.", rule);
+    }
+
+    if target.iter().any(|cell_state| *cell_state > CELL_IS_POPULATED) {
+        return PredecessorSearchResult::GardenOfEden;
+    }
+
+    PredecessorSearch::new(rule, topology, target, is_cell_constrained).run(step_limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step_plane(rule: &Rule, topology: Topology, plane: &UniversePlane) -> UniversePlane {
+        let (plane_height, plane_width) = plane.shape();
+        let mut universe = Universe::new(plane_width, plane_height, topology);
+        universe.set_rule(rule.clone());
+        for row_index in 0..plane_height {
+            for column_index in 0..plane_width {
+                universe.set_cell_state(row_index, column_index, plane[(row_index, column_index)]);
+            }
+        }
+
+        universe.next_generation();
+        UniversePlane::from_fn(plane_height, plane_width, |row_index, column_index| universe.get_cell_state(row_index, column_index))
+    }
+
+    #[test]
+    fn predecessor_evolves_into_target() {
+        let mut target = UniversePlane::from_element(6, 6, CELL_IS_FREE);
+        for position in [(1, 2), (2, 3), (3, 1), (3, 2), (3, 3)] {
+            target[position] = CELL_IS_POPULATED;
+        }
+
+        for (rule, topology) in [(Rule::conway(), Topology::Torus), (Rule::conway(), Topology::Plane), ("B2-a/S12".parse().unwrap(), Topology::Torus)] {
+            match find_predecessor(&rule, topology, &target, |_, _| true, DEFAULT_PREDECESSOR_SEARCH_STEP_LIMIT) {
+                PredecessorSearchResult::Predecessor(predecessor) => assert_eq!(step_plane(&rule, topology, &predecessor), target),
+                search_result => panic!("Expected a predecessor of the glider, received {:?}.", search_result),
+            }
+        }
+    }
+
+    // On the 3x3 torus every cell sees all the others, so cells of the same state always share their fate
+    // and a single populated cell can't be produced
+    #[test]
+    fn single_cell_of_smallest_torus_is_garden_of_eden() {
+        let mut target = UniversePlane::from_element(3, 3, CELL_IS_FREE);
+        target[(1, 1)] = CELL_IS_POPULATED;
+
+        let search_result = find_predecessor(&Rule::conway(), Topology::Torus, &target, |_, _| true, DEFAULT_PREDECESSOR_SEARCH_STEP_LIMIT);
+        assert_eq!(search_result, PredecessorSearchResult::GardenOfEden);
+
+        let mut target = UniversePlane::from_element(3, 3, CELL_IS_POPULATED);
+        target[(1, 1)] = CELL_IS_FREE;
+        let search_result = find_predecessor(&Rule::conway(), Topology::Torus, &target, |_, _| true, DEFAULT_PREDECESSOR_SEARCH_STEP_LIMIT);
+        assert_eq!(search_result, PredecessorSearchResult::GardenOfEden);
+    }
+
+    #[test]
+    fn region_is_matched_regardless_of_other_cells() {
+        let mut target = UniversePlane::from_element(8, 8, CELL_IS_POPULATED);
+        target.slice_mut((2, 2), (3, 3)).fill(CELL_IS_FREE);
+        target[(3, 3)] = CELL_IS_POPULATED;
+
+        let is_in_region = |row_index: usize, column_index: usize| (2..5).contains(&row_index) && (2..5).contains(&column_index);
+        match find_predecessor(&Rule::conway(), Topology::Plane, &target, is_in_region, DEFAULT_PREDECESSOR_SEARCH_STEP_LIMIT) {
+            PredecessorSearchResult::Predecessor(predecessor) => {
+                let successor = step_plane(&Rule::conway(), Topology::Plane, &predecessor);
+                assert_eq!(successor.slice((2, 2), (3, 3)), target.slice((2, 2), (3, 3)));
+            }
+            search_result => panic!("Expected a predecessor of the region, received {:?}.", search_result),
+        }
+    }
+
+    #[test]
+    fn nothing_comes_from_rule_without_births_and_survivals() {
+        let mut target = UniversePlane::from_element(5, 5, CELL_IS_FREE);
+        assert!(matches!(find_predecessor(&"B/S".parse().unwrap(), Topology::Torus, &target, |_, _| true, 100), PredecessorSearchResult::Predecessor(_)));
+
+        target[(0, 0)] = CELL_IS_POPULATED;
+        assert_eq!(find_predecessor(&"B/S".parse().unwrap(), Topology::Torus, &target, |_, _| true, 100), PredecessorSearchResult::GardenOfEden);
+        assert_eq!(find_predecessor(&Rule::conway(), Topology::Torus, &target, |_, _| true, 3), PredecessorSearchResult::SearchLimitReached);
+    }
+}
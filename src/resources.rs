@@ -1,27 +1,169 @@
 use specs::World;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{LineWriter, Write};
 use ggez::event::MouseButton;
+use crate::arguments::*;
+use crate::cell_age::*;
 use crate::components::*;
+use crate::constants::*;
+use crate::continuous_universe::*;
+use crate::generation_stats::*;
+use crate::grid::*;
+use crate::stochastic::*;
+use crate::topology::*;
 use crate::universe::*;
+use crate::volume_universe::*;
 
 // Resources
 #[derive(Default)]
 pub struct InputQueue {
     pub mouse_button_events: Vec<MouseButton>,
     pub pressed_cell_positions: Vec<Position>,
+    pub is_drag_events: Vec<bool>,
+    // State set by the last click of a multi-state rule, dragging paints it over the other cells
+    pub painted_cell_state: u8,
+    // Set when the mouse button is released, so that the cells edited since the press get undone together
+    pub is_edit_finished: bool,
 }
 
+// Continuous or volume field replaces the discrete one when a continuous or three-dimensional rule is chosen
 pub struct UniverseField {
     pub field: Universe,
+    pub continuous_field: Option<ContinuousUniverse>,
+    pub volume_field: Option<VolumeUniverse>,
 }
 
 impl Default for UniverseField {
     fn default() -> Self {
-        Self { field: Universe::new_random() }
+        Self {
+            field: Universe::new_random(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::default()),
+            continuous_field: None,
+            volume_field: None,
+        }
+    }
+}
+
+// Statistics of the discrete field are written to the CSV file line by line, since the event loop exits the process
+// without dropping the resources, and kept for the sparkline as the generations pass. Only stepped generations
+// are recorded, moves through the history leave the statistics as they are
+#[derive(Default)]
+pub struct StatsStream {
+    pub csv_writer: Option<LineWriter<File>>,
+    pub is_sparkline_shown: bool,
+    pub populations: VecDeque<usize>,
+}
+
+impl StatsStream {
+    pub fn is_recording(&self) -> bool {
+        self.csv_writer.is_some() || self.is_sparkline_shown
+    }
+
+    pub fn record(&mut self, generation_stats: &GenerationStats) {
+        if let Some(csv_writer) = self.csv_writer.as_mut() {
+            writeln!(csv_writer, "{}", generation_stats).expect("expected to write statistics");
+        }
+
+        if self.populations.len() == SPARKLINE_LENGTH {
+            self.populations.pop_front();
+        }
+        self.populations.push_back(generation_stats.population());
+    }
+}
+
+// Volume field is shown either a layer at a time or as the isometric projection of all its layers.
+// Discrete cells may be tinted by their age and leave fading trails for a few generations once they die
+pub struct Viewport {
+    pub tile_width: f32,
+    pub visible_layer: usize,
+    pub is_isometric: bool,
+    pub age_colour_ramp: Option<ColourRamp>,
+    pub ghost_trail_length: usize,
+}
+
+impl Viewport {
+    // Shrink tiles of large universes so that the window still fits the screen
+    pub fn fit_universe(grid: Grid, universe_shape: (usize, usize)) -> Self {
+        let (plane_width, plane_height) = grid.get_plane_size(universe_shape);
+        let tile_width = TILE_WIDTH
+            .min(MAX_WINDOW_WIDTH / plane_width)
+            .min(MAX_WINDOW_HEIGHT / plane_height);
+
+        Self { tile_width, ..Self::default() }
+    }
+
+    // Scrolling past the first or the last layer wraps around like the volume itself
+    pub fn scroll_layers(&mut self, layer_offset: isize, universe_depth: usize) {
+        self.visible_layer = (self.visible_layer as isize + layer_offset).rem_euclid(universe_depth as isize) as usize;
+    }
+
+    pub fn window_dimensions(&self, grid: Grid, universe_shape: (usize, usize)) -> (f32, f32) {
+        let (plane_width, plane_height) = grid.get_plane_size(universe_shape);
+        (plane_width * self.tile_width, plane_height * self.tile_width)
+    }
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self { tile_width: TILE_WIDTH, visible_layer: 0, is_isometric: false, age_colour_ramp: None, ghost_trail_length: 0 }
     }
 }
 
 // Registering resources
-pub fn register_resources(world: &mut World) {
+pub fn register_resources(world: &mut World, arguments: &Arguments) {
+    // Rule tables start with an empty universe to be drawn in, random states would mean nothing there
+    let seed = arguments.seed.unwrap_or_else(rand::random);
+    let mut universe = match &arguments.rule_table {
+        Some(rule_table) => {
+            let mut universe = Universe::new_seeded(arguments.universe_width, arguments.universe_height, arguments.topology, seed);
+            universe.set_rule_table(rule_table.clone());
+            universe
+        }
+        None => {
+            let mut universe = Universe::new_random_seeded(arguments.universe_width, arguments.universe_height, arguments.topology, seed, arguments.initial_density);
+            universe.set_rule(arguments.rule.clone());
+            universe
+        }
+    };
+    universe.set_parallel(arguments.is_parallel);
+    universe.set_transition_probabilities(TransitionProbabilities::new(arguments.birth_probability, arguments.survival_probability));
+    universe.set_update_mode(arguments.update_mode);
+    universe.set_history_capacity(arguments.history_capacity);
+    universe.set_cycle_detection(arguments.max_cycle_period);
+
+    let mut stats_stream = StatsStream { is_sparkline_shown: arguments.is_sparkline_shown, ..StatsStream::default() };
+    if let Some(stats_path) = &arguments.stats_path {
+        let stats_file = File::create(stats_path).unwrap_or_else(|error| panic!("Statistics cannot be written to '{}': {}.", stats_path, error));
+        let mut csv_writer = LineWriter::new(stats_file);
+        writeln!(csv_writer, "{}", GENERATION_STATS_CSV_HEADER).expect("expected to write statistics");
+        stats_stream.csv_writer = Some(csv_writer);
+    }
+
+    universe.set_stats_collection(stats_stream.is_recording());
+    if let Some(generation_stats) = universe.generation_stats() {
+        stats_stream.record(generation_stats);
+    }
+
+    let continuous_universe = arguments.continuous_rule.as_ref().map(|continuous_rule| {
+        let mut continuous_universe = ContinuousUniverse::new_random(arguments.universe_width, arguments.universe_height, arguments.topology);
+        continuous_universe.set_rule(continuous_rule.clone());
+        continuous_universe
+    });
+
+    let volume_universe = arguments.volume_rule.map(|volume_rule| {
+        let mut volume_universe = VolumeUniverse::new_random_seeded(arguments.universe_width, arguments.universe_height, arguments.universe_depth, seed, arguments.initial_density);
+        volume_universe.set_rule(volume_rule);
+        volume_universe
+    });
+
+    let mut viewport = Viewport::fit_universe(universe.rule().grid(), universe.shape());
+    viewport.is_isometric = arguments.is_isometric;
+    viewport.age_colour_ramp = arguments.age_colour_ramp.clone();
+    viewport.ghost_trail_length = arguments.ghost_trail_length;
+    universe.set_age_tracking(viewport.age_colour_ramp.is_some() || viewport.ghost_trail_length > 0);
+
     world.insert(InputQueue::default());
-    world.insert(UniverseField::default());
+    world.insert(stats_stream);
+    world.insert(viewport);
+    world.insert(UniverseField { field: universe, continuous_field: continuous_universe, volume_field: volume_universe });
 }
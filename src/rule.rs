@@ -0,0 +1,1049 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use nalgebra::DMatrix;
+use crate::grid::*;
+
+pub const MAX_NUMBER_OF_NEIGHBOURS: usize = 8;
+pub const NUMBER_OF_NEIGHBOURHOODS: usize = 1 << MAX_NUMBER_OF_NEIGHBOURS;
+pub const LIFE_NUMBER_OF_STATES: u8 = 2;
+pub const LIFE_RANGE: usize = 1;
+
+// Neighbours are numbered clockwise starting from the north one,
+// so that a neighbourhood is a byte with one bit per alive neighbour
+pub const NORTH_NEIGHBOUR: u32 = 1 << 0;
+pub const NORTH_EAST_NEIGHBOUR: u32 = 1 << 1;
+pub const EAST_NEIGHBOUR: u32 = 1 << 2;
+pub const SOUTH_EAST_NEIGHBOUR: u32 = 1 << 3;
+pub const SOUTH_NEIGHBOUR: u32 = 1 << 4;
+pub const SOUTH_WEST_NEIGHBOUR: u32 = 1 << 5;
+pub const WEST_NEIGHBOUR: u32 = 1 << 6;
+pub const NORTH_WEST_NEIGHBOUR: u32 = 1 << 7;
+const ALL_NEIGHBOURS: u32 = (NUMBER_OF_NEIGHBOURHOODS - 1) as u32;
+
+const RULE_PARTS_SEPARATOR: char = '/';
+const BIRTH_PREFIX: char = 'B';
+const SURVIVAL_PREFIX: char = 'S';
+const NUMBER_OF_STATES_PREFIX: char = 'C';
+const EXCLUDED_LETTERS_PREFIX: char = '-';
+
+const LARGER_THAN_LIFE_PARTS_SEPARATOR: char = ',';
+const RANGE_PREFIX: char = 'R';
+const MIDDLE_CELL_PREFIX: char = 'M';
+const NEIGHBOURHOOD_PREFIX: char = 'N';
+const NEIGHBOUR_COUNT_RANGE_SEPARATOR: &str = "..";
+
+// Neighbour counts of triangular grids above nine are written as hexadecimal digits A, B and C
+const GRID_NEIGHBOUR_COUNT_RADIX: u32 = 16;
+
+pub type NeighbourCounts = [bool; MAX_NUMBER_OF_NEIGHBOURS + 1];
+pub type Neighbourhoods = [bool; NUMBER_OF_NEIGHBOURHOODS];
+pub type RuleKernel = DMatrix<u32>;
+
+type HenselLetter = (char, u32);
+
+// Hensel notation letters with a neighbourhood representing each of them, indexed by the number
+// of alive neighbours. Letters of 5, 6 and 7 neighbours represent complements of 3, 2 and 1 ones
+const HENSEL_LETTERS: [&[HenselLetter]; MAX_NUMBER_OF_NEIGHBOURS / 2 + 1] = [
+    &[],
+    &[
+        ('c', NORTH_EAST_NEIGHBOUR),
+        ('e', NORTH_NEIGHBOUR),
+    ],
+    &[
+        ('c', NORTH_EAST_NEIGHBOUR | SOUTH_EAST_NEIGHBOUR),
+        ('e', NORTH_NEIGHBOUR | EAST_NEIGHBOUR),
+        ('k', NORTH_NEIGHBOUR | SOUTH_EAST_NEIGHBOUR),
+        ('a', NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR),
+        ('i', NORTH_NEIGHBOUR | SOUTH_NEIGHBOUR),
+        ('n', NORTH_EAST_NEIGHBOUR | SOUTH_WEST_NEIGHBOUR),
+    ],
+    &[
+        ('c', NORTH_EAST_NEIGHBOUR | SOUTH_EAST_NEIGHBOUR | SOUTH_WEST_NEIGHBOUR),
+        ('e', NORTH_NEIGHBOUR | EAST_NEIGHBOUR | SOUTH_NEIGHBOUR),
+        ('k', NORTH_NEIGHBOUR | EAST_NEIGHBOUR | SOUTH_WEST_NEIGHBOUR),
+        ('a', NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR | EAST_NEIGHBOUR),
+        ('i', NORTH_WEST_NEIGHBOUR | NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR),
+        ('n', NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR | SOUTH_EAST_NEIGHBOUR),
+        ('y', NORTH_NEIGHBOUR | SOUTH_EAST_NEIGHBOUR | SOUTH_WEST_NEIGHBOUR),
+        ('q', NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR | SOUTH_WEST_NEIGHBOUR),
+        ('j', NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR | WEST_NEIGHBOUR),
+        ('r', NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR | SOUTH_NEIGHBOUR),
+    ],
+    &[
+        ('c', NORTH_EAST_NEIGHBOUR | SOUTH_EAST_NEIGHBOUR | SOUTH_WEST_NEIGHBOUR | NORTH_WEST_NEIGHBOUR),
+        ('e', NORTH_NEIGHBOUR | EAST_NEIGHBOUR | SOUTH_NEIGHBOUR | WEST_NEIGHBOUR),
+        ('k', NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR | SOUTH_EAST_NEIGHBOUR | WEST_NEIGHBOUR),
+        ('a', NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR | EAST_NEIGHBOUR | SOUTH_EAST_NEIGHBOUR),
+        ('i', NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR | SOUTH_EAST_NEIGHBOUR | SOUTH_NEIGHBOUR),
+        ('n', NORTH_WEST_NEIGHBOUR | NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR | SOUTH_EAST_NEIGHBOUR),
+        ('y', NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR | SOUTH_EAST_NEIGHBOUR | SOUTH_WEST_NEIGHBOUR),
+        ('q', NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR | EAST_NEIGHBOUR | SOUTH_WEST_NEIGHBOUR),
+        ('j', NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR | SOUTH_NEIGHBOUR | WEST_NEIGHBOUR),
+        ('r', NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR | EAST_NEIGHBOUR | SOUTH_NEIGHBOUR),
+        ('t', NORTH_WEST_NEIGHBOUR | NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR | SOUTH_NEIGHBOUR),
+        ('w', NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR | SOUTH_WEST_NEIGHBOUR | WEST_NEIGHBOUR),
+        ('z', NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR | SOUTH_NEIGHBOUR | SOUTH_WEST_NEIGHBOUR),
+    ],
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleParseError {
+    EmptyRule,
+    MissingSeparator,
+    TooManySeparators,
+    MixedNotation,
+    DuplicatedPrefix(char),
+    InvalidNeighbourCount(char),
+    DuplicatedNeighbourCount(u8),
+    InvalidNeighbourhoodLetter(u8, char),
+    MissingNeighbourhoodLetters(u8),
+    InvalidNumberOfStates(String),
+    MissingRange,
+    InvalidLargerThanLifeParameter(String),
+    InvalidNeighbourCountRange(String),
+}
+
+impl fmt::Display for RuleParseError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleParseError::EmptyRule => write!(formatter, "rulestring is empty"),
+            RuleParseError::MissingSeparator => write!(formatter, "rulestring has no '{}' separator", RULE_PARTS_SEPARATOR),
+            RuleParseError::TooManySeparators => write!(formatter, "rulestring has too many '{}' separators", RULE_PARTS_SEPARATOR),
+            RuleParseError::MixedNotation => write!(formatter, "rulestring mixes B/S and S/B notations"),
+            RuleParseError::DuplicatedPrefix(prefix) => write!(formatter, "rulestring has prefix '{}' twice", prefix),
+            RuleParseError::InvalidNeighbourCount(character) => write!(formatter, "'{}' is not a number of neighbours", character),
+            RuleParseError::DuplicatedNeighbourCount(count) => write!(formatter, "number of neighbours {} is repeated", count),
+            RuleParseError::InvalidNeighbourhoodLetter(count, letter) => write!(formatter, "'{}' is not a neighbourhood of {} neighbours", letter, count),
+            RuleParseError::MissingNeighbourhoodLetters(count) => write!(formatter, "number of neighbours {} has no letters after '{}'", count, EXCLUDED_LETTERS_PREFIX),
+            RuleParseError::InvalidNumberOfStates(states) => write!(formatter, "'{}' is not a number of states from {} to {}", states, LIFE_NUMBER_OF_STATES, u8::MAX),
+            RuleParseError::MissingRange => write!(formatter, "rulestring has no '{}' range", RANGE_PREFIX),
+            RuleParseError::InvalidLargerThanLifeParameter(parameter) => write!(formatter, "'{}' is not a Larger than Life parameter", parameter),
+            RuleParseError::InvalidNeighbourCountRange(neighbour_counts) => write!(formatter, "'{}' is not a range of neighbour counts", neighbour_counts),
+        }
+    }
+}
+
+impl Error for RuleParseError {}
+
+fn count_neighbours(neighbourhood: u32) -> usize {
+    neighbourhood.count_ones() as usize
+}
+
+// Rotate by 90 degrees and reflect the neighbourhood in every possible way
+fn get_symmetric_neighbourhoods(neighbourhood: u32) -> [u32; 8] {
+    let mut symmetric_neighbourhoods = [0; 8];
+
+    for (symmetry_index, symmetric_neighbourhood) in symmetric_neighbourhoods.iter_mut().enumerate() {
+        let quarter_turns = symmetry_index / 2;
+        let is_reflected = symmetry_index % 2 == 1;
+
+        for neighbour_index in 0..MAX_NUMBER_OF_NEIGHBOURS {
+            if neighbourhood & (1 << neighbour_index) == 0 {
+                continue;
+            }
+
+            let reflected_index = if is_reflected { (MAX_NUMBER_OF_NEIGHBOURS - neighbour_index) % MAX_NUMBER_OF_NEIGHBOURS } else { neighbour_index };
+            let symmetric_index = (reflected_index + 2 * quarter_turns) % MAX_NUMBER_OF_NEIGHBOURS;
+            *symmetric_neighbourhood |= 1 << symmetric_index;
+        }
+    }
+
+    symmetric_neighbourhoods
+}
+
+fn get_hensel_letters(number_of_neighbours: usize) -> &'static [HenselLetter] {
+    HENSEL_LETTERS[number_of_neighbours.min(MAX_NUMBER_OF_NEIGHBOURS - number_of_neighbours)]
+}
+
+fn get_hensel_representative(number_of_neighbours: usize, representative: u32) -> u32 {
+    if number_of_neighbours > MAX_NUMBER_OF_NEIGHBOURS / 2 { ALL_NEIGHBOURS ^ representative } else { representative }
+}
+
+// Totalistic neighbourhoods of 0 and 8 neighbours have no letter
+fn get_hensel_letter(neighbourhood: u32) -> Option<char> {
+    let number_of_neighbours = count_neighbours(neighbourhood);
+    let symmetric_neighbourhoods = get_symmetric_neighbourhoods(neighbourhood);
+
+    get_hensel_letters(number_of_neighbours)
+        .iter()
+        .find(|(_, representative)| symmetric_neighbourhoods.contains(&get_hensel_representative(number_of_neighbours, *representative)))
+        .map(|(letter, _)| *letter)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighbourhood {
+    Moore,
+    VonNeumann,
+    Circular,
+}
+
+impl Neighbourhood {
+    const MOORE_NOTATION: char = 'M';
+    const VON_NEUMANN_NOTATION: char = 'N';
+    const CIRCULAR_NOTATION: char = 'C';
+
+    pub fn contains(&self, row_offset: isize, column_offset: isize, range: usize) -> bool {
+        let range = range as isize;
+        match self {
+            Neighbourhood::Moore => row_offset.abs() <= range && column_offset.abs() <= range,
+            Neighbourhood::VonNeumann => row_offset.abs() + column_offset.abs() <= range,
+            // Cells within the distance of the range and a half, as in Golly and LifeViewer
+            Neighbourhood::Circular => (2 * row_offset).pow(2) + (2 * column_offset).pow(2) <= (2 * range + 1).pow(2),
+        }
+    }
+
+    fn notation(&self) -> char {
+        match self {
+            Neighbourhood::Moore => Neighbourhood::MOORE_NOTATION,
+            Neighbourhood::VonNeumann => Neighbourhood::VON_NEUMANN_NOTATION,
+            Neighbourhood::Circular => Neighbourhood::CIRCULAR_NOTATION,
+        }
+    }
+
+    fn from_notation(notation: char) -> Option<Self> {
+        match notation {
+            Neighbourhood::MOORE_NOTATION => Some(Neighbourhood::Moore),
+            Neighbourhood::VON_NEUMANN_NOTATION => Some(Neighbourhood::VonNeumann),
+            Neighbourhood::CIRCULAR_NOTATION => Some(Neighbourhood::Circular),
+            _ => None,
+        }
+    }
+}
+
+// Rule telling which neighbourhoods of alive neighbours give birth to a free cell
+// and which ones let a populated cell survive.
+//
+// Life-like rules look at the eight nearest neighbours: totalistic ones only care about
+// the number of alive neighbours, while isotropic non-totalistic ones tell apart their arrangements.
+// Larger than Life rules count alive cells within a neighbourhood of an arbitrary range.
+// Hexagonal and triangular grid rules count the cells touching the cell of the grid.
+// Generations rules have more than two states: a populated cell that doesn't survive
+// passes through the decay states before it gets free
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    grid: Grid,
+    neighbourhood: Neighbourhood,
+    range: usize,
+    is_middle_cell_included: bool,
+    birth: Vec<bool>,
+    survival: Vec<bool>,
+    number_of_states: u8,
+}
+
+impl Rule {
+    pub fn new(birth: NeighbourCounts, survival: NeighbourCounts) -> Self {
+        Rule::new_generations(birth, survival, LIFE_NUMBER_OF_STATES)
+    }
+
+    pub fn new_generations(birth: NeighbourCounts, survival: NeighbourCounts, number_of_states: u8) -> Self {
+        Rule::check_number_of_states(number_of_states);
+
+        Rule {
+            grid: Grid::Square,
+            neighbourhood: Neighbourhood::Moore,
+            range: LIFE_RANGE,
+            is_middle_cell_included: false,
+            birth: Rule::get_totalistic_neighbourhoods(&birth).to_vec(),
+            survival: Rule::get_totalistic_neighbourhoods(&survival).to_vec(),
+            number_of_states,
+        }
+    }
+
+    pub fn new_larger_than_life(
+        neighbourhood: Neighbourhood,
+        range: usize,
+        is_middle_cell_included: bool,
+        birth: &[RangeInclusive<u32>],
+        survival: &[RangeInclusive<u32>],
+        number_of_states: u8,
+    ) -> Self {
+        let is_included_in = |neighbour_counts: &[RangeInclusive<u32>], count: usize| {
+            neighbour_counts.iter().any(|neighbour_counts| neighbour_counts.contains(&(count as u32)))
+        };
+
+        let get_neighbour_counts = |neighbour_counts: &[RangeInclusive<u32>]| {
+            let mut counts: NeighbourCounts = [false; MAX_NUMBER_OF_NEIGHBOURS + 1];
+            for (count, is_included) in counts.iter_mut().enumerate() {
+                *is_included = is_included_in(neighbour_counts, count);
+            }
+            counts
+        };
+
+        let mut rule = Rule {
+            grid: Grid::Square,
+            neighbourhood,
+            range,
+            is_middle_cell_included,
+            birth: Vec::new(),
+            survival: Vec::new(),
+            number_of_states,
+        };
+
+        // Range 1 Moore neighbourhood without the middle cell is just a Life-like rule
+        if rule.is_life_like() {
+            return Rule::new_generations(get_neighbour_counts(birth), get_neighbour_counts(survival), number_of_states);
+        }
+
+        Rule::check_number_of_states(number_of_states);
+
+        let number_of_neighbour_counts = rule.count_kernel_cells() + 1;
+        rule.birth = (0..number_of_neighbour_counts).map(|count| is_included_in(birth, count)).collect();
+        rule.survival = (0..number_of_neighbour_counts).map(|count| is_included_in(survival, count)).collect();
+        rule
+    }
+
+    // Totalistic rule of the grid, neighbour counts run from zero up to the number of neighbours of the grid
+    pub fn new_on_grid(grid: Grid, birth: &[bool], survival: &[bool], number_of_states: u8) -> Self {
+        let number_of_neighbour_counts = grid.number_of_neighbours() + 1;
+        if birth.len() != number_of_neighbour_counts || survival.len() != number_of_neighbour_counts {
+            panic!("'Rule' expects {} neighbour counts on the {:?} grid, received {} and {}.", number_of_neighbour_counts, grid, birth.len(), survival.len());
+        }
+
+        if grid == Grid::Square {
+            let mut birth_counts: NeighbourCounts = [false; MAX_NUMBER_OF_NEIGHBOURS + 1];
+            let mut survival_counts: NeighbourCounts = [false; MAX_NUMBER_OF_NEIGHBOURS + 1];
+            birth_counts.copy_from_slice(birth);
+            survival_counts.copy_from_slice(survival);
+
+            return Rule::new_generations(birth_counts, survival_counts, number_of_states);
+        }
+
+        Rule::check_number_of_states(number_of_states);
+
+        Rule {
+            grid,
+            neighbourhood: Neighbourhood::Moore,
+            range: LIFE_RANGE,
+            is_middle_cell_included: false,
+            birth: birth.to_vec(),
+            survival: survival.to_vec(),
+            number_of_states,
+        }
+    }
+
+    pub fn conway() -> Self {
+        "B3/S23".parse().unwrap()
+    }
+
+    pub fn number_of_states(&self) -> u8 {
+        self.number_of_states
+    }
+
+    pub fn grid(&self) -> Grid {
+        self.grid
+    }
+
+    pub fn neighbourhood(&self) -> Neighbourhood {
+        self.neighbourhood
+    }
+
+    pub fn range(&self) -> usize {
+        self.range
+    }
+
+    // Life-like rules describe the arrangement of the eight nearest neighbours bit by bit
+    pub fn is_life_like(&self) -> bool {
+        self.grid == Grid::Square && self.neighbourhood == Neighbourhood::Moore && self.range == LIFE_RANGE && !self.is_middle_cell_included
+    }
+
+    // Convolving alive cells with the kernel yields the neighbourhood which `is_birth`
+    // and `is_survival` expect: the arrangement of alive neighbours for Life-like rules
+    // or the number of alive cells within the range for Larger than Life ones
+    pub fn kernel(&self) -> RuleKernel {
+        if self.grid != Grid::Square {
+            panic!("'kernel' expects a rule of the square grid, received {}, its cells of different parities need 'kernels'.", self);
+        }
+
+        let kernel_side = 2 * self.range + 1;
+
+        if self.is_life_like() {
+            return RuleKernel::from_row_slice(kernel_side, kernel_side, &[
+                NORTH_WEST_NEIGHBOUR, NORTH_NEIGHBOUR, NORTH_EAST_NEIGHBOUR,
+                WEST_NEIGHBOUR,       0,               EAST_NEIGHBOUR,
+                SOUTH_WEST_NEIGHBOUR, SOUTH_NEIGHBOUR, SOUTH_EAST_NEIGHBOUR,
+            ]);
+        }
+
+        RuleKernel::from_fn(kernel_side, kernel_side, |row_index, column_index| {
+            let row_offset = row_index as isize - self.range as isize;
+            let column_offset = column_index as isize - self.range as isize;
+
+            if row_offset == 0 && column_offset == 0 {
+                return self.is_middle_cell_included as u32;
+            }
+
+            self.neighbourhood.contains(row_offset, column_offset, self.range) as u32
+        })
+    }
+
+    // Kernel for each parity of the cells of the grid, see `Grid::get_cell_parity`
+    pub fn kernels(&self) -> Vec<RuleKernel> {
+        if self.grid == Grid::Square {
+            return vec![self.kernel()];
+        }
+
+        self.grid.kernels(self.is_middle_cell_included)
+    }
+
+    pub fn is_birth(&self, neighbourhood: u32) -> bool {
+        self.birth.get(neighbourhood as usize).copied().unwrap_or(false)
+    }
+
+    pub fn is_survival(&self, neighbourhood: u32) -> bool {
+        self.survival.get(neighbourhood as usize).copied().unwrap_or(false)
+    }
+
+    // Totalistic rules give the same result for every neighbourhood with the same number of alive neighbours
+    pub fn is_totalistic(&self) -> bool {
+        if !self.is_life_like() {
+            return true;
+        }
+
+        (0..NUMBER_OF_NEIGHBOURHOODS).all(|neighbourhood| {
+            let totalistic_neighbourhood = ((1u16 << count_neighbours(neighbourhood as u32)) - 1) as usize;
+            self.birth[neighbourhood] == self.birth[totalistic_neighbourhood] && self.survival[neighbourhood] == self.survival[totalistic_neighbourhood]
+        })
+    }
+
+    fn check_number_of_states(number_of_states: u8) {
+        if number_of_states < LIFE_NUMBER_OF_STATES {
+            panic!("'Rule' expects at least {} states, received {}.", LIFE_NUMBER_OF_STATES, number_of_states);
+        }
+    }
+
+    fn count_kernel_cells(&self) -> usize {
+        self.kernels()[0].iter().filter(|weight| **weight > 0).count()
+    }
+
+    fn get_totalistic_neighbourhoods(neighbour_counts: &NeighbourCounts) -> Neighbourhoods {
+        let mut neighbourhoods = [false; NUMBER_OF_NEIGHBOURHOODS];
+        for (neighbourhood, is_included) in neighbourhoods.iter_mut().enumerate() {
+            *is_included = neighbour_counts[count_neighbours(neighbourhood as u32)];
+        }
+
+        neighbourhoods
+    }
+
+    fn parse_hensel_letters(number_of_neighbours: u8, letters: &[char]) -> Result<(), RuleParseError> {
+        let hensel_letters = get_hensel_letters(number_of_neighbours as usize);
+
+        match letters.iter().find(|letter| !hensel_letters.iter().any(|(hensel_letter, _)| hensel_letter == *letter)) {
+            Some(invalid_letter) => Err(RuleParseError::InvalidNeighbourhoodLetter(number_of_neighbours, *invalid_letter)),
+            None => Ok(()),
+        }
+    }
+
+    // Parse numbers of neighbours optionally followed by Hensel notation letters like "2-a3ij"
+    fn parse_neighbourhoods(conditions: &str) -> Result<Neighbourhoods, RuleParseError> {
+        let mut neighbourhoods = [false; NUMBER_OF_NEIGHBOURHOODS];
+        let mut neighbour_counts = [false; MAX_NUMBER_OF_NEIGHBOURS + 1];
+        let mut characters = conditions.chars().peekable();
+
+        while let Some(character) = characters.next() {
+            let number_of_neighbours = match character.to_digit(10) {
+                Some(count) if (count as usize) <= MAX_NUMBER_OF_NEIGHBOURS => count as u8,
+                _ => return Err(RuleParseError::InvalidNeighbourCount(character)),
+            };
+
+            if neighbour_counts[number_of_neighbours as usize] {
+                return Err(RuleParseError::DuplicatedNeighbourCount(number_of_neighbours));
+            }
+
+            neighbour_counts[number_of_neighbours as usize] = true;
+
+            let is_excluding = characters.next_if_eq(&EXCLUDED_LETTERS_PREFIX).is_some();
+            let mut letters = Vec::new();
+            while let Some(letter) = characters.next_if(|character| character.is_alphabetic()) {
+                letters.push(letter.to_ascii_lowercase());
+            }
+
+            if is_excluding && letters.is_empty() {
+                return Err(RuleParseError::MissingNeighbourhoodLetters(number_of_neighbours));
+            }
+
+            Rule::parse_hensel_letters(number_of_neighbours, &letters)?;
+
+            for (neighbourhood, is_included) in neighbourhoods.iter_mut().enumerate() {
+                if count_neighbours(neighbourhood as u32) != number_of_neighbours as usize {
+                    continue;
+                }
+
+                let is_listed = get_hensel_letter(neighbourhood as u32).is_some_and(|letter| letters.contains(&letter));
+                *is_included = letters.is_empty() || is_listed != is_excluding;
+            }
+        }
+
+        Ok(neighbourhoods)
+    }
+
+    fn parse_grid_neighbour_counts(grid: Grid, conditions: &str) -> Result<Vec<bool>, RuleParseError> {
+        let mut neighbour_counts = vec![false; grid.number_of_neighbours() + 1];
+
+        for character in conditions.chars() {
+            let number_of_neighbours = match character.to_digit(GRID_NEIGHBOUR_COUNT_RADIX) {
+                Some(count) if (count as usize) <= grid.number_of_neighbours() => count as usize,
+                _ => return Err(RuleParseError::InvalidNeighbourCount(character)),
+            };
+
+            if neighbour_counts[number_of_neighbours] {
+                return Err(RuleParseError::DuplicatedNeighbourCount(number_of_neighbours as u8));
+            }
+
+            neighbour_counts[number_of_neighbours] = true;
+        }
+
+        Ok(neighbour_counts)
+    }
+
+    fn format_grid_neighbour_counts(formatter: &mut fmt::Formatter, neighbour_counts: &[bool]) -> fmt::Result {
+        for (number_of_neighbours, is_included) in neighbour_counts.iter().enumerate() {
+            if *is_included {
+                let digit = char::from_digit(number_of_neighbours as u32, GRID_NEIGHBOUR_COUNT_RADIX).unwrap().to_ascii_uppercase();
+                write!(formatter, "{}", digit)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse_number_of_states(number_of_states: &str) -> Result<u8, RuleParseError> {
+        let digits = number_of_states.strip_prefix(NUMBER_OF_STATES_PREFIX).unwrap_or(number_of_states);
+
+        match digits.parse::<u8>() {
+            Ok(number_of_states) if number_of_states >= LIFE_NUMBER_OF_STATES => Ok(number_of_states),
+            _ => Err(RuleParseError::InvalidNumberOfStates(String::from(number_of_states))),
+        }
+    }
+
+    // Letters are listed when they are fewer than the excluded ones
+    fn format_neighbourhoods(formatter: &mut fmt::Formatter, neighbourhoods: &[bool]) -> fmt::Result {
+        for number_of_neighbours in 0..=MAX_NUMBER_OF_NEIGHBOURS {
+            let hensel_letters = get_hensel_letters(number_of_neighbours);
+            if hensel_letters.is_empty() {
+                let totalistic_neighbourhood = ((1u16 << number_of_neighbours) - 1) as usize;
+                if neighbourhoods[totalistic_neighbourhood] {
+                    write!(formatter, "{}", number_of_neighbours)?;
+                }
+
+                continue;
+            }
+
+            let (included_letters, excluded_letters): (Vec<HenselLetter>, Vec<HenselLetter>) = hensel_letters
+                .iter()
+                .partition(|(_, representative)| neighbourhoods[get_hensel_representative(number_of_neighbours, *representative) as usize]);
+
+            if included_letters.is_empty() {
+                continue;
+            }
+
+            write!(formatter, "{}", number_of_neighbours)?;
+            if excluded_letters.is_empty() {
+                continue;
+            }
+
+            if included_letters.len() > excluded_letters.len() {
+                write!(formatter, "{}", EXCLUDED_LETTERS_PREFIX)?;
+                for (letter, _) in excluded_letters {
+                    write!(formatter, "{}", letter)?;
+                }
+            } else {
+                for (letter, _) in included_letters {
+                    write!(formatter, "{}", letter)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse_neighbour_count_range(neighbour_counts: &str) -> Result<RangeInclusive<u32>, RuleParseError> {
+        let invalid_range_error = || RuleParseError::InvalidNeighbourCountRange(String::from(neighbour_counts));
+
+        let (minimal_count, maximal_count) = neighbour_counts
+            .split_once(NEIGHBOUR_COUNT_RANGE_SEPARATOR)
+            .unwrap_or((neighbour_counts, neighbour_counts));
+
+        let minimal_count = minimal_count.parse::<u32>().map_err(|_| invalid_range_error())?;
+        let maximal_count = maximal_count.parse::<u32>().map_err(|_| invalid_range_error())?;
+
+        if minimal_count > maximal_count {
+            return Err(invalid_range_error());
+        }
+
+        Ok(minimal_count..=maximal_count)
+    }
+
+    // Parse Larger than Life rules like "R5,C0,M1,S34..58,B34..45,NM"
+    fn parse_larger_than_life(rulestring: &str) -> Result<Self, RuleParseError> {
+        let mut range = None;
+        let mut number_of_states = LIFE_NUMBER_OF_STATES;
+        let mut is_middle_cell_included = false;
+        let mut birth = Vec::new();
+        let mut survival = Vec::new();
+        let mut neighbourhood = Neighbourhood::Moore;
+
+        for parameter in rulestring.split(LARGER_THAN_LIFE_PARTS_SEPARATOR) {
+            let invalid_parameter_error = || RuleParseError::InvalidLargerThanLifeParameter(String::from(parameter));
+
+            let mut characters = parameter.chars();
+            let prefix = characters.next().ok_or_else(invalid_parameter_error)?;
+            let value = characters.as_str();
+
+            match prefix {
+                RANGE_PREFIX => range = Some(value.parse::<usize>().ok().filter(|range| *range > 0).ok_or_else(invalid_parameter_error)?),
+                NUMBER_OF_STATES_PREFIX => number_of_states = value.parse::<u8>().map_err(|_| invalid_parameter_error())?.max(LIFE_NUMBER_OF_STATES),
+                MIDDLE_CELL_PREFIX => is_middle_cell_included = match value {
+                    "0" => false,
+                    "1" => true,
+                    _ => return Err(invalid_parameter_error()),
+                },
+                SURVIVAL_PREFIX => survival.push(Rule::parse_neighbour_count_range(value)?),
+                BIRTH_PREFIX => birth.push(Rule::parse_neighbour_count_range(value)?),
+                NEIGHBOURHOOD_PREFIX => neighbourhood = match value.chars().collect::<Vec<char>>().as_slice() {
+                    [notation] => Neighbourhood::from_notation(*notation).ok_or_else(invalid_parameter_error)?,
+                    _ => return Err(invalid_parameter_error()),
+                },
+                _ => return Err(invalid_parameter_error()),
+            }
+        }
+
+        let range = range.ok_or(RuleParseError::MissingRange)?;
+        let rule = Rule::new_larger_than_life(
+            neighbourhood,
+            range,
+            is_middle_cell_included,
+            &birth,
+            &survival,
+            number_of_states,
+        );
+
+        let maximal_neighbour_count = rule.count_kernel_cells() as u32;
+        for neighbour_counts in birth.iter().chain(survival.iter()) {
+            if *neighbour_counts.end() > maximal_neighbour_count {
+                let neighbour_counts = format!("{}{}{}", neighbour_counts.start(), NEIGHBOUR_COUNT_RANGE_SEPARATOR, neighbour_counts.end());
+                return Err(RuleParseError::InvalidNeighbourCountRange(neighbour_counts));
+            }
+        }
+
+        Ok(rule)
+    }
+
+    // Neighbour counts are written as ranges, one per each run of consecutive counts
+    fn format_neighbour_count_ranges(formatter: &mut fmt::Formatter, prefix: char, neighbour_counts: &[bool]) -> fmt::Result {
+        let mut count = 0;
+        while count < neighbour_counts.len() {
+            if !neighbour_counts[count] {
+                count += 1;
+                continue;
+            }
+
+            let minimal_count = count;
+            while count + 1 < neighbour_counts.len() && neighbour_counts[count + 1] {
+                count += 1;
+            }
+
+            write!(formatter, "{}{}{}{}{}", LARGER_THAN_LIFE_PARTS_SEPARATOR, prefix, minimal_count, NEIGHBOUR_COUNT_RANGE_SEPARATOR, count)?;
+            count += 1;
+        }
+
+        Ok(())
+    }
+
+    fn format_larger_than_life(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let number_of_states = if self.number_of_states > LIFE_NUMBER_OF_STATES { self.number_of_states } else { 0 };
+
+        write!(formatter, "{}{}", RANGE_PREFIX, self.range)?;
+        write!(formatter, "{}{}{}", LARGER_THAN_LIFE_PARTS_SEPARATOR, NUMBER_OF_STATES_PREFIX, number_of_states)?;
+        write!(formatter, "{}{}{}", LARGER_THAN_LIFE_PARTS_SEPARATOR, MIDDLE_CELL_PREFIX, self.is_middle_cell_included as u8)?;
+        Rule::format_neighbour_count_ranges(formatter, SURVIVAL_PREFIX, &self.survival)?;
+        Rule::format_neighbour_count_ranges(formatter, BIRTH_PREFIX, &self.birth)?;
+        write!(formatter, "{}{}{}", LARGER_THAN_LIFE_PARTS_SEPARATOR, NEIGHBOURHOOD_PREFIX, self.neighbourhood.notation())
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule::conway()
+    }
+}
+
+// Parse "B36/S23" (or "S23/B36") and the older S/B notation "23/36",
+// numbers of neighbours can be refined with Hensel notation letters like "B2-a/S12",
+// Generations rules append the number of states like "B2/S/C3" or "/2/3".
+// Larger than Life rules are written like "R5,C0,M1,S34..58,B34..45,NM".
+// Hexagonal and triangular grid rules end with "H" and "L" respectively like "B2/S34H"
+impl FromStr for Rule {
+    type Err = RuleParseError;
+
+    fn from_str(rulestring: &str) -> Result<Self, Self::Err> {
+        let rulestring = rulestring.trim().to_uppercase();
+        if rulestring.is_empty() {
+            return Err(RuleParseError::EmptyRule);
+        }
+
+        if rulestring.starts_with(RANGE_PREFIX) {
+            return Rule::parse_larger_than_life(&rulestring);
+        }
+
+        let (rulestring, grid) = match rulestring.chars().last().and_then(Grid::from_notation) {
+            Some(grid) => (&rulestring[..rulestring.len() - 1], grid),
+            None => (rulestring.as_str(), Grid::Square),
+        };
+
+        let rule_parts: Vec<&str> = rulestring.split(RULE_PARTS_SEPARATOR).collect();
+        let (first_part, second_part, number_of_states) = match rule_parts.as_slice() {
+            [_] => return Err(RuleParseError::MissingSeparator),
+            [first_part, second_part] => (*first_part, *second_part, LIFE_NUMBER_OF_STATES),
+            [first_part, second_part, number_of_states] => (*first_part, *second_part, Rule::parse_number_of_states(number_of_states)?),
+            _ => return Err(RuleParseError::TooManySeparators),
+        };
+
+        let first_prefix = first_part.chars().next().filter(|character| character.is_alphabetic());
+        let second_prefix = second_part.chars().next().filter(|character| character.is_alphabetic());
+
+        let (birth_conditions, survival_conditions) = match (first_prefix, second_prefix) {
+            (None, None) => (second_part, first_part),
+            (Some(BIRTH_PREFIX), Some(SURVIVAL_PREFIX)) => (&first_part[1..], &second_part[1..]),
+            (Some(SURVIVAL_PREFIX), Some(BIRTH_PREFIX)) => (&second_part[1..], &first_part[1..]),
+            (Some(first_prefix), Some(second_prefix)) if first_prefix == second_prefix && (first_prefix == BIRTH_PREFIX || first_prefix == SURVIVAL_PREFIX) => {
+                return Err(RuleParseError::DuplicatedPrefix(first_prefix))
+            },
+            (Some(BIRTH_PREFIX), None) | (Some(SURVIVAL_PREFIX), None) | (None, Some(BIRTH_PREFIX)) | (None, Some(SURVIVAL_PREFIX)) => {
+                return Err(RuleParseError::MixedNotation)
+            },
+            (Some(unknown_prefix), _) | (_, Some(unknown_prefix)) => return Err(RuleParseError::InvalidNeighbourCount(unknown_prefix)),
+        };
+
+        if grid != Grid::Square {
+            let birth = Rule::parse_grid_neighbour_counts(grid, birth_conditions)?;
+            let survival = Rule::parse_grid_neighbour_counts(grid, survival_conditions)?;
+            return Ok(Rule::new_on_grid(grid, &birth, &survival, number_of_states));
+        }
+
+        Ok(Rule {
+            grid,
+            neighbourhood: Neighbourhood::Moore,
+            range: LIFE_RANGE,
+            is_middle_cell_included: false,
+            birth: Rule::parse_neighbourhoods(birth_conditions)?.to_vec(),
+            survival: Rule::parse_neighbourhoods(survival_conditions)?.to_vec(),
+            number_of_states,
+        })
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(grid_notation) = self.grid.notation() {
+            write!(formatter, "{}", BIRTH_PREFIX)?;
+            Rule::format_grid_neighbour_counts(formatter, &self.birth)?;
+            write!(formatter, "{}{}", RULE_PARTS_SEPARATOR, SURVIVAL_PREFIX)?;
+            Rule::format_grid_neighbour_counts(formatter, &self.survival)?;
+
+            if self.number_of_states > LIFE_NUMBER_OF_STATES {
+                write!(formatter, "{}{}{}", RULE_PARTS_SEPARATOR, NUMBER_OF_STATES_PREFIX, self.number_of_states)?;
+            }
+
+            return write!(formatter, "{}", grid_notation);
+        }
+
+        if !self.is_life_like() {
+            return self.format_larger_than_life(formatter);
+        }
+
+        write!(formatter, "{}", BIRTH_PREFIX)?;
+        Rule::format_neighbourhoods(formatter, &self.birth)?;
+        write!(formatter, "{}{}", RULE_PARTS_SEPARATOR, SURVIVAL_PREFIX)?;
+        Rule::format_neighbourhoods(formatter, &self.survival)?;
+
+        if self.number_of_states > LIFE_NUMBER_OF_STATES {
+            write!(formatter, "{}{}{}", RULE_PARTS_SEPARATOR, NUMBER_OF_STATES_PREFIX, self.number_of_states)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn neighbour_counts(counts: &[usize]) -> NeighbourCounts {
+        let mut neighbour_counts = [false; MAX_NUMBER_OF_NEIGHBOURS + 1];
+        for count in counts {
+            neighbour_counts[*count] = true;
+        }
+
+        neighbour_counts
+    }
+
+    #[test]
+    fn conway_rule_is_default() {
+        let rule = Rule::default();
+        assert_eq!(rule, Rule::new(neighbour_counts(&[3]), neighbour_counts(&[2, 3])));
+    }
+
+    #[test]
+    fn birth_survival_notation_is_parsed() {
+        let rule: Rule = "B36/S23".parse().unwrap();
+        assert_eq!(rule, Rule::new(neighbour_counts(&[3, 6]), neighbour_counts(&[2, 3])));
+    }
+
+    #[test]
+    fn survival_birth_notation_is_parsed() {
+        let rule: Rule = "23/36".parse().unwrap();
+        assert_eq!(rule, "B36/S23".parse().unwrap());
+
+        let rule: Rule = "S23/B36".parse().unwrap();
+        assert_eq!(rule, "B36/S23".parse().unwrap());
+    }
+
+    #[test]
+    fn empty_neighbour_counts_are_parsed() {
+        let rule: Rule = "B2/S".parse().unwrap();
+        assert_eq!(rule, Rule::new(neighbour_counts(&[2]), neighbour_counts(&[])));
+        assert!(!(0..NUMBER_OF_NEIGHBOURHOODS as u32).any(|neighbourhood| rule.is_survival(neighbourhood)));
+    }
+
+    #[test]
+    fn lowercase_rule_is_parsed() {
+        let rule: Rule = " b3678/s34678 ".parse().unwrap();
+        assert_eq!(rule, "B3678/S34678".parse().unwrap());
+    }
+
+    #[test]
+    fn rule_is_displayed_in_birth_survival_notation() {
+        let rule: Rule = "23/63".parse().unwrap();
+        assert_eq!(rule.to_string(), "B36/S23");
+    }
+
+    #[test]
+    fn malformed_rules_are_rejected() {
+        assert_eq!("".parse::<Rule>(), Err(RuleParseError::EmptyRule));
+        assert_eq!("B3S23".parse::<Rule>(), Err(RuleParseError::MissingSeparator));
+        assert_eq!("B3/S23/C3/C4".parse::<Rule>(), Err(RuleParseError::TooManySeparators));
+        assert_eq!("B3/23".parse::<Rule>(), Err(RuleParseError::MixedNotation));
+        assert_eq!("B3/B23".parse::<Rule>(), Err(RuleParseError::DuplicatedPrefix('B')));
+        assert_eq!("B39/S23".parse::<Rule>(), Err(RuleParseError::InvalidNeighbourCount('9')));
+        assert_eq!("B3/S2x".parse::<Rule>(), Err(RuleParseError::InvalidNeighbourhoodLetter(2, 'x')));
+        assert_eq!("B3/S-a".parse::<Rule>(), Err(RuleParseError::InvalidNeighbourCount('-')));
+        assert_eq!("B1k/S23".parse::<Rule>(), Err(RuleParseError::InvalidNeighbourhoodLetter(1, 'k')));
+        assert_eq!("B0c/S23".parse::<Rule>(), Err(RuleParseError::InvalidNeighbourhoodLetter(0, 'c')));
+        assert_eq!("B2-/S23".parse::<Rule>(), Err(RuleParseError::MissingNeighbourhoodLetters(2)));
+        assert_eq!("X3/S23".parse::<Rule>(), Err(RuleParseError::InvalidNeighbourCount('X')));
+        assert_eq!("B33/S23".parse::<Rule>(), Err(RuleParseError::DuplicatedNeighbourCount(3)));
+        assert_eq!("B2/S/C1".parse::<Rule>(), Err(RuleParseError::InvalidNumberOfStates(String::from("C1"))));
+        assert_eq!("/2/256".parse::<Rule>(), Err(RuleParseError::InvalidNumberOfStates(String::from("256"))));
+        assert_eq!("B2/S/CX".parse::<Rule>(), Err(RuleParseError::InvalidNumberOfStates(String::from("CX"))));
+    }
+
+    #[test]
+    fn generations_rules_are_parsed() {
+        let brians_brain: Rule = "/2/3".parse().unwrap();
+        assert_eq!(brians_brain, Rule::new_generations(neighbour_counts(&[2]), neighbour_counts(&[]), 3));
+        assert_eq!(brians_brain, "B2/S/C3".parse().unwrap());
+
+        let star_wars: Rule = "345/2/4".parse().unwrap();
+        assert_eq!(star_wars, Rule::new_generations(neighbour_counts(&[2]), neighbour_counts(&[3, 4, 5]), 4));
+        assert_eq!(star_wars.number_of_states(), 4);
+    }
+
+    #[test]
+    fn generations_rule_is_displayed_with_number_of_states() {
+        let rule: Rule = "345/2/4".parse().unwrap();
+        assert_eq!(rule.to_string(), "B2/S345/C4");
+
+        let rule: Rule = "B3/S23/C2".parse().unwrap();
+        assert_eq!(rule.to_string(), "B3/S23");
+    }
+
+    fn count_hensel_neighbourhoods(number_of_neighbours: usize, letter: char) -> usize {
+        (0..NUMBER_OF_NEIGHBOURHOODS as u32)
+            .filter(|neighbourhood| count_neighbours(*neighbourhood) == number_of_neighbours)
+            .filter(|neighbourhood| get_hensel_letter(*neighbourhood) == Some(letter))
+            .count()
+    }
+
+    #[test]
+    fn hensel_letters_split_neighbourhoods_into_symmetry_classes() {
+        let expected_numbers_of_letters = [0, 2, 6, 10, 13, 10, 6, 2, 0];
+        let expected_numbers_of_neighbourhoods = [1, 8, 28, 56, 70, 56, 28, 8, 1];
+
+        for number_of_neighbours in 0..=MAX_NUMBER_OF_NEIGHBOURS {
+            let hensel_letters = get_hensel_letters(number_of_neighbours);
+            assert_eq!(hensel_letters.len(), expected_numbers_of_letters[number_of_neighbours]);
+
+            let mut number_of_neighbourhoods = 0;
+            for (letter, representative) in hensel_letters {
+                let representative = get_hensel_representative(number_of_neighbours, *representative);
+                assert_eq!(count_neighbours(representative), number_of_neighbours);
+                assert_eq!(get_hensel_letter(representative), Some(*letter));
+
+                number_of_neighbourhoods += count_hensel_neighbourhoods(number_of_neighbours, *letter);
+            }
+
+            if hensel_letters.is_empty() {
+                number_of_neighbourhoods = 1;
+            }
+
+            assert_eq!(number_of_neighbourhoods, expected_numbers_of_neighbourhoods[number_of_neighbours]);
+        }
+
+        assert_eq!(count_hensel_neighbourhoods(2, 'c'), 4);
+        assert_eq!(count_hensel_neighbourhoods(2, 'a'), 8);
+        assert_eq!(count_hensel_neighbourhoods(4, 'e'), 1);
+        assert_eq!(count_hensel_neighbourhoods(4, 'c'), 1);
+    }
+
+    #[test]
+    fn hensel_letters_are_complemented_above_four_neighbours() {
+        let three_i = NORTH_WEST_NEIGHBOUR | NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR;
+        assert_eq!(get_hensel_letter(three_i), Some('i'));
+        assert_eq!(get_hensel_letter(ALL_NEIGHBOURS ^ three_i), Some('i'));
+
+        let six_k = ALL_NEIGHBOURS ^ (NORTH_NEIGHBOUR | SOUTH_EAST_NEIGHBOUR);
+        assert_eq!(get_hensel_letter(six_k), Some('k'));
+    }
+
+    #[test]
+    fn hensel_notation_of_every_letter_is_totalistic() {
+        let rule: Rule = "B3cekainyqjr/S2cekain3cekainyqjr".parse().unwrap();
+        assert_eq!(rule, Rule::conway());
+        assert!(rule.is_totalistic());
+    }
+
+    #[test]
+    fn hensel_notation_is_parsed() {
+        let rule: Rule = "B2-a/S12".parse().unwrap();
+        assert!(!rule.is_totalistic());
+
+        assert!(rule.is_birth(NORTH_NEIGHBOUR | SOUTH_NEIGHBOUR));
+        assert!(rule.is_birth(NORTH_EAST_NEIGHBOUR | SOUTH_WEST_NEIGHBOUR));
+        assert!(!rule.is_birth(NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR));
+        assert!(!rule.is_birth(WEST_NEIGHBOUR | SOUTH_WEST_NEIGHBOUR));
+        assert!(!rule.is_birth(NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR | EAST_NEIGHBOUR));
+        assert!(rule.is_survival(SOUTH_NEIGHBOUR));
+        assert!(rule.is_survival(NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR));
+
+        let rule: Rule = "B3/S23-a4i".parse().unwrap();
+        assert!(!rule.is_survival(WEST_NEIGHBOUR | NORTH_WEST_NEIGHBOUR | NORTH_NEIGHBOUR));
+        assert!(rule.is_survival(NORTH_WEST_NEIGHBOUR | NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR));
+        assert!(rule.is_survival(NORTH_NEIGHBOUR | NORTH_EAST_NEIGHBOUR | SOUTH_EAST_NEIGHBOUR | SOUTH_NEIGHBOUR));
+        assert!(!rule.is_survival(NORTH_NEIGHBOUR | EAST_NEIGHBOUR | SOUTH_NEIGHBOUR | WEST_NEIGHBOUR));
+    }
+
+    #[test]
+    fn hensel_notation_is_displayed_in_shortest_form() {
+        for rulestring in ["B2-a/S12", "B3/S23-a4i", "B2ci3ai4c8/S02ea3ekiqj4iz5ar6i7e", "B2e3-ky/S23/C4"] {
+            let rule: Rule = rulestring.parse().unwrap();
+            assert_eq!(rule.to_string(), rulestring);
+        }
+
+        let rule: Rule = "B2ic3ia4c8/S02ae3eijkq4zi5ra6i7e".parse().unwrap();
+        assert_eq!(rule.to_string(), "B2ci3ai4c8/S02ea3ekiqj4iz5ar6i7e");
+
+        let rule: Rule = "B2cekin/S2-cekain".parse().unwrap();
+        assert_eq!(rule.to_string(), "B2-a/S");
+    }
+
+    #[test]
+    fn larger_than_life_rule_is_parsed() {
+        let rule: Rule = "R5,C0,M1,S34..58,B34..45,NM".parse().unwrap();
+        assert_eq!(rule.neighbourhood(), Neighbourhood::Moore);
+        assert_eq!(rule.range(), 5);
+        assert_eq!(rule.number_of_states(), LIFE_NUMBER_OF_STATES);
+        assert!(!rule.is_life_like());
+        assert!(rule.is_totalistic());
+
+        assert!(!rule.is_birth(33));
+        assert!(rule.is_birth(34));
+        assert!(rule.is_birth(45));
+        assert!(!rule.is_birth(46));
+        assert!(!rule.is_survival(33));
+        assert!(rule.is_survival(58));
+        assert!(!rule.is_survival(59));
+    }
+
+    #[test]
+    fn larger_than_life_rule_is_displayed() {
+        for rulestring in ["R5,C0,M1,S34..58,B34..45,NM", "R2,C3,M0,S1..2,S5..6,B3..3,NN", "R3,C0,M1,B10..14,NC"] {
+            let rule: Rule = rulestring.parse().unwrap();
+            assert_eq!(rule.to_string(), rulestring);
+        }
+
+        let rule: Rule = "r2,m1,b3,s4..5,s6,nc".parse().unwrap();
+        assert_eq!(rule.to_string(), "R2,C0,M1,S4..6,B3..3,NC");
+    }
+
+    #[test]
+    fn larger_than_life_rule_of_range_one_is_life_like() {
+        let rule: Rule = "R1,C0,M0,S2..3,B3..3,NM".parse().unwrap();
+        assert!(rule.is_life_like());
+        assert_eq!(rule, Rule::conway());
+        assert_eq!(rule.to_string(), "B3/S23");
+    }
+
+    #[test]
+    fn larger_than_life_kernel_depends_on_neighbourhood() {
+        let count_kernel_cells = |rulestring: &str| {
+            let rule: Rule = rulestring.parse().unwrap();
+            rule.kernel().iter().sum::<u32>()
+        };
+
+        assert_eq!(count_kernel_cells("R2,M0,NM"), 24);
+        assert_eq!(count_kernel_cells("R2,M1,NM"), 25);
+        assert_eq!(count_kernel_cells("R2,M0,NN"), 12);
+        assert_eq!(count_kernel_cells("R2,M0,NC"), 20);
+        assert_eq!(count_kernel_cells("R3,M0,NC"), 36);
+        assert_eq!(count_kernel_cells("R5,M1,NM"), 121);
+
+        let rule: Rule = "R7,NM".parse().unwrap();
+        assert_eq!(rule.kernel().shape(), (15, 15));
+    }
+
+    #[test]
+    fn invalid_larger_than_life_rule_is_rejected() {
+        assert_eq!("R0,C0,M0,S2..3,B3..3,NM".parse::<Rule>(), Err(RuleParseError::InvalidLargerThanLifeParameter(String::from("R0"))));
+        assert_eq!("R2,C0,M2,S2..3,B3..3,NM".parse::<Rule>(), Err(RuleParseError::InvalidLargerThanLifeParameter(String::from("M2"))));
+        assert_eq!("R2,C0,M0,S2..3,B3..3,NX".parse::<Rule>(), Err(RuleParseError::InvalidLargerThanLifeParameter(String::from("NX"))));
+        assert_eq!("R2,C0,M0,S2..3,,NM".parse::<Rule>(), Err(RuleParseError::InvalidLargerThanLifeParameter(String::new())));
+        assert_eq!("R2,C0,M0,S3..2,NM".parse::<Rule>(), Err(RuleParseError::InvalidNeighbourCountRange(String::from("3..2"))));
+        assert_eq!("R2,C0,M0,S2..,NM".parse::<Rule>(), Err(RuleParseError::InvalidNeighbourCountRange(String::from("2.."))));
+        assert_eq!("R1,C0,M0,S2..9,NM".parse::<Rule>(), Err(RuleParseError::InvalidNeighbourCountRange(String::from("2..9"))));
+    }
+
+    #[test]
+    fn grid_rule_is_parsed_and_displayed() {
+        let rule: Rule = "B2/S34H".parse().unwrap();
+        assert_eq!(rule.grid(), Grid::Hexagonal);
+        assert!(!rule.is_life_like());
+        assert!(rule.is_birth(2) && !rule.is_birth(3));
+        assert!(rule.is_survival(3) && rule.is_survival(4) && !rule.is_survival(6));
+        assert_eq!(rule.to_string(), "B2/S34H");
+
+        let rule: Rule = "b4/s3ab/3l".parse().unwrap();
+        assert_eq!(rule.grid(), Grid::Triangular);
+        assert_eq!(rule.number_of_states(), 3);
+        assert!(rule.is_survival(10) && rule.is_survival(11) && !rule.is_survival(12));
+        assert_eq!(rule.to_string(), "B4/S3AB/C3L");
+        assert_eq!(rule.to_string().parse::<Rule>(), Ok(rule));
+
+        assert_eq!("34/2H".parse::<Rule>(), "B2/S34H".parse::<Rule>());
+    }
+
+    #[test]
+    fn grid_rule_rejects_too_many_neighbours() {
+        assert_eq!("B7/S2H".parse::<Rule>(), Err(RuleParseError::InvalidNeighbourCount('7')));
+        assert_eq!("B2/SDL".parse::<Rule>(), Err(RuleParseError::InvalidNeighbourCount('D')));
+        assert!("B2a/S2H".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn grid_rule_has_kernel_per_parity() {
+        let rule: Rule = "B2/S34H".parse().unwrap();
+        assert_eq!(rule.kernels(), Grid::Hexagonal.kernels(false));
+        assert_eq!(Rule::conway().kernels(), vec![Rule::conway().kernel()]);
+    }
+}
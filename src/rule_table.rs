@@ -0,0 +1,582 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+const WIREWORLD_RULE: &str = include_str!("../resources/rules/Wireworld.rule");
+
+const SECTION_PREFIX: char = '@';
+const COMMENT_PREFIX: char = '#';
+const RULE_SECTION: &str = "@RULE";
+const TABLE_SECTION: &str = "@TABLE";
+const COLOURS_SECTION: &str = "@COLORS";
+const TREE_SECTION: &str = "@TREE";
+
+const KEY_SEPARATOR: char = ':';
+const ENTRIES_SEPARATOR: char = ',';
+const NUMBER_OF_STATES_KEY: &str = "n_states";
+const NEIGHBOURHOOD_KEY: &str = "neighborhood";
+const SYMMETRIES_KEY: &str = "symmetries";
+const VARIABLE_PREFIX: &str = "var ";
+const VARIABLE_ASSIGNMENT: char = '=';
+const VARIABLE_VALUES_START: char = '{';
+const VARIABLE_VALUES_END: char = '}';
+
+// Golly lists the neighbours clockwise starting from the north one
+const MOORE_NEIGHBOUR_OFFSETS: [(isize, isize); 8] = [(-1, 0), (-1, 1), (0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1)];
+const VON_NEUMANN_NEIGHBOUR_OFFSETS: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleTableParseError {
+    Unreadable(String),
+    MissingTable,
+    UnsupportedTree,
+    MissingNumberOfStates,
+    InvalidNumberOfStates(String),
+    UnknownNeighbourhood(String),
+    UnknownSymmetries(String),
+    UnknownVariable(String),
+    InvalidState(String),
+    InvalidLine(usize, String),
+}
+
+impl fmt::Display for RuleTableParseError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleTableParseError::Unreadable(error) => write!(formatter, "rule file is unreadable: {}", error),
+            RuleTableParseError::MissingTable => write!(formatter, "rule has no '{}' section", TABLE_SECTION),
+            RuleTableParseError::UnsupportedTree => write!(formatter, "rule trees of '{}' section are not supported", TREE_SECTION),
+            RuleTableParseError::MissingNumberOfStates => write!(formatter, "table has no '{}'", NUMBER_OF_STATES_KEY),
+            RuleTableParseError::InvalidNumberOfStates(states) => write!(formatter, "'{}' is not a number of states from 2 to 255", states),
+            RuleTableParseError::UnknownNeighbourhood(neighbourhood) => write!(formatter, "'{}' is not one of Moore, vonNeumann", neighbourhood),
+            RuleTableParseError::UnknownSymmetries(symmetries) => write!(formatter, "'{}' are not supported symmetries", symmetries),
+            RuleTableParseError::UnknownVariable(variable) => write!(formatter, "variable '{}' is not defined", variable),
+            RuleTableParseError::InvalidState(state) => write!(formatter, "'{}' is not a state of the table", state),
+            RuleTableParseError::InvalidLine(line_number, line) => write!(formatter, "line {} '{}' is invalid", line_number, line),
+        }
+    }
+}
+
+impl Error for RuleTableParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableNeighbourhood {
+    Moore,
+    VonNeumann,
+}
+
+impl TableNeighbourhood {
+    pub fn neighbour_offsets(&self) -> &'static [(isize, isize)] {
+        match self {
+            TableNeighbourhood::Moore => &MOORE_NEIGHBOUR_OFFSETS,
+            TableNeighbourhood::VonNeumann => &VON_NEUMANN_NEIGHBOUR_OFFSETS,
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "moore" => Some(TableNeighbourhood::Moore),
+            "vonneumann" => Some(TableNeighbourhood::VonNeumann),
+            _ => None,
+        }
+    }
+}
+
+// Symmetries tell which rearrangements of the neighbours a transition stands for besides the written one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetries {
+    None,
+    Rotate2,
+    Rotate4,
+    Rotate8,
+    Reflect,
+    Rotate4Reflect,
+    Rotate8Reflect,
+    Permute,
+}
+
+impl Symmetries {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(Symmetries::None),
+            "rotate2" => Some(Symmetries::Rotate2),
+            "rotate4" => Some(Symmetries::Rotate4),
+            "rotate8" => Some(Symmetries::Rotate8),
+            "reflect" => Some(Symmetries::Reflect),
+            "rotate4reflect" => Some(Symmetries::Rotate4Reflect),
+            "rotate8reflect" => Some(Symmetries::Rotate8Reflect),
+            "permute" => Some(Symmetries::Permute),
+            _ => None,
+        }
+    }
+
+    // Neighbours are listed clockwise, so rotations shift them and the reflection reverses them
+    // leaving the north neighbour in place. Eight rotations exist only in the Moore neighbourhood
+    fn get_rearrangements(&self, number_of_neighbours: usize) -> Option<Vec<Vec<usize>>> {
+        let rotation_step = number_of_neighbours / 8;
+        let rotation_steps: Vec<usize> = match self {
+            Symmetries::None | Symmetries::Reflect => vec![0],
+            Symmetries::Rotate2 => vec![0, number_of_neighbours / 2],
+            Symmetries::Rotate4 | Symmetries::Rotate4Reflect => (0..4).map(|rotation| rotation * number_of_neighbours / 4).collect(),
+            Symmetries::Rotate8 | Symmetries::Rotate8Reflect if rotation_step > 0 => (0..8).map(|rotation| rotation * rotation_step).collect(),
+            _ => return None,
+        };
+
+        let is_reflected = matches!(self, Symmetries::Reflect | Symmetries::Rotate4Reflect | Symmetries::Rotate8Reflect);
+        let mut rearrangements = Vec::new();
+        for rotation_step in rotation_steps {
+            let rotation: Vec<usize> = (0..number_of_neighbours).map(|index| (index + rotation_step) % number_of_neighbours).collect();
+            if is_reflected {
+                rearrangements.push(rotation.iter().map(|index| (number_of_neighbours - index) % number_of_neighbours).collect());
+            }
+            rearrangements.push(rotation);
+        }
+
+        Some(rearrangements)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TableEntry {
+    State(u8),
+    Variable(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Transition {
+    current_state: TableEntry,
+    neighbour_states: Vec<TableEntry>,
+    next_state: TableEntry,
+}
+
+// Rule given by the table of transitions like Golly's `.rule` files: each transition lists the states
+// of the cell and its neighbours followed by the next state of the cell, variables stand for sets of states
+// and take the same state wherever they occur within the transition. The first matching transition wins,
+// cells matching none keep their state
+#[derive(Debug, Clone)]
+pub struct RuleTable {
+    name: String,
+    number_of_states: u8,
+    neighbourhood: TableNeighbourhood,
+    symmetries: Symmetries,
+    variables: Vec<Vec<u8>>,
+    transitions: Vec<Transition>,
+    state_colours: HashMap<u8, (u8, u8, u8)>,
+    next_states: HashMap<Vec<u8>, u8>,
+}
+
+// Memoised next states depend on the table alone, so they don't tell tables apart
+impl PartialEq for RuleTable {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.number_of_states == other.number_of_states
+            && self.neighbourhood == other.neighbourhood
+            && self.symmetries == other.symmetries
+            && self.variables == other.variables
+            && self.transitions == other.transitions
+            && self.state_colours == other.state_colours
+    }
+}
+
+impl Eq for RuleTable {}
+
+impl RuleTable {
+    pub fn wireworld() -> Self {
+        WIREWORLD_RULE.parse().unwrap()
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, RuleTableParseError> {
+        let rule_file = fs::read_to_string(path).map_err(|error| RuleTableParseError::Unreadable(error.to_string()))?;
+        rule_file.parse()
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn number_of_states(&self) -> u8 {
+        self.number_of_states
+    }
+
+    pub fn neighbourhood(&self) -> TableNeighbourhood {
+        self.neighbourhood
+    }
+
+    // Colour of the `@COLORS` section if there is one for the state
+    pub fn get_state_colour(&self, state: u8) -> Option<(u8, u8, u8)> {
+        self.state_colours.get(&state).copied()
+    }
+
+    fn match_entry(&self, entry: &TableEntry, state: u8, bound_states: &mut [Option<u8>]) -> bool {
+        match entry {
+            TableEntry::State(entry_state) => *entry_state == state,
+            TableEntry::Variable(variable_index) => match bound_states[*variable_index] {
+                Some(bound_state) => bound_state == state,
+                None if self.variables[*variable_index].contains(&state) => {
+                    bound_states[*variable_index] = Some(state);
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+
+    // Match the neighbours against the entries in any order, backtracking whenever the variables get bound wrong
+    fn match_permuted(&self, entries: &[TableEntry], is_entry_used: &mut [bool], neighbour_states: &[u8], bound_states: &mut Vec<Option<u8>>) -> bool {
+        let (neighbour_state, other_neighbour_states) = match neighbour_states.split_first() {
+            Some(split) => split,
+            None => return true,
+        };
+
+        for (entry_index, entry) in entries.iter().enumerate() {
+            if is_entry_used[entry_index] {
+                continue;
+            }
+
+            let previously_bound_states = bound_states.clone();
+            if self.match_entry(entry, *neighbour_state, bound_states) {
+                is_entry_used[entry_index] = true;
+                if self.match_permuted(entries, is_entry_used, other_neighbour_states, bound_states) {
+                    return true;
+                }
+                is_entry_used[entry_index] = false;
+            }
+            *bound_states = previously_bound_states;
+        }
+
+        false
+    }
+
+    fn match_transition(&self, transition: &Transition, current_state: u8, neighbour_states: &[u8]) -> Option<u8> {
+        let get_next_state = |bound_states: &[Option<u8>]| match transition.next_state {
+            TableEntry::State(next_state) => next_state,
+            TableEntry::Variable(variable_index) => bound_states[variable_index].unwrap(),
+        };
+
+        let mut bound_states = vec![None; self.variables.len()];
+        if !self.match_entry(&transition.current_state, current_state, &mut bound_states) {
+            return None;
+        }
+
+        if self.symmetries == Symmetries::Permute {
+            let mut is_entry_used = vec![false; neighbour_states.len()];
+            return self
+                .match_permuted(&transition.neighbour_states, &mut is_entry_used, neighbour_states, &mut bound_states)
+                .then(|| get_next_state(&bound_states));
+        }
+
+        for rearrangement in self.symmetries.get_rearrangements(neighbour_states.len()).unwrap() {
+            let mut rearranged_bound_states = bound_states.clone();
+            let is_matching = rearrangement.iter().zip(neighbour_states).all(|(entry_index, neighbour_state)| {
+                self.match_entry(&transition.neighbour_states[*entry_index], *neighbour_state, &mut rearranged_bound_states)
+            });
+
+            if is_matching {
+                return Some(get_next_state(&rearranged_bound_states));
+            }
+        }
+
+        None
+    }
+
+    // States of the cell and its neighbours listed as in the neighbourhood, the result is memoised
+    pub fn get_next_state(&mut self, cell_states: &[u8]) -> u8 {
+        if let Some(next_state) = self.next_states.get(cell_states) {
+            return *next_state;
+        }
+
+        let (current_state, neighbour_states) = cell_states.split_first().unwrap();
+        let next_state = self.transitions
+            .iter()
+            .find_map(|transition| self.match_transition(transition, *current_state, neighbour_states))
+            .unwrap_or(*current_state);
+
+        self.next_states.insert(cell_states.to_vec(), next_state);
+        next_state
+    }
+
+    fn parse_state(state: &str, number_of_states: u8) -> Result<u8, RuleTableParseError> {
+        match state.parse::<u8>() {
+            Ok(parsed_state) if parsed_state < number_of_states => Ok(parsed_state),
+            _ => Err(RuleTableParseError::InvalidState(String::from(state))),
+        }
+    }
+
+    fn parse_variable(&mut self, definition: &str, variable_indices: &mut HashMap<String, usize>) -> Option<Result<(), RuleTableParseError>> {
+        let (name, values) = definition.split_once(VARIABLE_ASSIGNMENT)?;
+        let values = values.trim().strip_prefix(VARIABLE_VALUES_START)?.strip_suffix(VARIABLE_VALUES_END)?;
+
+        let mut states = Vec::new();
+        for value in values.split(ENTRIES_SEPARATOR).map(|value| value.trim()) {
+            match variable_indices.get(value) {
+                Some(variable_index) => states.extend_from_slice(&self.variables[*variable_index]),
+                None => match RuleTable::parse_state(value, self.number_of_states) {
+                    Ok(state) => states.push(state),
+                    Err(error) => return Some(Err(error)),
+                },
+            }
+        }
+
+        variable_indices.insert(String::from(name.trim()), self.variables.len());
+        self.variables.push(states);
+        Some(Ok(()))
+    }
+
+    // Entries are separated by commas, tables of up to ten states may also list them digit by digit
+    fn parse_transition(&self, line: &str, variable_indices: &HashMap<String, usize>) -> Result<Option<Transition>, RuleTableParseError> {
+        let entries: Vec<&str> = if line.contains(ENTRIES_SEPARATOR) {
+            line.split(ENTRIES_SEPARATOR).map(|entry| entry.trim()).collect()
+        } else if line.chars().all(|character| character.is_ascii_digit()) {
+            (0..line.len()).map(|index| &line[index..index + 1]).collect()
+        } else {
+            return Ok(None);
+        };
+
+        if entries.len() != self.neighbourhood.neighbour_offsets().len() + 2 {
+            return Ok(None);
+        }
+
+        let parse_entry = |entry: &str| match variable_indices.get(entry) {
+            Some(variable_index) => Ok(TableEntry::Variable(*variable_index)),
+            None if entry.chars().all(|character| character.is_ascii_digit()) => Ok(TableEntry::State(RuleTable::parse_state(entry, self.number_of_states)?)),
+            None => Err(RuleTableParseError::UnknownVariable(String::from(entry))),
+        };
+
+        let mut parsed_entries = entries.iter().map(|entry| parse_entry(entry)).collect::<Result<Vec<TableEntry>, _>>()?;
+        let next_state = parsed_entries.pop().unwrap();
+        let current_state = parsed_entries.remove(0);
+
+        // Next state can only be a variable which gets bound by the cell or its neighbours
+        if let TableEntry::Variable(variable_index) = next_state {
+            if !parsed_entries.iter().chain([current_state].iter()).any(|entry| *entry == TableEntry::Variable(variable_index)) {
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(Transition { current_state, neighbour_states: parsed_entries, next_state }))
+    }
+
+    // Colours are given either per state as `state red green blue`
+    // or as a gradient `red green blue red green blue` from the first populated state to the last one
+    fn parse_colours(&mut self, line: &str) -> bool {
+        let numbers: Option<Vec<u8>> = line.split_whitespace().map(|number| number.parse::<u8>().ok()).collect();
+
+        match numbers.as_deref() {
+            Some([state, red, green, blue]) => {
+                self.state_colours.insert(*state, (*red, *green, *blue));
+                true
+            }
+            Some([first_red, first_green, first_blue, last_red, last_green, last_blue]) => {
+                let number_of_gradient_states = (self.number_of_states.max(2) - 1) as f32;
+                for state in 1..self.number_of_states {
+                    let progress = if number_of_gradient_states > 1.0 { (state - 1) as f32 / (number_of_gradient_states - 1.0) } else { 0.0 };
+                    let mix = |first: u8, last: u8| (first as f32 + (last as f32 - first as f32) * progress).round() as u8;
+                    self.state_colours.insert(state, (mix(*first_red, *last_red), mix(*first_green, *last_green), mix(*first_blue, *last_blue)));
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+// Parse the `@RULE`, `@TABLE` and `@COLORS` sections of a Golly `.rule` file, other sections are skipped
+impl FromStr for RuleTable {
+    type Err = RuleTableParseError;
+
+    fn from_str(rule_file: &str) -> Result<Self, Self::Err> {
+        let mut rule_table = RuleTable {
+            name: String::new(),
+            number_of_states: 0,
+            neighbourhood: TableNeighbourhood::Moore,
+            symmetries: Symmetries::None,
+            variables: Vec::new(),
+            transitions: Vec::new(),
+            state_colours: HashMap::new(),
+            next_states: HashMap::new(),
+        };
+
+        let mut variable_indices = HashMap::new();
+        let mut section = "";
+        let mut is_table_found = false;
+
+        for (line_index, line) in rule_file.lines().enumerate() {
+            let line = line.split(COMMENT_PREFIX).next().unwrap().trim();
+            let invalid_line_error = || RuleTableParseError::InvalidLine(line_index + 1, String::from(line));
+            if line.is_empty() {
+                continue;
+            }
+
+            if line.starts_with(SECTION_PREFIX) {
+                let (section_name, section_argument) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+                section = match section_name {
+                    RULE_SECTION => {
+                        rule_table.name = String::from(section_argument.trim());
+                        RULE_SECTION
+                    }
+                    TABLE_SECTION => {
+                        is_table_found = true;
+                        TABLE_SECTION
+                    }
+                    COLOURS_SECTION => COLOURS_SECTION,
+                    TREE_SECTION => return Err(RuleTableParseError::UnsupportedTree),
+                    _ => "",
+                };
+                continue;
+            }
+
+            match section {
+                TABLE_SECTION => {
+                    if let Some((key, value)) = line.split_once(KEY_SEPARATOR) {
+                        let value = value.trim();
+                        match key.trim() {
+                            NUMBER_OF_STATES_KEY => rule_table.number_of_states = match value.parse::<u8>() {
+                                Ok(number_of_states) if number_of_states >= 2 => number_of_states,
+                                _ => return Err(RuleTableParseError::InvalidNumberOfStates(String::from(value))),
+                            },
+                            NEIGHBOURHOOD_KEY => rule_table.neighbourhood = TableNeighbourhood::from_name(value)
+                                .ok_or_else(|| RuleTableParseError::UnknownNeighbourhood(String::from(value)))?,
+                            SYMMETRIES_KEY => rule_table.symmetries = Symmetries::from_name(value)
+                                .ok_or_else(|| RuleTableParseError::UnknownSymmetries(String::from(value)))?,
+                            _ => return Err(invalid_line_error()),
+                        }
+                        continue;
+                    }
+
+                    if rule_table.number_of_states == 0 {
+                        return Err(RuleTableParseError::MissingNumberOfStates);
+                    }
+
+                    if let Some(definition) = line.strip_prefix(VARIABLE_PREFIX) {
+                        rule_table.parse_variable(definition, &mut variable_indices).ok_or_else(invalid_line_error)??;
+                        continue;
+                    }
+
+                    let transition = rule_table.parse_transition(line, &variable_indices)?.ok_or_else(invalid_line_error)?;
+                    rule_table.transitions.push(transition);
+                }
+                COLOURS_SECTION if !rule_table.parse_colours(line) => return Err(invalid_line_error()),
+                _ => (),
+            }
+        }
+
+        if !is_table_found {
+            return Err(RuleTableParseError::MissingTable);
+        }
+
+        if rule_table.number_of_states == 0 {
+            return Err(RuleTableParseError::MissingNumberOfStates);
+        }
+
+        let number_of_neighbours = rule_table.neighbourhood.neighbour_offsets().len();
+        if rule_table.symmetries != Symmetries::Permute && rule_table.symmetries.get_rearrangements(number_of_neighbours).is_none() {
+            return Err(RuleTableParseError::UnknownSymmetries(format!("{:?}", rule_table.symmetries).to_lowercase()));
+        }
+
+        Ok(rule_table)
+    }
+}
+
+impl fmt::Display for RuleTable {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIREWORLD_EMPTY: u8 = 0;
+    const WIREWORLD_HEAD: u8 = 1;
+    const WIREWORLD_TAIL: u8 = 2;
+    const WIREWORLD_CONDUCTOR: u8 = 3;
+
+    fn get_wireworld_next_state(rule_table: &mut RuleTable, current_state: u8, number_of_heads: usize) -> u8 {
+        let mut cell_states = vec![current_state];
+        for neighbour_index in 0..8 {
+            cell_states.push(if neighbour_index < number_of_heads { WIREWORLD_HEAD } else { WIREWORLD_CONDUCTOR });
+        }
+
+        rule_table.get_next_state(&cell_states)
+    }
+
+    #[test]
+    fn wireworld_is_built_in() {
+        let mut wireworld = RuleTable::wireworld();
+        assert_eq!(wireworld.name(), "Wireworld");
+        assert_eq!(wireworld.number_of_states(), 4);
+        assert_eq!(wireworld.get_state_colour(WIREWORLD_HEAD), Some((0, 128, 255)));
+
+        assert_eq!(get_wireworld_next_state(&mut wireworld, WIREWORLD_EMPTY, 3), WIREWORLD_EMPTY);
+        assert_eq!(get_wireworld_next_state(&mut wireworld, WIREWORLD_HEAD, 0), WIREWORLD_TAIL);
+        assert_eq!(get_wireworld_next_state(&mut wireworld, WIREWORLD_TAIL, 5), WIREWORLD_CONDUCTOR);
+
+        for (number_of_heads, next_state) in [(0, WIREWORLD_CONDUCTOR), (1, WIREWORLD_HEAD), (2, WIREWORLD_HEAD), (3, WIREWORLD_CONDUCTOR)] {
+            assert_eq!(get_wireworld_next_state(&mut wireworld, WIREWORLD_CONDUCTOR, number_of_heads), next_state);
+        }
+
+        // Heads are counted wherever they are
+        assert_eq!(wireworld.get_next_state(&[WIREWORLD_CONDUCTOR, 0, 0, 0, 0, 0, 1, 0, 1]), WIREWORLD_HEAD);
+
+        // Memo filled by the lookups above is left out of equality
+        assert_eq!(wireworld, RuleTable::wireworld());
+    }
+
+    #[test]
+    fn symmetries_rearrange_neighbours() {
+        let rule_file = "@RULE Test\n@TABLE\nn_states:2\nneighborhood:vonNeumann\nsymmetries:rotate4\n0,1,1,0,0,1\n";
+        let mut rule_table: RuleTable = rule_file.parse().unwrap();
+
+        // Two adjacent neighbours in any rotation, but not the opposite ones
+        assert_eq!(rule_table.get_next_state(&[0, 1, 1, 0, 0]), 1);
+        assert_eq!(rule_table.get_next_state(&[0, 0, 0, 1, 1]), 1);
+        assert_eq!(rule_table.get_next_state(&[0, 1, 0, 0, 1]), 1);
+        assert_eq!(rule_table.get_next_state(&[0, 1, 0, 1, 0]), 0);
+
+        let rule_file = "@TABLE\nn_states:2\nneighborhood:Moore\nsymmetries:none\n0,1,0,0,0,0,0,0,0,1\n";
+        let mut rule_table: RuleTable = rule_file.parse().unwrap();
+        assert_eq!(rule_table.get_next_state(&[0, 1, 0, 0, 0, 0, 0, 0, 0]), 1);
+        assert_eq!(rule_table.get_next_state(&[0, 0, 1, 0, 0, 0, 0, 0, 0]), 0);
+
+        let rule_file = "@TABLE\nn_states:2\nneighborhood:Moore\nsymmetries:reflect\n0,0,1,0,0,0,0,0,0,1\n";
+        let mut rule_table: RuleTable = rule_file.parse().unwrap();
+        assert_eq!(rule_table.get_next_state(&[0, 0, 0, 0, 0, 0, 0, 0, 1]), 1);
+        assert_eq!(rule_table.get_next_state(&[0, 0, 0, 1, 0, 0, 0, 0, 0]), 0);
+    }
+
+    #[test]
+    fn variables_are_bound_within_transition() {
+        let rule_file = "@TABLE\nn_states:3\nneighborhood:vonNeumann\nsymmetries:none\nvar a={1,2}\nvar b={a}\n0,a,a,b,0,a\n";
+        let mut rule_table: RuleTable = rule_file.parse().unwrap();
+
+        assert_eq!(rule_table.get_next_state(&[0, 2, 2, 1, 0]), 2);
+        assert_eq!(rule_table.get_next_state(&[0, 1, 1, 1, 0]), 1);
+        assert_eq!(rule_table.get_next_state(&[0, 1, 2, 1, 0]), 0);
+    }
+
+    #[test]
+    fn compact_transitions_and_colour_gradients_are_parsed() {
+        let rule_file = "@RULE Compact\n@TABLE\nn_states:3\nneighborhood:vonNeumann\nsymmetries:permute\n012002\n@COLORS\n0 0 255 255 0 0\n";
+        let mut rule_table: RuleTable = rule_file.parse().unwrap();
+
+        assert_eq!(rule_table.get_next_state(&[0, 0, 0, 2, 1]), 2);
+        assert_eq!(rule_table.get_state_colour(1), Some((0, 0, 255)));
+        assert_eq!(rule_table.get_state_colour(2), Some((255, 0, 0)));
+    }
+
+    #[test]
+    fn invalid_rule_files_are_rejected() {
+        assert_eq!("@RULE Empty\n".parse::<RuleTable>(), Err(RuleTableParseError::MissingTable));
+        assert_eq!("@TABLE\nvar a={0}\n".parse::<RuleTable>(), Err(RuleTableParseError::MissingNumberOfStates));
+        assert_eq!("@TABLE\nn_states:2\nneighborhood:hexagonal\n".parse::<RuleTable>(), Err(RuleTableParseError::UnknownNeighbourhood(String::from("hexagonal"))));
+        assert_eq!("@TABLE\nn_states:2\nneighborhood:vonNeumann\nsymmetries:rotate8\n".parse::<RuleTable>(), Err(RuleTableParseError::UnknownSymmetries(String::from("rotate8"))));
+        assert_eq!("@TABLE\nn_states:2\nneighborhood:vonNeumann\n0,x,0,0,0,1\n".parse::<RuleTable>(), Err(RuleTableParseError::UnknownVariable(String::from("x"))));
+        assert_eq!("@TABLE\nn_states:2\nneighborhood:vonNeumann\n0,2,0,0,0,1\n".parse::<RuleTable>(), Err(RuleTableParseError::InvalidState(String::from("2"))));
+        assert_eq!("@TABLE\nn_states:2\n0,1,0\n".parse::<RuleTable>(), Err(RuleTableParseError::InvalidLine(3, String::from("0,1,0"))));
+        assert_eq!("@TREE\n".parse::<RuleTable>(), Err(RuleTableParseError::UnsupportedTree));
+        assert!(RuleTable::from_file("missing.rule").is_err());
+    }
+}
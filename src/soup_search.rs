@@ -0,0 +1,186 @@
+use rayon::prelude::*;
+use std::fmt::Write;
+use crate::census::*;
+use crate::rule::*;
+use crate::stochastic::*;
+use crate::topology::*;
+use crate::universe::*;
+
+pub const DEFAULT_SOUP_SIDE: usize = 16;
+pub const DEFAULT_SOUP_MAX_GENERATIONS: usize = 5000;
+pub const DEFAULT_NUMBER_OF_SOUPS: usize = 1000;
+
+// Soups are seeded one after another from the first seed, so any of them can be replayed
+// with `Universe::new_random_seeded` and the same settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoupSearchSettings {
+    pub rule: Rule,
+    pub topology: Topology,
+    pub soup_width: usize,
+    pub soup_height: usize,
+    pub initial_density: f64,
+    pub max_generations: usize,
+    pub first_seed: u64,
+    pub number_of_soups: usize,
+}
+
+impl Default for SoupSearchSettings {
+    fn default() -> Self {
+        Self {
+            rule: Rule::default(),
+            topology: Topology::default(),
+            soup_width: DEFAULT_SOUP_SIDE,
+            soup_height: DEFAULT_SOUP_SIDE,
+            initial_density: DEFAULT_INITIAL_DENSITY,
+            max_generations: DEFAULT_SOUP_MAX_GENERATIONS,
+            first_seed: 0,
+            number_of_soups: DEFAULT_NUMBER_OF_SOUPS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SoupResult {
+    pub seed: u64,
+    // Generation from which the soup repeats itself, none if it didn't settle within the generation cap
+    pub stabilisation_generation: Option<usize>,
+    pub census: Census,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnusualFind {
+    pub seed: u64,
+    pub object_code: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoupSearchReport {
+    pub settings: SoupSearchSettings,
+    pub census: Census,
+    pub unusual_finds: Vec<UnusualFind>,
+    pub unstabilised_seeds: Vec<u64>,
+}
+
+// Everything but still lifes and the common named objects is worth a closer look
+pub fn is_unusual_object(object_code: &str) -> bool {
+    get_object_name(object_code).is_none() && !is_still_life_code(object_code)
+}
+
+pub fn run_soup(settings: &SoupSearchSettings, seed: u64) -> SoupResult {
+    let mut universe = Universe::new_random_seeded(settings.soup_width, settings.soup_height, settings.topology, seed, settings.initial_density);
+    universe.set_rule(settings.rule.clone());
+
+    let stabilisation_generation = universe.find_cycle(settings.max_generations).map(|cycle| cycle.start_generation());
+    SoupResult { seed, stabilisation_generation, census: universe.take_census(DEFAULT_CENSUS_MAX_GENERATIONS) }
+}
+
+// Soups are stepped across all cores, their results are gathered in the order of their seeds
+pub fn run_soup_search(settings: &SoupSearchSettings) -> SoupSearchReport {
+    let seeds: Vec<u64> = (0..settings.number_of_soups as u64).map(|soup_index| settings.first_seed.wrapping_add(soup_index)).collect();
+    let soup_results: Vec<SoupResult> = seeds.par_iter().map(|seed| run_soup(settings, *seed)).collect();
+
+    let mut report = SoupSearchReport {
+        settings: settings.clone(),
+        census: Census::new(),
+        unusual_finds: Vec::new(),
+        unstabilised_seeds: Vec::new(),
+    };
+
+    for soup_result in soup_results {
+        report.census.merge(&soup_result.census);
+        report.unusual_finds.extend(soup_result.census
+            .iter()
+            .filter(|(object_code, _)| is_unusual_object(object_code))
+            .map(|(object_code, _)| UnusualFind { seed: soup_result.seed, object_code: String::from(object_code) }));
+
+        if soup_result.stabilisation_generation.is_none() {
+            report.unstabilised_seeds.push(soup_result.seed);
+        }
+    }
+
+    report
+}
+
+// Codes and rulestrings hold no characters which would need escaping besides quotes and backslashes
+fn to_json_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl SoupSearchReport {
+    pub fn number_of_stabilised_soups(&self) -> usize {
+        self.settings.number_of_soups - self.unstabilised_seeds.len()
+    }
+
+    pub fn to_json(&self) -> String {
+        let object_counts: Vec<String> = self.census.iter().map(|(object_code, count)| format!("{}: {}", to_json_string(object_code), count)).collect();
+        let unusual_finds: Vec<String> = self.unusual_finds
+            .iter()
+            .map(|unusual_find| format!("{{\"seed\": {}, \"object\": {}}}", unusual_find.seed, to_json_string(&unusual_find.object_code)))
+            .collect();
+        let unstabilised_seeds: Vec<String> = self.unstabilised_seeds.iter().map(|seed| seed.to_string()).collect();
+
+        let mut json = String::from("{\n");
+        writeln!(json, "  \"rule\": {},", to_json_string(&self.settings.rule.to_string())).unwrap();
+        writeln!(json, "  \"topology\": {},", to_json_string(&self.settings.topology.to_string())).unwrap();
+        writeln!(json, "  \"width\": {},", self.settings.soup_width).unwrap();
+        writeln!(json, "  \"height\": {},", self.settings.soup_height).unwrap();
+        writeln!(json, "  \"density\": {},", self.settings.initial_density).unwrap();
+        writeln!(json, "  \"max_generations\": {},", self.settings.max_generations).unwrap();
+        writeln!(json, "  \"first_seed\": {},", self.settings.first_seed).unwrap();
+        writeln!(json, "  \"number_of_soups\": {},", self.settings.number_of_soups).unwrap();
+        writeln!(json, "  \"number_of_stabilised_soups\": {},", self.number_of_stabilised_soups()).unwrap();
+        writeln!(json, "  \"objects\": {{{}}},", object_counts.join(", ")).unwrap();
+        writeln!(json, "  \"unusual_finds\": [{}],", unusual_finds.join(", ")).unwrap();
+        writeln!(json, "  \"unstabilised_seeds\": [{}]", unstabilised_seeds.join(", ")).unwrap();
+        json.push('}');
+        json
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn soups_are_reproduced_from_their_seeds() {
+        let settings = SoupSearchSettings { number_of_soups: 6, first_seed: 40, ..SoupSearchSettings::default() };
+        let report = run_soup_search(&settings);
+
+        let soup_results: Vec<SoupResult> = (40..46).map(|seed| run_soup(&settings, seed)).collect();
+        let mut census = Census::new();
+        for soup_result in soup_results.iter() {
+            census.merge(&soup_result.census);
+        }
+
+        assert_eq!(report.census, census);
+        assert_eq!(report.number_of_stabilised_soups(), soup_results.iter().filter(|soup_result| soup_result.stabilisation_generation.is_some()).count());
+        assert_eq!(run_soup(&settings, 42), soup_results[2]);
+    }
+
+    #[test]
+    fn report_is_written_as_json() {
+        let mut census = Census::new();
+        census.add("xs4_33");
+        census.add("xs4_33");
+        census.add("xp3_co9nas0san9oc");
+
+        let report = SoupSearchReport {
+            settings: SoupSearchSettings { number_of_soups: 2, ..SoupSearchSettings::default() },
+            census,
+            unusual_finds: vec![UnusualFind { seed: 1, object_code: String::from("xp3_co9nas0san9oc") }],
+            unstabilised_seeds: vec![0],
+        };
+
+        let json = report.to_json();
+        assert!(json.contains("\"rule\": \"B3/S23\","));
+        assert!(json.contains("\"number_of_stabilised_soups\": 1,"));
+        assert!(json.contains("\"objects\": {\"xs4_33\": 2, \"xp3_co9nas0san9oc\": 1},"));
+        assert!(json.contains("\"unusual_finds\": [{\"seed\": 1, \"object\": \"xp3_co9nas0san9oc\"}],"));
+        assert!(json.ends_with("\"unstabilised_seeds\": [0]\n}"));
+
+        assert!(is_unusual_object("xp3_co9nas0san9oc"));
+        assert!(is_unusual_object(UNCLASSIFIED_OBJECT_CODE));
+        assert!(!is_unusual_object("xq4_153"));
+        assert!(!is_unusual_object("xs12_g8o653z11"));
+    }
+}
@@ -0,0 +1,72 @@
+use rand::Rng;
+
+pub const DEFAULT_INITIAL_DENSITY: f64 = 0.5;
+
+pub fn check_probability(probability_name: &str, probability: f64) {
+    if !(0.0..=1.0).contains(&probability) {
+        panic!("'{}' expects a probability from 0 to 1, received {}.", probability_name, probability);
+    }
+}
+
+// Draws from the generator only for uncertain events, so deterministic runs leave it untouched
+pub fn is_happening<R: Rng>(random_generator: &mut R, probability: f64) -> bool {
+    probability >= 1.0 || (probability > 0.0 && random_generator.gen_bool(probability))
+}
+
+// Chances that births and survivals allowed by the rule actually happen,
+// a cell which fails to survive dies as if the rule killed it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransitionProbabilities {
+    birth_probability: f64,
+    survival_probability: f64,
+}
+
+impl TransitionProbabilities {
+    pub fn new(birth_probability: f64, survival_probability: f64) -> Self {
+        check_probability("birth_probability", birth_probability);
+        check_probability("survival_probability", survival_probability);
+
+        TransitionProbabilities { birth_probability, survival_probability }
+    }
+
+    pub fn birth_probability(&self) -> f64 {
+        self.birth_probability
+    }
+
+    pub fn survival_probability(&self) -> f64 {
+        self.survival_probability
+    }
+
+    pub fn is_deterministic(&self) -> bool {
+        self.birth_probability == 1.0 && self.survival_probability == 1.0
+    }
+}
+
+impl Default for TransitionProbabilities {
+    fn default() -> Self {
+        TransitionProbabilities::new(1.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn certain_events_leave_generator_untouched() {
+        let mut random_generator = StdRng::seed_from_u64(5);
+        assert!(is_happening(&mut random_generator, 1.0));
+        assert!(!is_happening(&mut random_generator, 0.0));
+
+        let mut fresh_random_generator = StdRng::seed_from_u64(5);
+        assert_eq!(random_generator.gen::<u64>(), fresh_random_generator.gen::<u64>());
+    }
+
+    #[test]
+    #[should_panic]
+    fn probability_above_one_is_rejected() {
+        TransitionProbabilities::new(1.5, 1.0);
+    }
+}
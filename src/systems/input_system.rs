@@ -1,5 +1,5 @@
 use ggez::event::MouseButton;
-use specs::{System, Write};
+use specs::{Read, System, Write};
 use num::clamp;
 use crate::resources::*;
 
@@ -9,40 +9,60 @@ impl<'a> System<'a> for InputSystem {
     type SystemData = (
         Write<'a, InputQueue>,
         Write<'a, UniverseField>,
+        Read<'a, Viewport>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut input_queue, mut universe_field) = data;
+        let (mut input_queue, mut universe_field, viewport) = data;
 
-        while 
-            input_queue.mouse_button_events.len() > 0 && 
-            input_queue.pressed_cell_positions.len() > 0 {
+        // Events are handled in the order they came, so that a click comes before the drag following it
+        let mouse_button_events: Vec<MouseButton> = input_queue.mouse_button_events.drain(..).collect();
+        let pressed_cell_positions: Vec<_> = input_queue.pressed_cell_positions.drain(..).collect();
+        let is_drag_events: Vec<bool> = input_queue.is_drag_events.drain(..).collect();
 
-            let button_optional = input_queue.mouse_button_events.pop();
-            if !button_optional.is_some()
-            {
+        for ((button, pressed_position), is_drag_event) in mouse_button_events.into_iter().zip(pressed_cell_positions).zip(is_drag_events) {
+            let (universe_height, universe_width) = universe_field.field.shape();
+
+            let pressed_cell_x = clamp(pressed_position.x, 0, universe_width - 1);
+            let pressed_cell_y = clamp(pressed_position.y, 0, universe_height - 1);
+
+            // Cells of the volume field are edited in the visible layer, the isometric projection can't be edited
+            if let Some(volume_field) = universe_field.volume_field.as_mut() {
+                match button {
+                    MouseButton::Left if !viewport.is_isometric => volume_field.populate_cell(viewport.visible_layer, pressed_cell_y, pressed_cell_x),
+                    MouseButton::Right if !viewport.is_isometric => volume_field.free_cell(viewport.visible_layer, pressed_cell_y, pressed_cell_x),
+                    _ => ()
+                }
                 continue;
             }
 
-            let pressed_position_optional = input_queue.pressed_cell_positions.pop();
-            if !pressed_position_optional.is_some()
-            {
+            if let Some(continuous_field) = universe_field.continuous_field.as_mut() {
+                match button {
+                    MouseButton::Left => continuous_field.populate_cell(pressed_cell_y, pressed_cell_x),
+                    MouseButton::Right => continuous_field.free_cell(pressed_cell_y, pressed_cell_x),
+                    _ => ()
+                }
                 continue;
             }
 
-            let button = button_optional.unwrap();
-            let pressed_position = pressed_position_optional.unwrap();
-
-            let (universe_height, universe_width) = universe_field.field.shape();
-
-            let pressed_cell_x = clamp(pressed_position.x, 0, (universe_height - 1) as u8);
-            let pressed_cell_y = clamp(pressed_position.y, 0, (universe_width - 1) as u8);
-
+            // Cells of multi-state rules cycle through the states on click instead of only getting populated
+            let is_multi_state = universe_field.field.number_of_states() > 2;
             match button {
+                MouseButton::Left if is_multi_state && !is_drag_event => {
+                    input_queue.painted_cell_state = universe_field.field.cycle_cell_state(pressed_cell_y, pressed_cell_x);
+                }
+                MouseButton::Left if is_multi_state => {
+                    universe_field.field.set_cell_state(pressed_cell_y, pressed_cell_x, input_queue.painted_cell_state);
+                }
                 MouseButton::Left => universe_field.field.populate_cell(pressed_cell_y, pressed_cell_x),
                 MouseButton::Right => universe_field.field.free_cell(pressed_cell_y, pressed_cell_x),
                 _ => ()
             }
         }
+
+        if input_queue.is_edit_finished {
+            universe_field.field.commit_edits();
+            input_queue.is_edit_finished = false;
+        }
     }
 }
@@ -1,27 +1,168 @@
 use glam::Vec2;
 use ggez::Context;
-use ggez::graphics::{self, DrawParam, Image, spritebatch::SpriteBatch};
+use ggez::graphics::{self, Color, DrawMode, DrawParam, Image, MeshBuilder, Rect, spritebatch::SpriteBatch};
 use specs::{join::Join, ReadStorage, Read, System};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use crate::constants::*;
+use crate::grid::*;
+use crate::history::*;
 use crate::components::*;
 use crate::resources::*;
 use crate::universe::*;
+use crate::volume_universe::*;
 
 pub struct RenderingSystem<'a> {
     pub context: &'a mut Context,
 }
 
+// Populated cells keep the tile colour while decaying cells of Generations rules
+// fade from orange to dark red as they get closer to the free state
+fn get_cell_state_tint(cell_state: u8, number_of_states: u8) -> Color {
+    if cell_state == CELL_IS_POPULATED {
+        return Color::WHITE;
+    }
+
+    let decay_progress = ((cell_state - CELL_IS_POPULATED) as f32) / ((number_of_states - CELL_IS_POPULATED) as f32);
+    Color::new(1.0 - 0.6 * decay_progress, 0.6 * (1.0 - decay_progress), 0.0, 1.0)
+}
+
+// Ghosts of dead cells start dim and fade out completely at the end of the trail
+fn get_ghost_tint(time_since_death: u32, ghost_trail_length: usize) -> Color {
+    let fade_progress = time_since_death as f32 / ghost_trail_length as f32;
+    Color::new(0.4, 0.4, 0.6, 0.5 * (1.0 - fade_progress))
+}
+
+// Cell values of continuous rules run from dark blue through cyan and yellow to white
+const CELL_VALUE_GRADIENT: [(f32, f32, f32); 4] = [(0.1, 0.0, 0.4), (0.0, 0.6, 0.9), (1.0, 0.9, 0.1), (1.0, 1.0, 1.0)];
+const MIN_VISIBLE_CELL_VALUE: f64 = 0.01;
+
+fn get_cell_value_tint(cell_value: f64) -> Color {
+    let gradient_position = (cell_value as f32).clamp(0.0, 1.0) * ((CELL_VALUE_GRADIENT.len() - 1) as f32);
+    let lower_stop_index = (gradient_position as usize).min(CELL_VALUE_GRADIENT.len() - 2);
+    let stop_progress = gradient_position - lower_stop_index as f32;
+
+    let (lower_red, lower_green, lower_blue) = CELL_VALUE_GRADIENT[lower_stop_index];
+    let (upper_red, upper_green, upper_blue) = CELL_VALUE_GRADIENT[lower_stop_index + 1];
+    Color::new(
+        lower_red + (upper_red - lower_red) * stop_progress,
+        lower_green + (upper_green - lower_green) * stop_progress,
+        lower_blue + (upper_blue - lower_blue) * stop_progress,
+        1.0,
+    )
+}
+
+// Faces of isometric cubes are shaded as if the light came from above and from the left
+const CUBE_TOP_FACE_COLOUR: (f32, f32, f32) = (0.95, 0.95, 1.0);
+const CUBE_LEFT_FACE_COLOUR: (f32, f32, f32) = (0.55, 0.6, 0.8);
+const CUBE_RIGHT_FACE_COLOUR: (f32, f32, f32) = (0.35, 0.4, 0.6);
+const ISOMETRIC_HALF_WIDTH: f32 = 0.866;
+const ISOMETRIC_HALF_HEIGHT: f32 = 0.5;
+
+// Project the volume with columns going right and down, rows going left and down and layers going up,
+// cubes are added from the farthest to the nearest so that the nearer ones cover them
+fn add_isometric_cubes(mesh_builder: &mut MeshBuilder, volume_field: &VolumeUniverse, window_size: (f32, f32)) -> bool {
+    let (universe_depth, universe_height, universe_width) = volume_field.shape();
+    let (window_width, window_height) = window_size;
+    let diagonal_length = (universe_width + universe_height) as f32;
+    let cube_side = (window_width / (diagonal_length * ISOMETRIC_HALF_WIDTH))
+        .min(window_height / (diagonal_length * ISOMETRIC_HALF_HEIGHT + universe_depth as f32));
+
+    let project = |column: f32, row: f32, layer: f32| [
+        ((column - row) * ISOMETRIC_HALF_WIDTH + universe_height as f32 * ISOMETRIC_HALF_WIDTH) * cube_side,
+        ((column + row) * ISOMETRIC_HALF_HEIGHT - layer + universe_depth as f32) * cube_side,
+    ];
+
+    let mut populated_cells: Vec<(usize, usize, usize)> = Vec::new();
+    for layer in 0..universe_depth {
+        for row in 0..universe_height {
+            for column in 0..universe_width {
+                if volume_field.get_cell_state(layer, row, column) == CELL_IS_POPULATED {
+                    populated_cells.push((layer, row, column));
+                }
+            }
+        }
+    }
+    populated_cells.sort_by_key(|(layer, row, column)| layer + row + column);
+
+    for (layer, row, column) in populated_cells.iter() {
+        let (layer, row, column) = (*layer as f32, *row as f32, *column as f32);
+        let faces = [
+            ([project(column, row, layer + 1.0), project(column + 1.0, row, layer + 1.0), project(column + 1.0, row + 1.0, layer + 1.0), project(column, row + 1.0, layer + 1.0)], CUBE_TOP_FACE_COLOUR),
+            ([project(column, row + 1.0, layer + 1.0), project(column + 1.0, row + 1.0, layer + 1.0), project(column + 1.0, row + 1.0, layer), project(column, row + 1.0, layer)], CUBE_LEFT_FACE_COLOUR),
+            ([project(column + 1.0, row, layer + 1.0), project(column + 1.0, row + 1.0, layer + 1.0), project(column + 1.0, row + 1.0, layer), project(column + 1.0, row, layer)], CUBE_RIGHT_FACE_COLOUR),
+        ];
+
+        for (face, (red, green, blue)) in faces.iter() {
+            mesh_builder.polygon(DrawMode::fill(), face, Color::new(*red, *green, *blue, 1.0)).expect("expected polygon");
+        }
+    }
+
+    !populated_cells.is_empty()
+}
+
+// Timeline is dark where the history reaches, lighter up to the current generation, and edits are ticked
+const TIMELINE_COLOUR: Color = Color::new(0.15, 0.15, 0.2, 1.0);
+const TIMELINE_PLAYED_COLOUR: Color = Color::new(0.3, 0.45, 0.7, 1.0);
+const TIMELINE_EDIT_COLOUR: Color = Color::new(1.0, 0.6, 0.2, 1.0);
+const TIMELINE_KNOB_WIDTH: f32 = 3.0;
+
+fn build_timeline_mesh(mesh_builder: &mut MeshBuilder, history: &History, timeline_area: (f32, f32)) {
+    let (timeline_width, timeline_top) = timeline_area;
+    let entry_width = timeline_width / (history.len().max(1) as f32);
+    let played_width = entry_width * history.position() as f32;
+
+    mesh_builder.rectangle(DrawMode::fill(), Rect::new(0.0, timeline_top, timeline_width, TIMELINE_HEIGHT), TIMELINE_COLOUR).expect("expected rectangle");
+    mesh_builder.rectangle(DrawMode::fill(), Rect::new(0.0, timeline_top, played_width, TIMELINE_HEIGHT), TIMELINE_PLAYED_COLOUR).expect("expected rectangle");
+
+    for (entry_index, entry) in history.entries().enumerate() {
+        if entry.kind() == HistoryEntryKind::Edit {
+            let edit_x = entry_width * (entry_index as f32 + 0.5);
+            mesh_builder.rectangle(DrawMode::fill(), Rect::new(edit_x, timeline_top, 1.0, TIMELINE_HEIGHT / 2.0), TIMELINE_EDIT_COLOUR).expect("expected rectangle");
+        }
+    }
+
+    let knob_x = (played_width - TIMELINE_KNOB_WIDTH / 2.0).clamp(0.0, timeline_width - TIMELINE_KNOB_WIDTH);
+    mesh_builder.rectangle(DrawMode::fill(), Rect::new(knob_x, timeline_top, TIMELINE_KNOB_WIDTH, TIMELINE_HEIGHT), Color::WHITE).expect("expected rectangle");
+}
+
+const SPARKLINE_BACKGROUND_COLOUR: Color = Color::new(0.0, 0.0, 0.0, 0.6);
+const SPARKLINE_COLOUR: Color = Color::new(0.3, 0.9, 0.4, 1.0);
+const SPARKLINE_MARGIN: f32 = 4.0;
+
+// Population of the last generations drawn in the upper right corner, scaled to the largest one shown
+fn build_sparkline_mesh(mesh_builder: &mut MeshBuilder, populations: &VecDeque<usize>, window_width: f32) {
+    let sparkline_left = window_width - SPARKLINE_WIDTH - SPARKLINE_MARGIN;
+    let max_population = populations.iter().max().copied().unwrap_or(0).max(1) as f32;
+    let point_spacing = SPARKLINE_WIDTH / (SPARKLINE_LENGTH - 1) as f32;
+
+    let points: Vec<Vec2> = populations
+        .iter()
+        .enumerate()
+        .map(|(point_index, population)| Vec2::new(
+            sparkline_left + point_spacing * point_index as f32,
+            SPARKLINE_MARGIN + SPARKLINE_HEIGHT * (1.0 - *population as f32 / max_population),
+        ))
+        .collect();
+
+    mesh_builder.rectangle(DrawMode::fill(), Rect::new(sparkline_left, SPARKLINE_MARGIN, SPARKLINE_WIDTH, SPARKLINE_HEIGHT), SPARKLINE_BACKGROUND_COLOUR).expect("expected rectangle");
+    mesh_builder.line(&points, 1.0, SPARKLINE_COLOUR).expect("expected line");
+}
+
 // System implementation
 impl<'a> System<'a> for RenderingSystem<'a> {
     type SystemData = (
         ReadStorage<'a, Position>, 
         ReadStorage<'a, Renderable>,
         Read<'a, UniverseField>,
+        Read<'a, Viewport>,
+        Read<'a, StatsStream>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (positions, renderables, universe_field) = data;
+        let (positions, renderables, universe_field, viewport, stats_stream) = data;
+        let tile_scale = viewport.tile_width / TILE_WIDTH;
+        let number_of_states = universe_field.field.number_of_states();
+        let grid = universe_field.field.rule().grid();
 
         // Clearing the screen (this gives us the background colour)
         graphics::clear(self.context, graphics::Color::new(0.0, 0.0, 0.0, 1.0));
@@ -30,18 +171,86 @@ impl<'a> System<'a> for RenderingSystem<'a> {
         let rendering_data = (&positions, &renderables).join().collect::<Vec<_>>();
         let mut rendering_batches: HashMap<String, Vec<DrawParam>> = HashMap::new();
 
+        // Hexagons and triangles are drawn as polygons of a single mesh instead of square tiles
+        let mut cell_mesh_builder = MeshBuilder::new();
+        let mut is_cell_mesh_empty = true;
+
+        let isometric_volume_field = universe_field.volume_field.as_ref().filter(|_| viewport.is_isometric);
+        if let Some(volume_field) = isometric_volume_field {
+            is_cell_mesh_empty = !add_isometric_cubes(&mut cell_mesh_builder, volume_field, graphics::drawable_size(self.context));
+        }
+
         // Iterate each of the renderables, determine which image path should be rendered
         // at which drawparams, and then add that to the rendering_batches.
-        for (position, renderable) in rendering_data.iter() {
-            if universe_field.field.get_cell_state(position.y, position.x) == CELL_IS_FREE {
+        for (position, renderable) in rendering_data.iter().filter(|_| isometric_volume_field.is_none()) {
+            if let Some(volume_field) = &universe_field.volume_field {
+                if volume_field.get_cell_state(viewport.visible_layer, position.y, position.x) == CELL_IS_FREE {
+                    continue;
+                }
+
+                let x = (position.x as f32) * viewport.tile_width;
+                let y = (position.y as f32) * viewport.tile_width;
+                rendering_batches
+                    .entry(renderable.path.clone())
+                    .or_default()
+                    .push(DrawParam::new().dest(Vec2::new(x, y)).scale(Vec2::new(tile_scale, tile_scale)));
                 continue;
             }
 
-            let x = (position.x as f32) * TILE_WIDTH;
-            let y = (position.y as f32) * TILE_WIDTH;
+            let cell_tint = match &universe_field.continuous_field {
+                Some(continuous_field) => {
+                    let cell_value = continuous_field.get_cell_value(position.y, position.x);
+                    if cell_value < MIN_VISIBLE_CELL_VALUE {
+                        continue;
+                    }
+
+                    get_cell_value_tint(cell_value)
+                }
+                None => {
+                    let cell_state = universe_field.field.get_cell_state(position.y, position.x);
+                    if cell_state == CELL_IS_FREE {
+                        let time_since_death = universe_field.field
+                            .get_time_since_death(position.y, position.x)
+                            .filter(|time_since_death| (*time_since_death as usize) < viewport.ghost_trail_length);
+                        match time_since_death {
+                            Some(time_since_death) => get_ghost_tint(time_since_death, viewport.ghost_trail_length),
+                            None => continue,
+                        }
+                    } else {
+                        // Rule tables may bring their own colours of the states, otherwise populated cells may be tinted by their age
+                        let state_colour = universe_field.field.rule_table().and_then(|rule_table| rule_table.get_state_colour(cell_state));
+                        let age_colour = viewport.age_colour_ramp
+                            .as_ref()
+                            .filter(|_| cell_state == CELL_IS_POPULATED)
+                            .zip(universe_field.field.get_cell_age(position.y, position.x))
+                            .map(|(age_colour_ramp, cell_age)| age_colour_ramp.get_colour(cell_age));
+                        match state_colour.or(age_colour) {
+                            Some((red, green, blue)) => Color::from_rgb(red, green, blue),
+                            None => get_cell_state_tint(cell_state, number_of_states),
+                        }
+                    }
+                }
+            };
+
+            if grid != Grid::Square {
+                let cell_polygon: Vec<[f32; 2]> = grid
+                    .get_cell_polygon(position.y, position.x)
+                    .iter()
+                    .map(|(x, y)| [x * viewport.tile_width, y * viewport.tile_width])
+                    .collect();
+                cell_mesh_builder.polygon(DrawMode::fill(), &cell_polygon, cell_tint).expect("expected polygon");
+                is_cell_mesh_empty = false;
+                continue;
+            }
+
+            let x = (position.x as f32) * viewport.tile_width;
+            let y = (position.y as f32) * viewport.tile_width;
 
             // Add to rendering batches
-            let draw_param = DrawParam::new().dest(Vec2::new(x, y));
+            let draw_param = DrawParam::new()
+                .dest(Vec2::new(x, y))
+                .scale(Vec2::new(tile_scale, tile_scale))
+                .color(cell_tint);
             rendering_batches
                 .entry(renderable.path.clone())
                 .or_default()
@@ -62,6 +271,30 @@ impl<'a> System<'a> for RenderingSystem<'a> {
                 .expect("expected render");
         }
 
+        if !is_cell_mesh_empty {
+            let cell_mesh = cell_mesh_builder.build(self.context).expect("expected mesh");
+            graphics::draw(self.context, &cell_mesh, graphics::DrawParam::new())
+                .expect("expected render");
+        }
+
+        let is_timeline_shown = universe_field.continuous_field.is_none() && universe_field.volume_field.is_none();
+        if let Some(history) = universe_field.field.history().filter(|_| is_timeline_shown) {
+            let mut timeline_mesh_builder = MeshBuilder::new();
+            build_timeline_mesh(&mut timeline_mesh_builder, history, viewport.window_dimensions(grid, universe_field.field.shape()));
+            let timeline_mesh = timeline_mesh_builder.build(self.context).expect("expected mesh");
+            graphics::draw(self.context, &timeline_mesh, graphics::DrawParam::new())
+                .expect("expected render");
+        }
+
+        if stats_stream.is_sparkline_shown && stats_stream.populations.len() > 1 {
+            let mut sparkline_mesh_builder = MeshBuilder::new();
+            let (window_width, _) = viewport.window_dimensions(grid, universe_field.field.shape());
+            build_sparkline_mesh(&mut sparkline_mesh_builder, &stats_stream.populations, window_width);
+            let sparkline_mesh = sparkline_mesh_builder.build(self.context).expect("expected mesh");
+            graphics::draw(self.context, &sparkline_mesh, graphics::DrawParam::new())
+                .expect("expected render");
+        }
+
         // Finally, present the context, this will actually display everything
         // on the screen.
         graphics::present(self.context).expect("expected to present");
@@ -0,0 +1,173 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use crate::convolution::*;
+
+const PLANE_NAME: &str = "plane";
+const HORIZONTAL_CYLINDER_NAME: &str = "horizontal-cylinder";
+const VERTICAL_CYLINDER_NAME: &str = "vertical-cylinder";
+const TORUS_NAME: &str = "torus";
+const KLEIN_BOTTLE_NAME: &str = "klein-bottle";
+const CROSS_SURFACE_NAME: &str = "cross-surface";
+
+// Surface made of the universe by joining (or not joining) its opposite edges:
+// cylinders join either left and right edges (horizontal) or upper and bottom edges (vertical),
+// Klein bottle twists upper and bottom edges before joining them, so a cell leaving
+// through the bottom edge comes back through the upper one mirrored left to right,
+// cross-surface (projective plane) twists both pairs of edges
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Topology {
+    Plane,
+    HorizontalCylinder,
+    VerticalCylinder,
+    #[default]
+    Torus,
+    KleinBottle,
+    CrossSurface,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopologyParseError(String);
+
+impl fmt::Display for TopologyParseError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "'{}' is not one of {}, {}, {}, {}, {}, {}", self.0,
+            PLANE_NAME, HORIZONTAL_CYLINDER_NAME, VERTICAL_CYLINDER_NAME, TORUS_NAME, KLEIN_BOTTLE_NAME, CROSS_SURFACE_NAME)
+    }
+}
+
+impl Error for TopologyParseError {}
+
+// Wrap index which may lay outside of the edges, also tell how many times the edges were crossed
+fn wrap_index(index: isize, size: usize) -> (usize, isize) {
+    let size = size as isize;
+    (index.rem_euclid(size) as usize, index.div_euclid(size))
+}
+
+fn reflect_index(index: usize, size: usize) -> usize {
+    size - 1 - index
+}
+
+impl Topology {
+    pub fn is_horizontally_joined(&self) -> bool {
+        !matches!(self, Topology::Plane | Topology::VerticalCylinder)
+    }
+
+    pub fn is_vertically_joined(&self) -> bool {
+        !matches!(self, Topology::Plane | Topology::HorizontalCylinder)
+    }
+
+    fn is_horizontally_twisted(&self) -> bool {
+        *self == Topology::CrossSurface
+    }
+
+    fn is_vertically_twisted(&self) -> bool {
+        matches!(self, Topology::KleinBottle | Topology::CrossSurface)
+    }
+
+    // Find the cell of the plane of the given shape which is seen at position (row, column),
+    // positions beyond the edges which are not joined have no cell at all
+    pub fn get_plane_position(&self, row: isize, column: isize, plane_shape: (usize, usize)) -> Option<(usize, usize)> {
+        let (plane_height, plane_width) = plane_shape;
+        let (mut plane_row, row_crossings) = wrap_index(row, plane_height);
+        let (mut plane_column, column_crossings) = wrap_index(column, plane_width);
+
+        if (row_crossings != 0 && !self.is_vertically_joined()) || (column_crossings != 0 && !self.is_horizontally_joined()) {
+            return None;
+        }
+
+        // Crossing twisted edges twice brings back the original orientation
+        if row_crossings % 2 != 0 && self.is_vertically_twisted() {
+            plane_column = reflect_index(plane_column, plane_width);
+        }
+
+        if column_crossings % 2 != 0 && self.is_horizontally_twisted() {
+            plane_row = reflect_index(plane_row, plane_height);
+        }
+
+        Some((plane_row, plane_column))
+    }
+}
+
+impl BoundaryCondition for Topology {
+    fn get_matrix_position(&self, row: isize, column: isize, matrix_shape: (usize, usize)) -> Option<(usize, usize)> {
+        self.get_plane_position(row, column, matrix_shape)
+    }
+}
+
+impl FromStr for Topology {
+    type Err = TopologyParseError;
+
+    fn from_str(topology_name: &str) -> Result<Self, Self::Err> {
+        match topology_name.trim().to_lowercase().as_str() {
+            PLANE_NAME => Ok(Topology::Plane),
+            HORIZONTAL_CYLINDER_NAME => Ok(Topology::HorizontalCylinder),
+            VERTICAL_CYLINDER_NAME => Ok(Topology::VerticalCylinder),
+            TORUS_NAME => Ok(Topology::Torus),
+            KLEIN_BOTTLE_NAME => Ok(Topology::KleinBottle),
+            CROSS_SURFACE_NAME => Ok(Topology::CrossSurface),
+            _ => Err(TopologyParseError(String::from(topology_name))),
+        }
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let topology_name = match self {
+            Topology::Plane => PLANE_NAME,
+            Topology::HorizontalCylinder => HORIZONTAL_CYLINDER_NAME,
+            Topology::VerticalCylinder => VERTICAL_CYLINDER_NAME,
+            Topology::Torus => TORUS_NAME,
+            Topology::KleinBottle => KLEIN_BOTTLE_NAME,
+            Topology::CrossSurface => CROSS_SURFACE_NAME,
+        };
+
+        write!(formatter, "{}", topology_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLANE_SHAPE: (usize, usize) = (4, 6);
+
+    #[test]
+    fn position_inside_of_plane_is_kept() {
+        for topology in [Topology::Plane, Topology::HorizontalCylinder, Topology::VerticalCylinder, Topology::Torus, Topology::KleinBottle, Topology::CrossSurface] {
+            assert_eq!(topology.get_plane_position(2, 3, PLANE_SHAPE), Some((2, 3)));
+        }
+    }
+
+    #[test]
+    fn position_beyond_edges_is_wrapped_by_topology() {
+        assert_eq!(Topology::Plane.get_plane_position(-1, 3, PLANE_SHAPE), None);
+        assert_eq!(Topology::Plane.get_plane_position(2, 6, PLANE_SHAPE), None);
+
+        assert_eq!(Topology::HorizontalCylinder.get_plane_position(2, 6, PLANE_SHAPE), Some((2, 0)));
+        assert_eq!(Topology::HorizontalCylinder.get_plane_position(4, 3, PLANE_SHAPE), None);
+
+        assert_eq!(Topology::VerticalCylinder.get_plane_position(4, 3, PLANE_SHAPE), Some((0, 3)));
+        assert_eq!(Topology::VerticalCylinder.get_plane_position(2, -1, PLANE_SHAPE), None);
+
+        assert_eq!(Topology::Torus.get_plane_position(-1, -1, PLANE_SHAPE), Some((3, 5)));
+        assert_eq!(Topology::Torus.get_plane_position(9, 13, PLANE_SHAPE), Some((1, 1)));
+
+        assert_eq!(Topology::KleinBottle.get_plane_position(4, 1, PLANE_SHAPE), Some((0, 4)));
+        assert_eq!(Topology::KleinBottle.get_plane_position(8, 1, PLANE_SHAPE), Some((0, 1)));
+        assert_eq!(Topology::KleinBottle.get_plane_position(1, 6, PLANE_SHAPE), Some((1, 0)));
+
+        assert_eq!(Topology::CrossSurface.get_plane_position(-1, 1, PLANE_SHAPE), Some((3, 4)));
+        assert_eq!(Topology::CrossSurface.get_plane_position(1, -1, PLANE_SHAPE), Some((2, 5)));
+    }
+
+    #[test]
+    fn topology_is_parsed_from_its_name() {
+        for topology in [Topology::Plane, Topology::HorizontalCylinder, Topology::VerticalCylinder, Topology::Torus, Topology::KleinBottle, Topology::CrossSurface] {
+            assert_eq!(topology.to_string().parse::<Topology>(), Ok(topology));
+        }
+
+        assert_eq!("Klein-Bottle".parse::<Topology>(), Ok(Topology::KleinBottle));
+        assert!("sphere".parse::<Topology>().is_err());
+    }
+}
@@ -1,237 +1,916 @@
-use nalgebra::{matrix, SMatrix};
-use rand::distributions::{Uniform};
-use crate::constants::*;
-
-pub type RuleKernel = SMatrix<u8, RULE_KERNEL_WIDTH, RULE_KERNEL_HEIGHT>;
-pub type UniverseWrapped = SMatrix<u8, UNIVERSE_WRAPPED_WIDTH, UNIVERSE_WRAPPED_HEIGHT>;
-
-const RULE_KERNEL_WIDTH_HALF: usize = RULE_KERNEL_WIDTH / 2;
-const RULE_KERNEL_HEIGHT_HALF: usize = RULE_KERNEL_HEIGHT / 2;
-const UNIVERSE_WRAPPED_WIDTH: usize = UNIVERSE_WIDTH + (2 * RULE_KERNEL_WIDTH_HALF);
-const UNIVERSE_WRAPPED_HEIGHT: usize = UNIVERSE_HEIGHT + (2 * RULE_KERNEL_HEIGHT_HALF);
+use nalgebra::{ClosedAdd, ClosedMul, DMatrix, Scalar};
+use num::Zero;
+use rand::distributions::Bernoulli;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rayon::prelude::*;
+use std::ops::Range;
+use crate::cell_age::*;
+use crate::census::*;
+use crate::convolution::*;
+use crate::cycle_detector::*;
+use crate::generation_stats::*;
+use crate::history::*;
+use crate::predecessor::*;
+use crate::rule::*;
+use crate::rule_table::*;
+use crate::stochastic::*;
+use crate::topology::*;
+use crate::update_mode::*;
+
+pub type UniversePlane = DMatrix<u8>;
 
 pub const CELL_IS_POPULATED: u8 = 1;
 pub const CELL_IS_FREE: u8 = 0;
 
-pub fn convolve_torus<const R1: usize, const C1: usize, const R2: usize, const C2: usize>(torus_wrapped_plane: & mut SMatrix<u8, R1, C1>, kernel: &SMatrix<u8, R2, C2>) -> SMatrix<u8, R1, C1>
+// Kernel is centred on the cell, so it needs an odd number of rows and columns
+fn check_kernel_shape<T: Scalar>(kernel: &DMatrix<T>) {
+    let (kernel_height, kernel_width) = kernel.shape();
+    if kernel_height % 2 == 0 || kernel_width % 2 == 0 {
+        panic!("Convolution expects a kernel with an odd number of rows and columns, received {:?}.", kernel.shape());
+    }
+}
+
+fn get_kernel_halves<T: Scalar>(kernel: &DMatrix<T>) -> (usize, usize) {
+    check_kernel_shape(kernel);
+
+    let (kernel_height, kernel_width) = kernel.shape();
+    (kernel_height / 2, kernel_width / 2)
+}
+
+// Rule kernels weight the neighbours at their own positions, so the plane is correlated with them
+// rather than convolved with the flipped ones
+pub fn convolve_on_topology<T>(plane: &DMatrix<T>, kernel: &DMatrix<T>, topology: Topology) -> DMatrix<T>
+    where T: Scalar + Zero + ClosedAdd + ClosedMul + Copy
 {
-    let kernel_height = kernel.shape().0;
-    let kernel_width = kernel.shape().1;
+    check_kernel_shape(kernel);
+    plane.correlate(kernel, ConvolutionMode::Same, &topology)
+}
+
+// Split the plane into bands of rows convolved across threads, every band reads its halo rows
+// from the plane padded once beforehand, so the result is the same as the sequential one
+pub fn convolve_on_topology_parallel<T>(plane: &DMatrix<T>, kernel: &DMatrix<T>, topology: Topology, number_of_bands: usize) -> DMatrix<T>
+    where T: Scalar + Zero + ClosedAdd + ClosedMul + Copy + Send + Sync
+{
+    let (kernel_height_half, kernel_width_half) = get_kernel_halves(kernel);
+    let (plane_height, plane_width) = plane.shape();
+    let padded_shape = (plane_height + (2 * kernel_height_half), plane_width + (2 * kernel_width_half));
+    let padded_plane = pad_matrix(plane, &topology, padded_shape, (kernel_height_half, kernel_width_half));
+
+    let band_height = plane_height.div_ceil(number_of_bands.max(1));
+
+    let band_starts: Vec<usize> = (0..plane_height).step_by(band_height).collect();
+    let convolved_bands: Vec<DMatrix<T>> = band_starts
+        .par_iter()
+        .map(|band_start| {
+            let band_end = (band_start + band_height).min(plane_height);
+            correlate_padded_rows(&padded_plane, kernel, *band_start..band_end, plane_width)
+        })
+        .collect();
+
+    let mut convolve_result = DMatrix::<T>::zeros(plane_height, plane_width);
+    for (band_start, convolved_band) in band_starts.iter().zip(convolved_bands) {
+        convolve_result.rows_mut(*band_start, convolved_band.nrows()).copy_from(&convolved_band);
+    }
 
-    let kernel_height_half = kernel_height / 2;
-    let kernel_width_half = kernel_width / 2;
+    convolve_result
+}
+
+// Spectrum of the kernel planned for planes of the given shape, see `convolve_on_topology_with_spectrum`
+pub fn get_kernel_spectrum(kernel: &DMatrix<u32>, plane_shape: (usize, usize)) -> KernelSpectrum {
+    check_kernel_shape(kernel);
+    KernelSpectrum::new(&kernel.map(|value| value as f64), plane_shape, ConvolutionMode::Same)
+}
 
-    let torus_wrapped_plane_shape = torus_wrapped_plane.shape();
+// Sums of kernel weights are integers far below 2^52, so rounding the FFT result recovers them exactly
+pub fn convolve_on_topology_with_spectrum(plane: &DMatrix<u32>, kernel_spectrum: &KernelSpectrum, topology: Topology) -> DMatrix<u32> {
+    let plane = plane.map(|value| value as f64);
+    kernel_spectrum.correlate(&plane, &topology).map(|sum| sum.round() as u32)
+}
 
-    let torus_plane_height = torus_wrapped_plane_shape.0 - (2 * kernel_height_half);
-    let torus_plane_width = torus_wrapped_plane_shape.1 - (2 * kernel_width_half);
+pub fn convolve_on_topology_fft(plane: &DMatrix<u32>, kernel: &DMatrix<u32>, topology: Topology) -> DMatrix<u32> {
+    convolve_on_topology_with_spectrum(plane, &get_kernel_spectrum(kernel, plane.shape()), topology)
+}
 
-    let torus_plane_shape = (torus_plane_height, torus_plane_width);
-    let kernel_shape = kernel.shape();
+// Kernels large enough to be convolved through FFT get their spectra, planned once for the shape of the universe
+fn get_kernel_spectra(rule_kernels: &[RuleKernel], universe_shape: (usize, usize)) -> Vec<Option<KernelSpectrum>> {
+    rule_kernels
+        .iter()
+        .map(|rule_kernel| is_fft_faster(rule_kernel.shape()).then(|| get_kernel_spectrum(rule_kernel, universe_shape)))
+        .collect()
+}
 
-    if kernel_shape > torus_plane_shape {
-        panic!("'convolve_on_torus' expects `torus_plane_shape.shape() > kernel.shape()`, received {:?} and {:?} respectively.", 
-            torus_plane_shape, kernel_shape);
+// Large kernels are convolved through FFT, small ones by sliding dot products
+pub fn count_alive_neighbourhoods(alive_cells: &DMatrix<u32>, rule_kernel: &RuleKernel, topology: Topology) -> DMatrix<u32> {
+    if is_fft_faster(rule_kernel.shape()) {
+        return convolve_on_topology_fft(alive_cells, rule_kernel, topology);
     }
 
-    let mut reflect_slice = |source_position, target_position, slice_shape| {
-        let slice_source = torus_wrapped_plane.slice(source_position, slice_shape).clone_owned();
-        let mut slice_target = torus_wrapped_plane.slice_mut(target_position, slice_shape);
-        slice_target.copy_from(&slice_source);
-    };
+    convolve_on_topology(alive_cells, rule_kernel, topology)
+}
 
-    // Corner shape (suppose kernel is square)
+pub struct Universe {
+    universe_height: usize,
+    universe_width: usize,
+    topology: Topology,
+    rule: Rule,
+    rule_kernels: Vec<RuleKernel>,
+    kernel_spectra: Vec<Option<KernelSpectrum>>,
+    rule_table: Option<RuleTable>,
+    next_generation: UniversePlane,
+    is_parallel: bool,
+    seed: u64,
+    random_generator: StdRng,
+    transition_probabilities: TransitionProbabilities,
+    update_mode: UpdateMode,
+    block_partition_offset: usize,
+    history: Option<History>,
+    cycle_detector: Option<CycleDetector>,
+    generation_stats: Option<GenerationStats>,
+    cell_ages: Option<CellAges>,
+}
 
-    let corner_slice_shape = (kernel_height_half, kernel_width_half);
+impl Universe {
+    fn check_shape(universe_width: usize, universe_height: usize) {
+        if universe_width == 0 || universe_height == 0 {
+            panic!("'Universe' expects non-zero dimensions, received {}x{}.", universe_width, universe_height);
+        }
+    }
 
-    // Upper left corner
+    fn seed_initial_generation(universe_width: usize, universe_height: usize, initial_density: f64, random_generator: &mut StdRng) -> UniversePlane {
+        Universe::check_shape(universe_width, universe_height);
 
-    let upper_left_slice_target_position = (0, 0);
-    let upper_left_slice_source_position = (upper_left_slice_target_position.0 + torus_plane_height, upper_left_slice_target_position.1 + torus_plane_width);
-    reflect_slice(upper_left_slice_source_position, upper_left_slice_target_position, corner_slice_shape);
+        let density_distribution = Bernoulli::new(initial_density)
+            .unwrap_or_else(|_| panic!("'Universe' expects an initial density from 0 to 1, received {}.", initial_density));
+        let initial_generation: UniversePlane = DMatrix::<bool>::from_distribution(universe_height, universe_width, &density_distribution, random_generator)
+            .map(|is_populated| is_populated as u8);
 
-    // Upper right corner
+        initial_generation
+    }
 
-    let upper_right_slice_target_position = (0, kernel_width_half + torus_plane_width);
-    let upper_right_slice_source_position = (upper_right_slice_target_position.0 + torus_plane_height, upper_right_slice_target_position.1 - torus_plane_width);
-    reflect_slice(upper_right_slice_source_position, upper_right_slice_target_position, corner_slice_shape);
+    fn from_initial_generation(universe_width: usize, universe_height: usize, topology: Topology, initial_generation: UniversePlane, seed: u64, random_generator: StdRng) -> Self {
+        let rule = Rule::default();
+        let rule_kernels = rule.kernels();
 
-    // Bottom right corner
+        Universe {
+            universe_height,
+            universe_width,
+            topology,
+            kernel_spectra: get_kernel_spectra(&rule_kernels, (universe_height, universe_width)),
+            rule_kernels,
+            rule,
+            rule_table: None,
+            next_generation: initial_generation,
+            is_parallel: false,
+            seed,
+            random_generator,
+            transition_probabilities: TransitionProbabilities::default(),
+            update_mode: UpdateMode::default(),
+            block_partition_offset: 0,
+            history: None,
+            cycle_detector: None,
+            generation_stats: None,
+            cell_ages: None,
+        }
+    }
 
-    let bottom_right_slice_target_position = (kernel_height_half + torus_plane_height, kernel_width_half + torus_plane_width);
-    let bottom_right_slice_source_position = (bottom_right_slice_target_position.0 - torus_plane_height, bottom_right_slice_target_position.1 - torus_plane_width);
-    reflect_slice(bottom_right_slice_source_position, bottom_right_slice_target_position, corner_slice_shape);
+    pub fn new(universe_width: usize, universe_height: usize, topology: Topology) -> Self {
+        Universe::new_seeded(universe_width, universe_height, topology, rand::random())
+    }
 
-    // Bottom left corner
+    // Seed drives every random choice of the universe, so the same seed replays the same run
+    pub fn new_seeded(universe_width: usize, universe_height: usize, topology: Topology, seed: u64) -> Self {
+        Universe::check_shape(universe_width, universe_height);
+        let initial_generation = UniversePlane::from_element(universe_height, universe_width, CELL_IS_FREE);
 
-    let bottom_left_slice_target_position = (kernel_height_half + torus_plane_height, 0);
-    let bottom_left_slice_source_position = (bottom_left_slice_target_position.0 - torus_plane_height, bottom_left_slice_target_position.1 + torus_plane_width);
-    reflect_slice(bottom_left_slice_source_position, bottom_left_slice_target_position, corner_slice_shape);
+        Universe::from_initial_generation(universe_width, universe_height, topology, initial_generation, seed, StdRng::seed_from_u64(seed))
+    }
 
-    // Horizontal side shape
+    pub fn new_random(universe_width: usize, universe_height: usize, topology: Topology) -> Self {
+        Universe::new_random_seeded(universe_width, universe_height, topology, rand::random(), DEFAULT_INITIAL_DENSITY)
+    }
 
-    let horizontal_side_shape = (torus_plane_height, kernel_width_half);
+    // Random soup where every cell is populated with the probability of the initial density
+    pub fn new_random_seeded(universe_width: usize, universe_height: usize, topology: Topology, seed: u64, initial_density: f64) -> Self {
+        let mut random_generator = StdRng::seed_from_u64(seed);
+        let initial_generation = Universe::seed_initial_generation(universe_width, universe_height, initial_density, &mut random_generator);
 
-    // Left side
+        Universe::from_initial_generation(universe_width, universe_height, topology, initial_generation, seed, random_generator)
+    }
 
-    let left_side_target_position = (kernel_height_half, 0);
-    let left_side_source_position = (left_side_target_position.0, left_side_target_position.1 + torus_plane_width);
-    reflect_slice(left_side_source_position, left_side_target_position, horizontal_side_shape);
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
 
-    // Right side
+    pub fn transition_probabilities(&self) -> TransitionProbabilities {
+        self.transition_probabilities
+    }
 
-    let right_side_target_position = (kernel_height_half, kernel_width_half + torus_plane_width);
-    let right_side_source_position = (right_side_target_position.0, right_side_target_position.1 - torus_plane_width);
-    reflect_slice(right_side_source_position, right_side_target_position, horizontal_side_shape);
+    // Probabilities apply to births and survivals of totalistic rules, rule tables stay deterministic
+    pub fn set_transition_probabilities(&mut self, transition_probabilities: TransitionProbabilities) {
+        self.transition_probabilities = transition_probabilities;
+    }
 
-    // Vertical side shape
+    pub fn update_mode(&self) -> UpdateMode {
+        self.update_mode
+    }
 
-    let vertical_side_shape = (kernel_height_half, torus_plane_width);
+    // Blocks of Margolus mode must tile the joined edges, so those need an even number of cells across
+    pub fn set_update_mode(&mut self, update_mode: UpdateMode) {
+        if let UpdateMode::Margolus(_) = update_mode {
+            let is_height_odd = self.topology.is_vertically_joined() && !self.universe_height.is_multiple_of(BLOCK_SIDE);
+            let is_width_odd = self.topology.is_horizontally_joined() && !self.universe_width.is_multiple_of(BLOCK_SIDE);
+            if is_height_odd || is_width_odd {
+                panic!("Update mode {} doesn't fit the {} universe of shape {:?}, joined edges need an even number of cells across.", update_mode, self.topology, self.shape());
+            }
+        }
 
-    // Upper side
+        self.update_mode = update_mode;
+        self.block_partition_offset = 0;
+    }
 
-    let upper_side_target_position = (0, kernel_width_half);
-    let upper_side_source_position = (upper_side_target_position.0 + torus_plane_height, upper_side_target_position.1);
-    reflect_slice(upper_side_source_position, upper_side_target_position, vertical_side_shape);
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
 
-    // Bottom side
+    pub fn history(&self) -> Option<&History> {
+        self.history.as_ref()
+    }
 
-    let bottom_side_target_position = (kernel_height_half + torus_plane_height, kernel_width_half);
-    let bottom_side_source_position = (bottom_side_target_position.0 - torus_plane_height, bottom_side_target_position.1);
-    reflect_slice(bottom_side_source_position, bottom_side_target_position, vertical_side_shape);
+    // History keeps the last generations and edits up to the capacity, zero capacity turns it off
+    pub fn set_history_capacity(&mut self, history_capacity: usize) {
+        self.history = (history_capacity > 0).then(|| History::new(history_capacity, self.next_generation.as_slice(), self.block_partition_offset));
+    }
 
-    // Convolve
+    // Cells changed since the last generation become a single edit which can be undone at once
+    pub fn commit_edits(&mut self) {
+        if let Some(history) = self.history.as_mut() {
+            history.record(HistoryEntryKind::Edit, self.next_generation.as_slice(), self.block_partition_offset);
+        }
+    }
 
-    let mut convolve_result = SMatrix::<u8, R1, C1>::zeros();
-    let min_row_index = kernel_height_half;
-    let max_row_index = torus_plane_height + kernel_height_half;
-    let min_column_index = kernel_width_half;
-    let max_column_index = torus_plane_width + kernel_width_half;
+    // Undo the last generation or edit along with its block partition, tell whether there was one
+    pub fn step_back(&mut self) -> bool {
+        let is_undone = match self.history.as_mut() {
+            Some(history) => history.undo(self.next_generation.as_mut_slice(), &mut self.block_partition_offset).is_some(),
+            None => false,
+        };
+        if is_undone {
+            self.restart_age_tracking();
+        }
 
-    for row_index in min_row_index..max_row_index
-    {
-        for column_index in min_column_index..max_column_index
-        {
-            let convolve_slice_position = (row_index - kernel_height_half, column_index - kernel_width_half);
-            let matrix_slice = torus_wrapped_plane.slice(convolve_slice_position, kernel.shape());
+        is_undone
+    }
 
-            let convolve_target_position = (row_index, column_index);
-            convolve_result[convolve_target_position] = matrix_slice.dot(&kernel);
+    // Redo the last undone generation or edit along with its block partition, tell whether there was one
+    pub fn step_forward(&mut self) -> bool {
+        let is_redone = match self.history.as_mut() {
+            Some(history) => history.redo(self.next_generation.as_mut_slice(), &mut self.block_partition_offset).is_some(),
+            None => false,
+        };
+        if is_redone {
+            self.restart_age_tracking();
         }
+
+        is_redone
     }
 
-    convolve_result
-}
+    pub fn rewind_to(&mut self, history_position: usize) {
+        if let Some(history) = self.history.as_mut() {
+            history.rewind_to(history_position, self.next_generation.as_mut_slice(), &mut self.block_partition_offset);
+            self.restart_age_tracking();
+        }
+    }
 
-const RULE_KERNEL: RuleKernel = matrix![1, 1, 1;
-                                        1, 0, 1;
-                                        1, 1, 1];
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
 
-pub struct Universe {
-    next_generation_wrapped: UniverseWrapped,
-    alive_neighbours_wrapped: UniverseWrapped,
-}
+    // Tracked ages start from the current generation, with every non-free cell newborn
+    pub fn set_age_tracking(&mut self, is_tracking_ages: bool) {
+        self.cell_ages = is_tracking_ages.then(|| CellAges::new(&self.next_generation));
+    }
 
-impl Universe {
-    fn seed_initial_generation() -> UniverseWrapped {
-        let mut random_generator = rand::thread_rng();
-        let uniform_range = Uniform::new_inclusive(0, 1);
-        let initial_generation: UniverseWrapped = UniverseWrapped::from_distribution(&uniform_range, &mut random_generator);
- 
-        initial_generation
+    // History keeps no ages, so moving through it starts the tracked ages again from the generation moved to
+    fn restart_age_tracking(&mut self) {
+        if let Some(cell_ages) = self.cell_ages.as_mut() {
+            *cell_ages = CellAges::new(&self.next_generation);
+        }
     }
 
-    pub fn new_random() -> Self {
-        let mut initial_generation = Universe::seed_initial_generation(); 
-        let initial_neighbours = convolve_torus(&mut initial_generation, &RULE_KERNEL);
+    pub fn is_tracking_ages(&self) -> bool {
+        self.cell_ages.is_some()
+    }
 
-        Universe {
-            next_generation_wrapped: initial_generation,
-            alive_neighbours_wrapped: initial_neighbours,
+    // Generations the cell has been non-free, none for free cells or when ages aren't tracked.
+    // Cells drawn since the last generation are newborn
+    pub fn get_cell_age(&self, cell_row: usize, cell_column: usize) -> Option<u32> {
+        let position = self.get_plane_position(cell_row, cell_column);
+        if self.next_generation[position] == CELL_IS_FREE {
+            return None;
+        }
+
+        self.cell_ages.as_ref().map(|cell_ages| cell_ages.get_age(position).unwrap_or(0))
+    }
+
+    // Generations since the cell got free, none for non-free cells and cells which were never tracked alive
+    pub fn get_time_since_death(&self, cell_row: usize, cell_column: usize) -> Option<u32> {
+        let position = self.get_plane_position(cell_row, cell_column);
+        if self.next_generation[position] != CELL_IS_FREE {
+            return None;
+        }
+
+        let cell_ages = self.cell_ages.as_ref()?;
+        match cell_ages.get_age(position) {
+            Some(_) => Some(0),
+            None => cell_ages.get_time_since_death(position),
+        }
+    }
+
+    // Collected statistics start from the current generation, with nothing born or dead yet
+    pub fn set_stats_collection(&mut self, is_collecting_stats: bool) {
+        self.generation_stats = is_collecting_stats.then(|| GenerationStats::from_generations(0, &self.next_generation, &self.next_generation, self.topology));
+    }
+
+    // Statistics of the last step, if they are collected
+    pub fn generation_stats(&self) -> Option<&GenerationStats> {
+        self.generation_stats.as_ref()
+    }
+
+    // Cycle detection compares every generation with the previous ones up to the maximum period, zero turns it off
+    pub fn set_cycle_detection(&mut self, max_period: usize) {
+        self.cycle_detector = (max_period > 0).then(|| {
+            let mut cycle_detector = CycleDetector::new(max_period, self.topology);
+            cycle_detector.follow(&self.next_generation, self.block_partition_offset);
+            cycle_detector
+        });
+    }
+
+    // Random update modes and transition probabilities can take the same generation to different ones,
+    // so cycles are only detected without them
+    pub fn is_deterministic(&self) -> bool {
+        let is_update_mode_deterministic = matches!(self.update_mode, UpdateMode::Synchronous | UpdateMode::Checkerboard | UpdateMode::Margolus(_));
+        is_update_mode_deterministic && self.transition_probabilities.is_deterministic()
+    }
+
+    pub fn detected_cycle(&self) -> Option<Cycle> {
+        self.cycle_detector.as_ref().and_then(|cycle_detector| cycle_detector.cycle())
+    }
+
+    // Step the universe until it repeats itself or the generations run out,
+    // cycles longer than the maximum period of the detection aren't caught
+    pub fn find_cycle(&mut self, max_generations: usize) -> Option<Cycle> {
+        if self.cycle_detector.is_none() {
+            self.set_cycle_detection(DEFAULT_MAX_CYCLE_PERIOD);
+        }
+
+        for _ in 0..max_generations {
+            if self.detected_cycle().is_some() {
+                break;
+            }
+
+            self.next_generation();
+        }
+
+        self.detected_cycle()
+    }
+
+    // Cells within the interaction distance of each other make up an object, free cell belongs to no object
+    pub fn get_object_positions(&self, cell_row: usize, cell_column: usize) -> Vec<(usize, usize)> {
+        let position = self.get_plane_position(cell_row, cell_column);
+        find_object(&self.next_generation, self.topology, position, get_interaction_distance(&self.rule))
+            .map_or(Vec::new(), |object| object.positions().to_vec())
+    }
+
+    pub fn get_objects(&self) -> Vec<UniverseObject> {
+        segment_objects(&self.next_generation, self.topology, get_interaction_distance(&self.rule))
+    }
+
+    // Object is copied into a torus of its own with enough free cells around it to evolve undisturbed,
+    // the sides stay even for the rules and update modes which need them. Hexagonal and triangular cells,
+    // the checkerboard and the Margolus blocks depend on the parity of the rows and columns, so the object
+    // keeps the one it had, and the block partition comes along
+    fn isolate_object(&self, object: &UniverseObject) -> Universe {
+        let isolation_margin = 4 * get_interaction_distance(&self.rule) as usize;
+        let (object_height, object_width) = object.cells().shape();
+        let (object_row, object_column) = object.upper_left_position();
+        let (first_row, first_column) = (isolation_margin + object_row.rem_euclid(2) as usize, isolation_margin + object_column.rem_euclid(2) as usize);
+        let round_to_even = |side: usize| side + side % 2;
+        let mut object_universe = Universe::new_seeded(round_to_even(first_column + object_width + isolation_margin), round_to_even(first_row + object_height + isolation_margin), Topology::Torus, self.seed);
+
+        object_universe.set_rule(self.rule.clone());
+        if let Some(rule_table) = &self.rule_table {
+            object_universe.set_rule_table(rule_table.clone());
+        }
+        object_universe.set_transition_probabilities(self.transition_probabilities);
+        object_universe.set_update_mode(self.update_mode);
+        object_universe.block_partition_offset = self.block_partition_offset;
+        object_universe.set_cycle_detection(self.cycle_detector.as_ref().map_or(DEFAULT_MAX_CYCLE_PERIOD, |cycle_detector| cycle_detector.max_period()));
+
+        object_universe.next_generation
+            .slice_range_mut(first_row..first_row + object_height, first_column..first_column + object_width)
+            .copy_from(object.cells());
+        object_universe
+    }
+
+    // Object containing the cell is stepped alone, so that the rest of the universe doesn't disturb the detection
+    pub fn find_object_cycle(&self, cell_row: usize, cell_column: usize, max_generations: usize) -> Option<Cycle> {
+        let position = self.get_plane_position(cell_row, cell_column);
+        let object = find_object(&self.next_generation, self.topology, position, get_interaction_distance(&self.rule))?;
+        self.isolate_object(&object).find_cycle(max_generations)
+    }
+
+    // Step the object alone until it repeats itself and code all of its phases, see `get_object_code`.
+    // Object which dies out leaves nothing to code, so it gets none rather than `xs0_`
+    pub fn classify_object(&self, object: &UniverseObject, max_generations: usize) -> Option<String> {
+        let mut object_universe = self.isolate_object(object);
+        let cycle = match object_universe.find_cycle(max_generations) {
+            Some(cycle) => cycle,
+            None => return Some(String::from(UNCLASSIFIED_OBJECT_CODE)),
+        };
+        if object_universe.next_generation.iter().all(|cell_state| *cell_state == CELL_IS_FREE) {
+            return None;
+        }
+
+        let phases: Vec<UniversePlane> = (0..cycle.period())
+            .map(|_| {
+                let (_, phase) = crop_populated_cells(&object_universe.next_generation, Topology::Torus);
+                object_universe.next_generation();
+                phase
+            })
+            .collect();
+
+        Some(get_object_code(cycle, &phases))
+    }
+
+    // Count the objects left in the universe by their codes, best taken once it has settled
+    pub fn take_census(&self, max_generations: usize) -> Census {
+        let mut census = Census::new();
+        for object_code in self.get_objects().iter().filter_map(|object| self.classify_object(object, max_generations)) {
+            census.add(&object_code);
+        }
+
+        census
+    }
+
+    // Search goes back through the rule alone, so neither rule tables nor random or partial updates can be reversed
+    pub fn is_reversible(&self) -> bool {
+        self.rule_table.is_none() && self.update_mode == UpdateMode::Synchronous && self.transition_probabilities.is_deterministic() && self.rule.number_of_states() == 2
+    }
+
+    fn check_reversible(&self) {
+        if !self.is_reversible() {
+            panic!("Predecessors can only be searched for universes of deterministic synchronous rules of two states, received {} updated {}.", self.rule, self.update_mode);
+        }
+    }
+
+    // Find a generation which turns into the current one, cells beyond the edges which aren't joined are free
+    pub fn find_predecessor(&self) -> PredecessorSearchResult {
+        self.check_reversible();
+        find_predecessor(&self.rule, self.topology, &self.next_generation, |_, _| true, DEFAULT_PREDECESSOR_SEARCH_STEP_LIMIT)
+    }
+
+    // Find a generation which turns into the current one within the region, whatever happens to the other cells
+    pub fn find_region_predecessor(&self, rows: Range<usize>, columns: Range<usize>) -> PredecessorSearchResult {
+        self.check_reversible();
+        if rows.end > self.universe_height || columns.end > self.universe_width {
+            panic!("Region of rows {:?} and columns {:?} is out of the universe of shape {:?}.", rows, columns, self.shape());
+        }
+
+        let is_in_region = |row_index: usize, column_index: usize| rows.contains(&row_index) && columns.contains(&column_index);
+        find_predecessor(&self.rule, self.topology, &self.next_generation, is_in_region, DEFAULT_PREDECESSOR_SEARCH_STEP_LIMIT)
+    }
+
+    pub fn is_garden_of_eden(&self) -> bool {
+        self.find_predecessor() == PredecessorSearchResult::GardenOfEden
+    }
+
+    // Replace the current generation by its predecessor as an edit which can be undone, tell whether one was found
+    pub fn reverse_generation(&mut self) -> bool {
+        matches!(self.reverse_generation_within(DEFAULT_PREDECESSOR_SEARCH_STEP_LIMIT), PredecessorSearchResult::Predecessor(_))
+    }
+
+    // Search is given up after the number of steps, which keeps it short enough for interactive use
+    pub fn reverse_generation_within(&mut self, step_limit: u64) -> PredecessorSearchResult {
+        self.check_reversible();
+        let search_result = find_predecessor(&self.rule, self.topology, &self.next_generation, |_, _| true, step_limit);
+        if let PredecessorSearchResult::Predecessor(predecessor) = &search_result {
+            self.commit_edits();
+            self.next_generation = predecessor.clone();
+            self.commit_edits();
+            self.restart_age_tracking();
         }
+
+        search_result
+    }
+
+    pub fn is_parallel(&self) -> bool {
+        self.is_parallel
+    }
+
+    // Parallel stepping splits the universe into a band of rows per thread
+    pub fn set_parallel(&mut self, is_parallel: bool) {
+        self.is_parallel = is_parallel;
+    }
+
+    // Decay states which don't exist in the new rule are freed
+    pub fn set_rule(&mut self, rule: Rule) {
+        if !rule.grid().is_compatible_with(self.topology, self.shape()) {
+            panic!("Rule {} doesn't fit the {} universe of shape {:?}, joined edges need an even number of cells across.", rule, self.topology, self.shape());
+        }
+
+        let number_of_states = rule.number_of_states();
+        self.next_generation.apply(|cell_state| {
+            if *cell_state >= number_of_states {
+                *cell_state = CELL_IS_FREE;
+            }
+        });
+
+        self.rule_kernels = rule.kernels();
+        self.kernel_spectra = get_kernel_spectra(&self.rule_kernels, self.shape());
+        self.rule = rule;
+        self.rule_table = None;
+    }
+
+    pub fn rule_table(&self) -> Option<&RuleTable> {
+        self.rule_table.as_ref()
+    }
+
+    // Rule table takes over the rule until another rule is set, states which don't exist in the table are freed
+    pub fn set_rule_table(&mut self, rule_table: RuleTable) {
+        let number_of_states = rule_table.number_of_states();
+        self.next_generation.apply(|cell_state| {
+            if *cell_state >= number_of_states {
+                *cell_state = CELL_IS_FREE;
+            }
+        });
+
+        // Default rule is kept behind the table, without freeing the states of the table it doesn't know
+        let rule = Rule::default();
+        self.rule_kernels = rule.kernels();
+        self.kernel_spectra = get_kernel_spectra(&self.rule_kernels, self.shape());
+        self.rule = rule;
+        self.rule_table = Some(rule_table);
     }
 
     pub fn shape(&self) -> (usize, usize) {
-        (UNIVERSE_HEIGHT, UNIVERSE_WIDTH)
+        (self.universe_height, self.universe_width)
+    }
+
+    fn get_plane_position(&self, cell_row: usize, cell_column: usize) -> (usize, usize) {
+        if cell_row >= self.universe_height || cell_column >= self.universe_width {
+            panic!("Cell ({}, {}) is out of the universe of shape {:?}.", cell_row, cell_column, self.shape());
+        }
+
+        (cell_row, cell_column)
+    }
+
+    pub fn populate_cell(&mut self, cell_row: usize, cell_column: usize) {
+        let position = self.get_plane_position(cell_row, cell_column);
+        self.next_generation[position] = CELL_IS_POPULATED;
+    }
+
+    pub fn free_cell(&mut self, cell_row: usize, cell_column: usize) {
+        let position = self.get_plane_position(cell_row, cell_column);
+        self.next_generation[position] = CELL_IS_FREE;
+    }
+
+    pub fn set_cell_state(&mut self, cell_row: usize, cell_column: usize, cell_state: u8) {
+        if cell_state >= self.number_of_states() {
+            panic!("Cell state {} doesn't exist in rule {}.", cell_state, self.rule);
+        }
+
+        let position = self.get_plane_position(cell_row, cell_column);
+        self.next_generation[position] = cell_state;
+    }
+
+    pub fn get_cell_state(&self, cell_row: usize, cell_column: usize) -> u8 {
+        let position = self.get_plane_position(cell_row, cell_column);
+        self.next_generation[position]
     }
 
-    fn get_plane_position(cell_row: u8, cell_column: u8) -> (usize, usize) {
-        ((cell_row as usize) + RULE_KERNEL_HEIGHT_HALF, (cell_column as usize) + RULE_KERNEL_WIDTH_HALF)
+    // Clicking a cell walks it through all the states of the rule, the new state is returned
+    pub fn cycle_cell_state(&mut self, cell_row: usize, cell_column: usize) -> u8 {
+        let position = self.get_plane_position(cell_row, cell_column);
+        let cell_state = ((self.next_generation[position] as u16 + 1) % (self.number_of_states() as u16)) as u8;
+
+        self.next_generation[position] = cell_state;
+        cell_state
     }
 
-    pub fn populate_cell(&mut self, cell_row: u8, cell_column: u8) {
-        let position = Universe::get_plane_position(cell_row, cell_column);
-        self.next_generation_wrapped[position] = CELL_IS_POPULATED;
+    pub fn number_of_states(&self) -> u8 {
+        match &self.rule_table {
+            Some(rule_table) => rule_table.number_of_states(),
+            None => self.rule.number_of_states(),
+        }
     }
 
-    pub fn free_cell(&mut self, cell_row: u8, cell_column: u8) {
-        let position = Universe::get_plane_position(cell_row, cell_column);
-        self.next_generation_wrapped[position] = CELL_IS_FREE;
+    // Decaying cells of Generations rules are neither free nor populated,
+    // so only populated ones are taken as alive neighbours
+    fn get_alive_neighbourhoods(&self) -> DMatrix<u32> {
+        let alive_cells = self.next_generation.map(|cell_state| (cell_state == CELL_IS_POPULATED) as u32);
+        let mut parity_neighbourhoods: Vec<DMatrix<u32>> = self.rule_kernels
+            .iter()
+            .zip(self.kernel_spectra.iter())
+            .map(|(rule_kernel, kernel_spectrum)| {
+                if let Some(kernel_spectrum) = kernel_spectrum {
+                    return convolve_on_topology_with_spectrum(&alive_cells, kernel_spectrum, self.topology);
+                }
+
+                if self.is_parallel {
+                    return convolve_on_topology_parallel(&alive_cells, rule_kernel, self.topology, rayon::current_num_threads());
+                }
+
+                convolve_on_topology(&alive_cells, rule_kernel, self.topology)
+            })
+            .collect();
+
+        if parity_neighbourhoods.len() == 1 {
+            return parity_neighbourhoods.pop().unwrap();
+        }
+
+        // Each cell takes the neighbourhood counted with the kernel of its parity
+        let grid = self.rule.grid();
+        DMatrix::from_fn(self.universe_height, self.universe_width, |row_index, column_index| {
+            parity_neighbourhoods[grid.get_cell_parity(row_index, column_index)][(row_index, column_index)]
+        })
     }
 
-    pub fn get_cell_state(&self, cell_row: u8, cell_column: u8) -> u8 {
-        let position = Universe::get_plane_position(cell_row, cell_column);
-        return self.next_generation_wrapped[position];
+    fn is_need_to_be_killed(rule: &Rule, current_cell: u8, alive_neighbourhood: u32) -> bool
+    {
+        current_cell == CELL_IS_POPULATED && !rule.is_survival(alive_neighbourhood)
     }
 
-    fn is_need_to_be_killed(current_cell: u8, number_of_alive_neighbours: u8) -> bool
+    fn is_need_to_be_alived(rule: &Rule, current_cell: u8, alive_neighbourhood: u32) -> bool
     {
-        return current_cell == CELL_IS_POPULATED && (number_of_alive_neighbours < 2 || number_of_alive_neighbours > 3);
+        current_cell == CELL_IS_FREE && rule.is_birth(alive_neighbourhood)
     }
 
-    fn is_need_to_be_alived(current_cell: u8, number_of_alive_neighbours: u8) -> bool
+    fn is_decaying(current_cell: u8) -> bool
     {
-        return current_cell == CELL_IS_FREE && number_of_alive_neighbours == 3;
+        current_cell > CELL_IS_POPULATED
     }
 
-    pub fn next_generation(&mut self) {
-        self.alive_neighbours_wrapped = convolve_torus(&mut self.next_generation_wrapped, &RULE_KERNEL);
+    // Killed cell either gets free or enters the first decay state,
+    // and each decay state is followed by the next one until the cell gets free
+    fn get_decayed_state(rule: &Rule, current_cell: u8) -> u8
+    {
+        ((current_cell as u16 + 1) % (rule.number_of_states() as u16)) as u8
+    }
+
+    pub fn get_next_cell_state(rule: &Rule, current_cell: u8, alive_neighbourhood: u32) -> u8
+    {
+        if Universe::is_need_to_be_alived(rule, current_cell, alive_neighbourhood)
+        {
+            CELL_IS_POPULATED
+        }
+        else if Universe::is_need_to_be_killed(rule, current_cell, alive_neighbourhood) || Universe::is_decaying(current_cell)
+        {
+            Universe::get_decayed_state(rule, current_cell)
+        }
+        else
+        {
+            current_cell
+        }
+    }
+
+    // Births and survivals allowed by the rule are drawn with their probabilities,
+    // a populated cell which fails to survive decays like a killed one
+    fn get_stochastic_next_cell_state(rule: &Rule, transition_probabilities: &TransitionProbabilities, current_cell: u8, alive_neighbourhood: u32, random_generator: &mut StdRng) -> u8
+    {
+        if Universe::is_need_to_be_alived(rule, current_cell, alive_neighbourhood)
+        {
+            if is_happening(random_generator, transition_probabilities.birth_probability()) { CELL_IS_POPULATED } else { current_cell }
+        }
+        else if current_cell == CELL_IS_POPULATED && rule.is_survival(alive_neighbourhood)
+        {
+            if is_happening(random_generator, transition_probabilities.survival_probability()) { current_cell } else { Universe::get_decayed_state(rule, current_cell) }
+        }
+        else
+        {
+            Universe::get_next_cell_state(rule, current_cell, alive_neighbourhood)
+        }
+    }
+
+    fn get_next_cell_state_by_rule(&mut self, current_cell: u8, alive_neighbourhood: u32) -> u8 {
+        if self.transition_probabilities.is_deterministic() {
+            return Universe::get_next_cell_state(&self.rule, current_cell, alive_neighbourhood);
+        }
+
+        Universe::get_stochastic_next_cell_state(&self.rule, &self.transition_probabilities, current_cell, alive_neighbourhood, &mut self.random_generator)
+    }
+
+    // Checkerboard squares are told apart by the parity of the sum of the cell indices
+    fn is_cell_chosen(checkerboard_parity: Option<usize>, cell_row: usize, cell_column: usize) -> bool {
+        checkerboard_parity.is_none_or(|checkerboard_parity| (cell_row + cell_column) % 2 == checkerboard_parity)
+    }
 
-        self.next_generation_wrapped.zip_apply(&self.alive_neighbours_wrapped, |current_cell_state, number_of_alive_neighbours| {
-            if Universe::is_need_to_be_alived(*current_cell_state, number_of_alive_neighbours)
-            {
-                *current_cell_state = CELL_IS_POPULATED;
+    // Every cell looks its neighbours up in the plane padded by one cell beyond the edges,
+    // cells beyond the edges which aren't joined are free
+    fn update_cells_by_table(&mut self, checkerboard_parity: Option<usize>) {
+        let rule_table = self.rule_table.as_mut().unwrap();
+        let update_probability = self.update_mode.update_probability();
+        let neighbour_offsets = rule_table.neighbourhood().neighbour_offsets();
+        let padded_shape = (self.universe_height + 2, self.universe_width + 2);
+        let padded_plane = pad_matrix(&self.next_generation, &self.topology, padded_shape, (1, 1));
+
+        let mut cell_states = Vec::with_capacity(neighbour_offsets.len() + 1);
+        for row_index in 0..self.universe_height {
+            for column_index in 0..self.universe_width {
+                if !Universe::is_cell_chosen(checkerboard_parity, row_index, column_index) || !is_happening(&mut self.random_generator, update_probability) {
+                    continue;
+                }
+
+                cell_states.clear();
+                cell_states.push(self.next_generation[(row_index, column_index)]);
+                for (row_offset, column_offset) in neighbour_offsets {
+                    let padded_position = ((row_index as isize + 1 + row_offset) as usize, (column_index as isize + 1 + column_offset) as usize);
+                    cell_states.push(padded_plane[padded_position]);
+                }
+
+                self.next_generation[(row_index, column_index)] = rule_table.get_next_state(&cell_states);
             }
-            else if Universe::is_need_to_be_killed(*current_cell_state, number_of_alive_neighbours)
-            {
-                *current_cell_state = CELL_IS_FREE;
+        }
+    }
+
+    // Chosen cells get their next states all at once from the current generation,
+    // in the α-asynchronous mode each of them only with the update probability
+    fn update_cells(&mut self, checkerboard_parity: Option<usize>) {
+        if self.rule_table.is_some() {
+            self.update_cells_by_table(checkerboard_parity);
+            return;
+        }
+
+        let alive_neighbourhoods = self.get_alive_neighbourhoods();
+        let update_probability = self.update_mode.update_probability();
+        for row_index in 0..self.universe_height {
+            for column_index in 0..self.universe_width {
+                if !Universe::is_cell_chosen(checkerboard_parity, row_index, column_index) || !is_happening(&mut self.random_generator, update_probability) {
+                    continue;
+                }
+
+                let position = (row_index, column_index);
+                self.next_generation[position] = self.get_next_cell_state_by_rule(self.next_generation[position], alive_neighbourhoods[position]);
             }
-        });
+        }
+    }
+
+    fn get_neighbour_state(&self, cell_row: usize, cell_column: usize, row_offset: isize, column_offset: isize) -> u8 {
+        self.topology
+            .get_plane_position(cell_row as isize + row_offset, cell_column as isize + column_offset, self.shape())
+            .map_or(CELL_IS_FREE, |position| self.next_generation[position])
+    }
+
+    fn count_alive_neighbourhood_at(&self, cell_row: usize, cell_column: usize) -> u32 {
+        let rule_kernel = &self.rule_kernels[self.rule.grid().get_cell_parity(cell_row, cell_column)];
+        let (kernel_height_half, kernel_width_half) = get_kernel_halves(rule_kernel);
+
+        let mut alive_neighbourhood = 0;
+        for ((kernel_row, kernel_column), weight) in rule_kernel.iter().enumerate().map(|(index, weight)| ((index % rule_kernel.nrows(), index / rule_kernel.nrows()), *weight)) {
+            let row_offset = kernel_row as isize - kernel_height_half as isize;
+            let column_offset = kernel_column as isize - kernel_width_half as isize;
+            if weight != 0 && self.get_neighbour_state(cell_row, cell_column, row_offset, column_offset) == CELL_IS_POPULATED {
+                alive_neighbourhood += weight;
+            }
+        }
+
+        alive_neighbourhood
+    }
+
+    // Every cell is updated once per generation in random order, seeing the states its neighbours got so far
+    fn update_cells_sequentially(&mut self) {
+        let mut positions: Vec<(usize, usize)> = (0..self.universe_height)
+            .flat_map(|row_index| (0..self.universe_width).map(move |column_index| (row_index, column_index)))
+            .collect();
+        positions.shuffle(&mut self.random_generator);
+
+        for (row_index, column_index) in positions {
+            let current_cell = self.next_generation[(row_index, column_index)];
+            self.next_generation[(row_index, column_index)] = match &self.rule_table {
+                Some(rule_table) => {
+                    let mut cell_states = vec![current_cell];
+                    for (row_offset, column_offset) in rule_table.neighbourhood().neighbour_offsets() {
+                        cell_states.push(self.get_neighbour_state(row_index, column_index, *row_offset, *column_offset));
+                    }
+
+                    self.rule_table.as_mut().unwrap().get_next_state(&cell_states)
+                }
+                None => {
+                    let alive_neighbourhood = self.count_alive_neighbourhood_at(row_index, column_index);
+                    self.get_next_cell_state_by_rule(current_cell, alive_neighbourhood)
+                }
+            };
+        }
+    }
+
+    // Blocks starting beyond the upper (left) edge are only needed when it isn't joined to the opposite one,
+    // otherwise the last block wraps around and covers the same cells
+    fn get_block_starts(partition_offset: usize, universe_size: usize, is_joined: bool) -> Vec<isize> {
+        let first_block_start = if is_joined || partition_offset == 0 { partition_offset as isize } else { partition_offset as isize - BLOCK_SIDE as isize };
+        (first_block_start..universe_size as isize).step_by(BLOCK_SIDE).collect()
+    }
+
+    // Cells beyond the edges which aren't joined are free, and whatever the rule puts there is lost
+    fn update_blocks(&mut self, block_rule: BlockRule) {
+        let block_offsets = [(0, 0), (0, 1), (1, 0), (1, 1)];
+        let block_row_starts = Universe::get_block_starts(self.block_partition_offset, self.universe_height, self.topology.is_vertically_joined());
+        let block_column_starts = Universe::get_block_starts(self.block_partition_offset, self.universe_width, self.topology.is_horizontally_joined());
+
+        for block_row_start in &block_row_starts {
+            for block_column_start in &block_column_starts {
+                let block_positions = block_offsets.map(|(row_offset, column_offset)| {
+                    self.topology.get_plane_position(block_row_start + row_offset, block_column_start + column_offset, self.shape())
+                });
+
+                let block = block_positions
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, position)| position.is_some_and(|position| self.next_generation[position] == CELL_IS_POPULATED))
+                    .map(|(cell_index, _)| 1 << cell_index)
+                    .sum::<u8>();
+
+                let next_block = block_rule.get_next_block(block);
+                for (cell_index, position) in block_positions.iter().enumerate() {
+                    if let Some(position) = position {
+                        self.next_generation[*position] = (next_block >> cell_index) & 1;
+                    }
+                }
+            }
+        }
+
+        self.block_partition_offset = 1 - self.block_partition_offset;
+    }
+
+    pub fn next_generation(&mut self) {
+        self.commit_edits();
+        let previous_generation = self.generation_stats.is_some().then(|| self.next_generation.clone());
+        if let Some(cell_ages) = self.cell_ages.as_mut() {
+            cell_ages.record_edits(&self.next_generation);
+        }
+        let is_deterministic = self.is_deterministic();
+        if let Some(cycle_detector) = self.cycle_detector.as_mut() {
+            if is_deterministic {
+                cycle_detector.follow(&self.next_generation, self.block_partition_offset);
+            } else {
+                cycle_detector.reset();
+            }
+        }
+
+        match self.update_mode {
+            UpdateMode::Synchronous | UpdateMode::AlphaAsynchronous(_) => self.update_cells(None),
+            UpdateMode::RandomSequential => self.update_cells_sequentially(),
+            UpdateMode::Checkerboard => {
+                self.update_cells(Some(0));
+                self.update_cells(Some(1));
+            }
+            UpdateMode::Margolus(block_rule) => self.update_blocks(block_rule),
+        }
+
+        if let Some(history) = self.history.as_mut() {
+            history.record(HistoryEntryKind::Generation, self.next_generation.as_slice(), self.block_partition_offset);
+        }
+
+        if let Some(cycle_detector) = self.cycle_detector.as_mut().filter(|_| is_deterministic) {
+            cycle_detector.observe(&self.next_generation, self.block_partition_offset);
+        }
+
+        if let (Some(generation_stats), Some(previous_generation)) = (self.generation_stats.as_mut(), previous_generation) {
+            *generation_stats = GenerationStats::from_generations(generation_stats.generation() + 1, &previous_generation, &self.next_generation, self.topology);
+        }
+
+        if let Some(cell_ages) = self.cell_ages.as_mut() {
+            cell_ages.advance(&self.next_generation);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use nalgebra::{matrix, SMatrix};
+    use crate::constants::*;
 
-    fn iterate_universe<F: FnMut(u8, u8)>(shape: (usize, usize), mut indexer: F) {
+    fn iterate_universe<F: FnMut(usize, usize)>(shape: (usize, usize), mut indexer: F) {
         let (universe_height, universe_width) = shape;
         for row_index in 0..universe_height {
             for column_index in 0..universe_width {
-                indexer(row_index as u8, column_index as u8);
+                indexer(row_index, column_index);
             }
         }
     }
 
     fn create_free_universe() -> Universe {
-        let mut universe = Universe::new_random();
-        let shape = universe.shape();
-
-        let indexer = |row_index: u8, column_index: u8| {
-            universe.free_cell(row_index, column_index);
-        };
-
-        iterate_universe(shape, indexer);
-        universe
+        Universe::new(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::Torus)
     }
 
     #[test]
     fn at_least_one_cell_is_populated() {
 
-        let universe = Universe::new_random();
+        let universe = Universe::new_random(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::Torus);
         let mut number_of_populated_cells: usize = 0;
 
-        let indexer = |row_index: u8, column_index: u8| {
+        let indexer = |row_index: usize, column_index: usize| {
             if universe.get_cell_state(row_index, column_index) == CELL_IS_POPULATED {
                 number_of_populated_cells += 1;
             }
@@ -244,10 +923,10 @@ mod tests {
     #[test]
     fn at_least_one_cell_is_free() {
 
-        let universe = Universe::new_random();
+        let universe = Universe::new_random(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::Torus);
         let mut number_of_free_cells: usize = 0;
 
-        let indexer = |row_index: u8, column_index: u8| {
+        let indexer = |row_index: usize, column_index: usize| {
             if universe.get_cell_state(row_index, column_index) == CELL_IS_FREE {
                 number_of_free_cells += 1;
             }
@@ -259,10 +938,10 @@ mod tests {
 
     #[test]
     fn any_cell_can_be_populated() {
-        let mut universe = Universe::new_random();
+        let mut universe = Universe::new_random(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::Torus);
         let shape = universe.shape();
 
-        let indexer = |row_index: u8, column_index: u8| {
+        let indexer = |row_index: usize, column_index: usize| {
             universe.populate_cell(row_index, column_index);
 
             let cell_state = universe.get_cell_state(row_index, column_index);
@@ -274,10 +953,10 @@ mod tests {
 
     #[test]
     fn any_cell_can_be_freed() {
-        let mut universe = Universe::new_random();
+        let mut universe = Universe::new_random(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::Torus);
         let shape = universe.shape();
 
-        let indexer = |row_index: u8, column_index: u8| {
+        let indexer = |row_index: usize, column_index: usize| {
             universe.free_cell(row_index, column_index);
 
             let cell_state = universe.get_cell_state(row_index, column_index);
@@ -297,10 +976,10 @@ mod tests {
                                                                              CELL_IS_POPULATED, CELL_IS_POPULATED, CELL_IS_POPULATED];
 
     fn build_glider(upper_left_position: (usize, usize), universe: &mut Universe) {
-        let indexer = |row_index: u8, column_index: u8| {
-            let target_row_index = (upper_left_position.0 as u8) + row_index;
-            let target_column_index = (upper_left_position.1 as u8) + column_index;
-            let pattern_state = GLIDER_PATTERN[(row_index as usize, column_index as usize)];
+        let indexer = |row_index: usize, column_index: usize| {
+            let target_row_index = upper_left_position.0 + row_index;
+            let target_column_index = upper_left_position.1 + column_index;
+            let pattern_state = GLIDER_PATTERN[(row_index, column_index)];
 
             match pattern_state {
                 CELL_IS_POPULATED => universe.populate_cell(target_row_index, target_column_index),
@@ -312,23 +991,24 @@ mod tests {
         iterate_universe((GLIDER_HEIGHT, GLIDER_WIDTH), indexer);
     }
 
+    // Every cell of the pattern has to match, not only the last one compared
     fn is_glider_detected(upper_left_position: (usize, usize), universe: &Universe) -> bool {
-        let mut is_glider_detected = true;  
-        let indexer = |row_index: u8, column_index: u8| {
-            let target_row_index = (upper_left_position.0 as u8) + row_index;
-            let target_column_index = (upper_left_position.1 as u8) + column_index;
+        let mut is_glider_detected = true;
+        let indexer = |row_index: usize, column_index: usize| {
+            let target_row_index = upper_left_position.0 + row_index;
+            let target_column_index = upper_left_position.1 + column_index;
 
-            let pattern_state = GLIDER_PATTERN[(row_index as usize, column_index as usize)];
+            let pattern_state = GLIDER_PATTERN[(row_index, column_index)];
             let universe_state = universe.get_cell_state(target_row_index, target_column_index);
 
-            is_glider_detected = pattern_state == universe_state;
+            is_glider_detected &= pattern_state == universe_state;
         };
 
         iterate_universe((GLIDER_HEIGHT, GLIDER_WIDTH), indexer);
         is_glider_detected
     }
 
-    fn run_glider_test(initial_position: (usize, usize), expected_position: (usize, usize), mut universe: Universe) {
+    fn run_glider(initial_position: (usize, usize), mut universe: Universe) -> Universe {
         build_glider(initial_position, &mut universe);
 
         let border_cross_period = GLIDER_SIDE * GLIDER_PERIOD;
@@ -336,21 +1016,51 @@ mod tests {
             universe.next_generation();
         }
 
-        assert!(is_glider_detected(expected_position, &universe));
+        universe
     }
 
-    #[test]
-    fn glider_can_move_around_the_center() {
-        let universe = create_free_universe();
-        let (universe_height, universe_width) = universe.shape();
+    fn run_glider_test(initial_position: (usize, usize), expected_position: (usize, usize), universe: Universe) {
+        let universe = run_glider(initial_position, universe);
+        assert!(is_glider_detected(expected_position, &universe));
+    }
 
-        let initial_position = (universe_height / 2, universe_width / 2);
-        let expected_position = (initial_position.0 + GLIDER_SIDE, initial_position.1 + GLIDER_SIDE);
+    // Glider entering through a twisted edge comes out mirrored along that edge
+    fn get_glider_mirrored_left_to_right() -> SMatrix<u8, GLIDER_HEIGHT, GLIDER_WIDTH> {
+        SMatrix::from_fn(|row_index, column_index| GLIDER_PATTERN[(row_index, GLIDER_WIDTH - 1 - column_index)])
+    }
 
-        run_glider_test(initial_position, expected_position, universe);
+    fn get_glider_mirrored_upside_down() -> SMatrix<u8, GLIDER_HEIGHT, GLIDER_WIDTH> {
+        SMatrix::from_fn(|row_index, column_index| GLIDER_PATTERN[(GLIDER_HEIGHT - 1 - row_index, column_index)])
     }
 
-    #[test]
+    fn get_horizontal_border_crossing(universe: &Universe) -> ((usize, usize), (usize, usize)) {
+        let (universe_height, universe_width) = universe.shape();
+
+        let initial_position = (universe_height / 2, universe_width - GLIDER_WIDTH);
+        let expected_position = (initial_position.0 + GLIDER_SIDE, 0);
+        (initial_position, expected_position)
+    }
+
+    fn get_vertical_border_crossing(universe: &Universe) -> ((usize, usize), (usize, usize)) {
+        let (universe_height, universe_width) = universe.shape();
+
+        let initial_position = (universe_height - GLIDER_HEIGHT, universe_width / 3);
+        let expected_position = (0, initial_position.1 + GLIDER_SIDE);
+        (initial_position, expected_position)
+    }
+
+    #[test]
+    fn glider_can_move_around_the_center() {
+        let universe = create_free_universe();
+        let (universe_height, universe_width) = universe.shape();
+
+        let initial_position = (universe_height / 2, universe_width / 2);
+        let expected_position = (initial_position.0 + GLIDER_SIDE, initial_position.1 + GLIDER_SIDE);
+
+        run_glider_test(initial_position, expected_position, universe);
+    }
+
+    #[test]
     fn glider_can_cross_horizontal_borders() {
         let universe = create_free_universe();
         let (universe_height, universe_width) = universe.shape();
@@ -382,4 +1092,1153 @@ mod tests {
 
         run_glider_test(initial_position, expected_position, universe);
     }
+
+    #[test]
+    fn universe_has_requested_shape() {
+        let universe_shapes = [(50, 50), (400, 300), (7, 2000)];
+
+        for (universe_width, universe_height) in universe_shapes {
+            let free_universe = Universe::new(universe_width, universe_height, Topology::Torus);
+            assert_eq!(free_universe.shape(), (universe_height, universe_width));
+
+            let random_universe = Universe::new_random(universe_width, universe_height, Topology::Torus);
+            assert_eq!(random_universe.shape(), (universe_height, universe_width));
+        }
+    }
+
+    #[test]
+    fn glider_can_cross_corners_of_non_square_universe() {
+        let universe = Universe::new(37, 23, Topology::Torus);
+        let (universe_height, universe_width) = universe.shape();
+
+        let initial_position = (universe_height - GLIDER_HEIGHT, universe_width - GLIDER_WIDTH);
+        let expected_position = (0, 0);
+
+        run_glider_test(initial_position, expected_position, universe);
+    }
+
+    #[test]
+    fn glider_cannot_cross_borders_of_plane() {
+        let universe = Universe::new(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::Plane);
+        let (initial_position, expected_position) = get_horizontal_border_crossing(&universe);
+        let universe = run_glider(initial_position, universe);
+        assert!(!is_glider_detected(expected_position, &universe));
+
+        let universe = Universe::new(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::Plane);
+        let (initial_position, expected_position) = get_vertical_border_crossing(&universe);
+        let universe = run_glider(initial_position, universe);
+        assert!(!is_glider_detected(expected_position, &universe));
+    }
+
+    #[test]
+    fn glider_can_move_around_the_center_of_plane() {
+        let universe = Universe::new(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::Plane);
+        let (universe_height, universe_width) = universe.shape();
+
+        let initial_position = (universe_height / 2, universe_width / 2);
+        let expected_position = (initial_position.0 + GLIDER_SIDE, initial_position.1 + GLIDER_SIDE);
+
+        run_glider_test(initial_position, expected_position, universe);
+    }
+
+    #[test]
+    fn glider_can_cross_horizontal_borders_of_horizontal_cylinder() {
+        let universe = Universe::new(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::HorizontalCylinder);
+        let (initial_position, expected_position) = get_horizontal_border_crossing(&universe);
+        run_glider_test(initial_position, expected_position, universe);
+
+        let universe = Universe::new(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::HorizontalCylinder);
+        let (initial_position, expected_position) = get_vertical_border_crossing(&universe);
+        let universe = run_glider(initial_position, universe);
+        assert!(!is_glider_detected(expected_position, &universe));
+    }
+
+    #[test]
+    fn glider_can_cross_vertical_borders_of_vertical_cylinder() {
+        let universe = Universe::new(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::VerticalCylinder);
+        let (initial_position, expected_position) = get_vertical_border_crossing(&universe);
+        run_glider_test(initial_position, expected_position, universe);
+
+        let universe = Universe::new(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::VerticalCylinder);
+        let (initial_position, expected_position) = get_horizontal_border_crossing(&universe);
+        let universe = run_glider(initial_position, universe);
+        assert!(!is_glider_detected(expected_position, &universe));
+    }
+
+    #[test]
+    fn glider_can_cross_horizontal_borders_of_klein_bottle() {
+        let universe = Universe::new(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::KleinBottle);
+        let (initial_position, expected_position) = get_horizontal_border_crossing(&universe);
+
+        run_glider_test(initial_position, expected_position, universe);
+    }
+
+    #[test]
+    fn glider_is_mirrored_crossing_vertical_borders_of_klein_bottle() {
+        let universe = Universe::new(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::KleinBottle);
+        let (initial_position, expected_position) = get_vertical_border_crossing(&universe);
+        let mirrored_position = (expected_position.0, UNIVERSE_WIDTH - expected_position.1 - GLIDER_WIDTH);
+
+        let universe = run_glider(initial_position, universe);
+        assert!(!is_glider_detected(expected_position, &universe));
+        assert!(is_pattern_detected(&get_glider_mirrored_left_to_right(), mirrored_position, &universe));
+    }
+
+    #[test]
+    fn glider_is_mirrored_crossing_any_borders_of_cross_surface() {
+        let universe = Universe::new(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::CrossSurface);
+        let (initial_position, expected_position) = get_vertical_border_crossing(&universe);
+        let mirrored_position = (expected_position.0, UNIVERSE_WIDTH - expected_position.1 - GLIDER_WIDTH);
+
+        let universe = run_glider(initial_position, universe);
+        assert!(is_pattern_detected(&get_glider_mirrored_left_to_right(), mirrored_position, &universe));
+
+        let universe = Universe::new(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::CrossSurface);
+        let (initial_position, expected_position) = get_horizontal_border_crossing(&universe);
+        let mirrored_position = (UNIVERSE_HEIGHT - expected_position.0 - GLIDER_HEIGHT, expected_position.1);
+
+        let universe = run_glider(initial_position, universe);
+        assert!(is_pattern_detected(&get_glider_mirrored_upside_down(), mirrored_position, &universe));
+    }
+
+    #[test]
+    fn topology_is_chosen_at_creation() {
+        for topology in [Topology::Plane, Topology::HorizontalCylinder, Topology::VerticalCylinder, Topology::Torus, Topology::KleinBottle, Topology::CrossSurface] {
+            let universe = Universe::new_random(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, topology);
+            assert_eq!(universe.topology(), topology);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn cell_outside_of_universe_cannot_be_populated() {
+        let mut universe = Universe::new(40, 30, Topology::Torus);
+        universe.populate_cell(30, 0);
+    }
+
+    const REPLICATOR_PERIOD: usize = 12;
+    const REPLICATOR_OFFSET: usize = 2;
+    const REPLICATOR_SIDE: usize = 5;
+
+    const REPLICATOR_PATTERN: SMatrix<u8, REPLICATOR_SIDE, REPLICATOR_SIDE> = matrix![CELL_IS_FREE,      CELL_IS_FREE,      CELL_IS_POPULATED, CELL_IS_POPULATED, CELL_IS_POPULATED;
+                                                                                      CELL_IS_FREE,      CELL_IS_POPULATED, CELL_IS_FREE,      CELL_IS_FREE,      CELL_IS_POPULATED;
+                                                                                      CELL_IS_POPULATED, CELL_IS_FREE,      CELL_IS_FREE,      CELL_IS_FREE,      CELL_IS_POPULATED;
+                                                                                      CELL_IS_POPULATED, CELL_IS_FREE,      CELL_IS_FREE,      CELL_IS_POPULATED, CELL_IS_FREE;
+                                                                                      CELL_IS_POPULATED, CELL_IS_POPULATED, CELL_IS_POPULATED, CELL_IS_FREE,      CELL_IS_FREE];
+
+    fn build_pattern<const R: usize, const C: usize>(pattern: &SMatrix<u8, R, C>, upper_left_position: (usize, usize), universe: &mut Universe) {
+        let indexer = |row_index: usize, column_index: usize| {
+            let target_row_index = upper_left_position.0 + row_index;
+            let target_column_index = upper_left_position.1 + column_index;
+
+            universe.set_cell_state(target_row_index, target_column_index, pattern[(row_index, column_index)]);
+        };
+
+        iterate_universe(pattern.shape(), indexer);
+    }
+
+    fn is_pattern_detected<const R: usize, const C: usize>(pattern: &SMatrix<u8, R, C>, upper_left_position: (usize, usize), universe: &Universe) -> bool {
+        let mut is_pattern_detected = true;
+        let indexer = |row_index: usize, column_index: usize| {
+            let target_row_index = upper_left_position.0 + row_index;
+            let target_column_index = upper_left_position.1 + column_index;
+
+            let universe_state = universe.get_cell_state(target_row_index, target_column_index);
+            is_pattern_detected &= pattern[(row_index, column_index)] == universe_state;
+        };
+
+        iterate_universe(pattern.shape(), indexer);
+        is_pattern_detected
+    }
+
+    fn count_populated_cells(universe: &Universe) -> usize {
+        let mut number_of_populated_cells: usize = 0;
+        let indexer = |row_index: usize, column_index: usize| {
+            if universe.get_cell_state(row_index, column_index) == CELL_IS_POPULATED {
+                number_of_populated_cells += 1;
+            }
+        };
+
+        iterate_universe(universe.shape(), indexer);
+        number_of_populated_cells
+    }
+
+    #[test]
+    fn conway_rule_is_used_by_default() {
+        let universe = create_free_universe();
+        assert_eq!(universe.rule(), &Rule::conway());
+    }
+
+    #[test]
+    fn replicator_replicates_in_highlife() {
+        let mut universe = create_free_universe();
+        universe.set_rule("B36/S23".parse().unwrap());
+
+        let (universe_height, universe_width) = universe.shape();
+        let initial_position = (universe_height / 2, universe_width / 2);
+        build_pattern(&REPLICATOR_PATTERN, initial_position, &mut universe);
+
+        for _ in 0..REPLICATOR_PERIOD {
+            universe.next_generation();
+        }
+
+        let upper_left_copy_position = (initial_position.0 - REPLICATOR_OFFSET, initial_position.1 - REPLICATOR_OFFSET);
+        let bottom_right_copy_position = (initial_position.0 + REPLICATOR_OFFSET, initial_position.1 + REPLICATOR_OFFSET);
+
+        assert!(is_pattern_detected(&REPLICATOR_PATTERN, upper_left_copy_position, &universe));
+        assert!(is_pattern_detected(&REPLICATOR_PATTERN, bottom_right_copy_position, &universe));
+
+        let replicator_population = REPLICATOR_PATTERN.iter().filter(|cell_state| **cell_state == CELL_IS_POPULATED).count();
+        assert_eq!(count_populated_cells(&universe), 2 * replicator_population);
+    }
+
+    #[test]
+    fn replicator_does_not_replicate_in_conway_life() {
+        let mut universe = create_free_universe();
+
+        let (universe_height, universe_width) = universe.shape();
+        let initial_position = (universe_height / 2, universe_width / 2);
+        build_pattern(&REPLICATOR_PATTERN, initial_position, &mut universe);
+
+        for _ in 0..REPLICATOR_PERIOD {
+            universe.next_generation();
+        }
+
+        let upper_left_copy_position = (initial_position.0 - REPLICATOR_OFFSET, initial_position.1 - REPLICATOR_OFFSET);
+        assert!(!is_pattern_detected(&REPLICATOR_PATTERN, upper_left_copy_position, &universe));
+    }
+
+    #[test]
+    fn populated_cells_never_survive_in_seeds() {
+        let mut universe = Universe::new_random(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::Torus);
+        universe.set_rule("B2/S".parse().unwrap());
+
+        for _ in 0..10 {
+            let mut previous_generation = Vec::new();
+            iterate_universe(universe.shape(), |row_index, column_index| {
+                previous_generation.push(universe.get_cell_state(row_index, column_index));
+            });
+
+            universe.next_generation();
+
+            let mut previous_cell_states = previous_generation.iter();
+            iterate_universe(universe.shape(), |row_index, column_index| {
+                let previous_cell_state = *previous_cell_states.next().unwrap();
+                let cell_state = universe.get_cell_state(row_index, column_index);
+                assert!(!(previous_cell_state == CELL_IS_POPULATED && cell_state == CELL_IS_POPULATED));
+            });
+        }
+    }
+
+    #[test]
+    fn population_never_decreases_in_life_without_death() {
+        let mut universe = Universe::new_random(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::Torus);
+        universe.set_rule("B3/S012345678".parse().unwrap());
+
+        let mut number_of_populated_cells = count_populated_cells(&universe);
+        for _ in 0..10 {
+            universe.next_generation();
+
+            let next_number_of_populated_cells = count_populated_cells(&universe);
+            assert!(next_number_of_populated_cells >= number_of_populated_cells);
+            number_of_populated_cells = next_number_of_populated_cells;
+        }
+    }
+
+    #[test]
+    fn day_and_night_is_symmetric_under_inversion() {
+        let mut universe = Universe::new_random(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::Torus);
+        universe.set_rule("B3678/S34678".parse().unwrap());
+
+        let mut inverted_universe = create_free_universe();
+        inverted_universe.set_rule(universe.rule().clone());
+        iterate_universe(universe.shape(), |row_index, column_index| {
+            if universe.get_cell_state(row_index, column_index) == CELL_IS_FREE {
+                inverted_universe.populate_cell(row_index, column_index);
+            }
+        });
+
+        for _ in 0..10 {
+            universe.next_generation();
+            inverted_universe.next_generation();
+        }
+
+        iterate_universe(universe.shape(), |row_index, column_index| {
+            let cell_state = universe.get_cell_state(row_index, column_index);
+            let inverted_cell_state = inverted_universe.get_cell_state(row_index, column_index);
+            assert_ne!(cell_state, inverted_cell_state);
+        });
+    }
+
+    const BRIANS_BRAIN_SPACESHIP_HEIGHT: usize = 2;
+    const BRIANS_BRAIN_SPACESHIP_WIDTH: usize = 2;
+    const BRIANS_BRAIN_DECAY_STATE: u8 = 2;
+
+    const BRIANS_BRAIN_SPACESHIP_PATTERN: SMatrix<u8, BRIANS_BRAIN_SPACESHIP_HEIGHT, BRIANS_BRAIN_SPACESHIP_WIDTH> = matrix![CELL_IS_POPULATED,        CELL_IS_POPULATED;
+                                                                                                                            BRIANS_BRAIN_DECAY_STATE, BRIANS_BRAIN_DECAY_STATE];
+
+    #[test]
+    fn populated_cell_passes_through_decay_states() {
+        let mut universe = create_free_universe();
+        universe.set_rule("345/2/4".parse().unwrap());
+
+        let (universe_height, universe_width) = universe.shape();
+        let cell_position = (universe_height / 2, universe_width / 2);
+        universe.populate_cell(cell_position.0, cell_position.1);
+
+        for expected_cell_state in [2, 3, CELL_IS_FREE, CELL_IS_FREE] {
+            universe.next_generation();
+            assert_eq!(universe.get_cell_state(cell_position.0, cell_position.1), expected_cell_state);
+        }
+    }
+
+    #[test]
+    fn spaceship_can_cross_vertical_borders_in_brians_brain() {
+        let mut universe = create_free_universe();
+        universe.set_rule("/2/3".parse().unwrap());
+        assert_eq!(universe.number_of_states(), 3);
+
+        let (universe_height, universe_width) = universe.shape();
+        let initial_position = (1, universe_width / 2);
+        build_pattern(&BRIANS_BRAIN_SPACESHIP_PATTERN, initial_position, &mut universe);
+
+        let number_of_generations = 3;
+        for _ in 0..number_of_generations {
+            universe.next_generation();
+        }
+
+        let expected_position = (universe_height + initial_position.0 - number_of_generations, initial_position.1);
+        assert!(is_pattern_detected(&BRIANS_BRAIN_SPACESHIP_PATTERN, expected_position, &universe));
+        assert_eq!(count_populated_cells(&universe), BRIANS_BRAIN_SPACESHIP_WIDTH);
+    }
+
+    #[test]
+    fn decay_states_are_freed_when_rule_has_fewer_states() {
+        let mut universe = create_free_universe();
+        universe.set_rule("345/2/4".parse().unwrap());
+        universe.set_cell_state(0, 0, 3);
+        universe.set_cell_state(0, 1, 2);
+        universe.populate_cell(0, 2);
+
+        universe.set_rule("/2/3".parse().unwrap());
+        assert_eq!(universe.get_cell_state(0, 0), CELL_IS_FREE);
+        assert_eq!(universe.get_cell_state(0, 1), 2);
+        assert_eq!(universe.get_cell_state(0, 2), CELL_IS_POPULATED);
+    }
+
+    #[test]
+    #[should_panic]
+    fn cell_cannot_have_state_missing_in_rule() {
+        let mut universe = create_free_universe();
+        universe.set_cell_state(0, 0, 2);
+    }
+
+    const JUST_FRIENDS_SPACESHIP_PERIOD: usize = 6;
+    const JUST_FRIENDS_SPACESHIP_HEIGHT: usize = 2;
+    const JUST_FRIENDS_SPACESHIP_WIDTH: usize = 3;
+
+    const JUST_FRIENDS_SPACESHIP_PATTERN: SMatrix<u8, JUST_FRIENDS_SPACESHIP_HEIGHT, JUST_FRIENDS_SPACESHIP_WIDTH> = matrix![CELL_IS_FREE,      CELL_IS_FREE,      CELL_IS_POPULATED;
+                                                                                                                            CELL_IS_POPULATED, CELL_IS_POPULATED, CELL_IS_POPULATED];
+
+    #[test]
+    fn spaceship_can_cross_corners_in_non_totalistic_rule() {
+        let mut universe = create_free_universe();
+        universe.set_rule("B2-a/S12".parse().unwrap());
+
+        let (universe_height, universe_width) = universe.shape();
+        let initial_position = (universe_height - JUST_FRIENDS_SPACESHIP_HEIGHT, universe_width - JUST_FRIENDS_SPACESHIP_WIDTH);
+        build_pattern(&JUST_FRIENDS_SPACESHIP_PATTERN, initial_position, &mut universe);
+
+        let border_cross_period = JUST_FRIENDS_SPACESHIP_WIDTH * JUST_FRIENDS_SPACESHIP_PERIOD;
+        for _ in 0..border_cross_period {
+            universe.next_generation();
+        }
+
+        let expected_position = (
+            (initial_position.0 + JUST_FRIENDS_SPACESHIP_WIDTH) % universe_height,
+            (initial_position.1 + JUST_FRIENDS_SPACESHIP_WIDTH) % universe_width,
+        );
+        assert!(is_pattern_detected(&JUST_FRIENDS_SPACESHIP_PATTERN, expected_position, &universe));
+
+        let spaceship_population = JUST_FRIENDS_SPACESHIP_PATTERN.iter().filter(|cell_state| **cell_state == CELL_IS_POPULATED).count();
+        assert_eq!(count_populated_cells(&universe), spaceship_population);
+    }
+
+    #[test]
+    fn non_totalistic_spaceship_does_not_survive_in_totalistic_rule() {
+        let mut universe = create_free_universe();
+        universe.set_rule("B2/S12".parse().unwrap());
+
+        let (universe_height, universe_width) = universe.shape();
+        let initial_position = (universe_height / 2, universe_width / 2);
+        build_pattern(&JUST_FRIENDS_SPACESHIP_PATTERN, initial_position, &mut universe);
+
+        for _ in 0..JUST_FRIENDS_SPACESHIP_PERIOD {
+            universe.next_generation();
+        }
+
+        let expected_position = (initial_position.0 + 1, initial_position.1 + 1);
+        assert!(!is_pattern_detected(&JUST_FRIENDS_SPACESHIP_PATTERN, expected_position, &universe));
+    }
+
+    #[test]
+    fn larger_than_life_of_range_one_evolves_like_conway() {
+        let mut conway_universe = Universe::new_random(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::Torus);
+        let mut larger_than_life_universe = create_free_universe();
+        larger_than_life_universe.set_rule("R1,C0,M1,S3..4,B3..3,NM".parse().unwrap());
+
+        let indexer = |row_index: usize, column_index: usize| {
+            larger_than_life_universe.set_cell_state(row_index, column_index, conway_universe.get_cell_state(row_index, column_index));
+        };
+        iterate_universe(conway_universe.shape(), indexer);
+
+        for _ in 0..10 {
+            conway_universe.next_generation();
+            larger_than_life_universe.next_generation();
+        }
+
+        let mut is_same_generation = true;
+        let indexer = |row_index: usize, column_index: usize| {
+            is_same_generation &= conway_universe.get_cell_state(row_index, column_index) == larger_than_life_universe.get_cell_state(row_index, column_index);
+        };
+        iterate_universe(conway_universe.shape(), indexer);
+        assert!(is_same_generation);
+    }
+
+    #[test]
+    fn alive_cell_is_counted_within_neighbourhood_range() {
+        for (rulestring, number_of_neighbours) in [("R3,M0,B1,NM", 48), ("R3,M0,B1,NN", 24), ("R3,M0,B1,NC", 36)] {
+            let mut universe = create_free_universe();
+            universe.set_rule(rulestring.parse().unwrap());
+            universe.populate_cell(UNIVERSE_HEIGHT / 2, UNIVERSE_WIDTH / 2);
+
+            universe.next_generation();
+            assert_eq!(count_populated_cells(&universe), number_of_neighbours);
+        }
+    }
+
+    #[test]
+    fn neighbourhood_larger_than_universe_wraps_around_torus() {
+        // 11x11 neighbourhood covers 4x4 universe three times along each axis except for one row and one column
+        let mut universe = Universe::new(4, 4, Topology::Torus);
+        universe.set_rule("R5,M1,S9..9,B9..9,NM".parse().unwrap());
+        universe.populate_cell(0, 0);
+
+        universe.next_generation();
+        assert_eq!(count_populated_cells(&universe), 9);
+
+        let mut universe = Universe::new(3, 3, Topology::Torus);
+        universe.set_rule("R2,M1,S25..25,NM".parse().unwrap());
+        iterate_universe(universe.shape(), |row_index, column_index| universe.populate_cell(row_index, column_index));
+
+        universe.next_generation();
+        assert_eq!(count_populated_cells(&universe), 9);
+
+        universe.set_rule("R2,M1,S24..24,NM".parse().unwrap());
+        universe.next_generation();
+        assert_eq!(count_populated_cells(&universe), 0);
+    }
+
+    #[test]
+    fn parallel_convolution_matches_sequential_one_for_any_number_of_bands() {
+        let plane = DMatrix::<u32>::from_fn(37, 23, |row_index, column_index| ((row_index * 7 + column_index * 13) % 5 == 0) as u32);
+        let rule: Rule = "R3,M1,S2..9,B3..5,NC".parse().unwrap();
+
+        for topology in [Topology::Plane, Topology::Torus, Topology::CrossSurface] {
+            let sequential_result = convolve_on_topology(&plane, &rule.kernel(), topology);
+
+            for number_of_bands in [1, 2, 3, 8, 37, 100] {
+                assert_eq!(convolve_on_topology_parallel(&plane, &rule.kernel(), topology, number_of_bands), sequential_result);
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_generations_are_identical_to_sequential_ones() {
+        for rulestring in ["B3/S23", "B2-a/S12", "/2/3", "R2,C0,M1,S5..9,B6..7,NM"] {
+            for topology in [Topology::Torus, Topology::Plane, Topology::KleinBottle] {
+                let mut sequential_universe = Universe::new_random(61, 47, topology);
+                sequential_universe.set_rule(rulestring.parse().unwrap());
+
+                let mut parallel_universe = Universe::new(61, 47, topology);
+                parallel_universe.set_rule(rulestring.parse().unwrap());
+                parallel_universe.set_parallel(true);
+                iterate_universe(sequential_universe.shape(), |row_index, column_index| {
+                    parallel_universe.set_cell_state(row_index, column_index, sequential_universe.get_cell_state(row_index, column_index));
+                });
+
+                for _ in 0..10 {
+                    sequential_universe.next_generation();
+                    parallel_universe.next_generation();
+                    assert_eq!(parallel_universe.next_generation, sequential_universe.next_generation);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fft_convolution_counts_same_neighbourhoods_as_direct_one() {
+        let plane = DMatrix::<u32>::from_fn(40, 29, |row_index, column_index| ((row_index * 5 + column_index * 11) % 7 < 3) as u32);
+
+        for rulestring in ["B3/S23", "R5,C0,M1,S34..58,B34..45,NM", "R7,M0,S10..40,B20..30,NC"] {
+            let rule: Rule = rulestring.parse().unwrap();
+
+            for topology in [Topology::Plane, Topology::Torus, Topology::KleinBottle, Topology::CrossSurface] {
+                assert_eq!(convolve_on_topology_fft(&plane, &rule.kernel(), topology), convolve_on_topology(&plane, &rule.kernel(), topology));
+            }
+        }
+    }
+
+    fn get_populated_cells(universe: &Universe) -> Vec<(usize, usize)> {
+        let mut populated_cells = Vec::new();
+        iterate_universe(universe.shape(), |row_index, column_index| {
+            if universe.get_cell_state(row_index, column_index) == CELL_IS_POPULATED {
+                populated_cells.push((row_index, column_index));
+            }
+        });
+
+        populated_cells
+    }
+
+    #[test]
+    fn hexagonal_cell_gives_birth_to_its_six_neighbours() {
+        let mut universe = Universe::new(6, 6, Topology::Torus);
+        universe.set_rule("B1/SH".parse().unwrap());
+
+        // Odd rows are shifted right, so their neighbours above and below lay to the right
+        universe.populate_cell(3, 2);
+        universe.next_generation();
+        assert_eq!(get_populated_cells(&universe), vec![(2, 2), (2, 3), (3, 1), (3, 3), (4, 2), (4, 3)]);
+
+        let mut universe = Universe::new(4, 4, Topology::Torus);
+        universe.set_rule("B1/SH".parse().unwrap());
+        universe.populate_cell(0, 0);
+        universe.next_generation();
+        assert_eq!(get_populated_cells(&universe), vec![(0, 1), (0, 3), (1, 0), (1, 3), (3, 0), (3, 3)]);
+    }
+
+    #[test]
+    fn triangular_cell_gives_birth_to_its_twelve_neighbours() {
+        let mut universe = Universe::new(8, 6, Topology::Torus);
+        universe.set_rule("B1/SL".parse().unwrap());
+
+        // Cell (2, 2) points up, so it touches three cells above and five below
+        universe.populate_cell(2, 2);
+        universe.next_generation();
+        assert_eq!(get_populated_cells(&universe), vec![
+            (1, 1), (1, 2), (1, 3),
+            (2, 0), (2, 1), (2, 3), (2, 4),
+            (3, 0), (3, 1), (3, 2), (3, 3), (3, 4),
+        ]);
+
+        let mut universe = Universe::new(8, 6, Topology::Torus);
+        universe.set_rule("B1/SL".parse().unwrap());
+        universe.populate_cell(0, 7);
+        universe.next_generation();
+        assert_eq!(count_populated_cells(&universe), 12);
+        assert_eq!(universe.get_cell_state(5, 5), CELL_IS_POPULATED);
+        assert_eq!(universe.get_cell_state(1, 0), CELL_IS_POPULATED);
+    }
+
+    #[test]
+    fn hexagonal_generations_match_in_parallel() {
+        let mut sequential_universe = Universe::new_random(30, 20, Topology::Torus);
+        sequential_universe.set_rule("B2/S34H".parse().unwrap());
+
+        let mut parallel_universe = Universe::new(30, 20, Topology::Torus);
+        parallel_universe.set_rule("B2/S34H".parse().unwrap());
+        parallel_universe.set_parallel(true);
+        iterate_universe(sequential_universe.shape(), |row_index, column_index| {
+            parallel_universe.set_cell_state(row_index, column_index, sequential_universe.get_cell_state(row_index, column_index));
+        });
+
+        for _ in 0..10 {
+            sequential_universe.next_generation();
+            parallel_universe.next_generation();
+            assert_eq!(parallel_universe.next_generation, sequential_universe.next_generation);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn hexagonal_rule_rejects_odd_torus_height() {
+        let mut universe = Universe::new(6, 5, Topology::Torus);
+        universe.set_rule("B2/S34H".parse().unwrap());
+    }
+
+    const WIREWORLD_HEAD: u8 = 1;
+    const WIREWORLD_TAIL: u8 = 2;
+    const WIREWORLD_CONDUCTOR: u8 = 3;
+
+    #[test]
+    fn electron_runs_along_wireworld_wire() {
+        let mut universe = Universe::new(12, 5, Topology::Plane);
+        universe.set_rule_table(RuleTable::wireworld());
+        for column_index in 0..12 {
+            universe.set_cell_state(2, column_index, WIREWORLD_CONDUCTOR);
+        }
+        universe.set_cell_state(2, 0, WIREWORLD_TAIL);
+        universe.set_cell_state(2, 1, WIREWORLD_HEAD);
+
+        for generation in 1..=8 {
+            universe.next_generation();
+            assert_eq!(universe.get_cell_state(2, 1 + generation), WIREWORLD_HEAD);
+            assert_eq!(universe.get_cell_state(2, generation), WIREWORLD_TAIL);
+            assert_eq!(universe.get_cell_state(2, generation - 1), WIREWORLD_CONDUCTOR);
+        }
+
+        assert_eq!(universe.get_cell_state(1, 5), CELL_IS_FREE);
+        assert_eq!(universe.get_cell_state(3, 5), CELL_IS_FREE);
+    }
+
+    #[test]
+    fn rule_table_keeps_its_states_on_the_plane() {
+        let mut universe = Universe::new(6, 3, Topology::Plane);
+        universe.set_rule(Rule::default());
+        universe.next_generation.row_mut(1).copy_from_slice(&[WIREWORLD_TAIL, WIREWORLD_HEAD, WIREWORLD_CONDUCTOR, WIREWORLD_CONDUCTOR, 4, 5]);
+
+        universe.set_rule_table(RuleTable::wireworld());
+        let states: Vec<u8> = (0..6).map(|column_index| universe.get_cell_state(1, column_index)).collect();
+        assert_eq!(states, vec![WIREWORLD_TAIL, WIREWORLD_HEAD, WIREWORLD_CONDUCTOR, WIREWORLD_CONDUCTOR, CELL_IS_FREE, CELL_IS_FREE]);
+
+        universe.next_generation();
+        assert_eq!(universe.get_cell_state(1, 2), WIREWORLD_HEAD);
+        assert_eq!(universe.rule_table(), Some(&RuleTable::wireworld()));
+    }
+
+    #[test]
+    fn cell_cycles_through_rule_table_states() {
+        let mut universe = Universe::new(4, 4, Topology::Torus);
+        universe.set_rule_table(RuleTable::wireworld());
+
+        let cycled_states: Vec<u8> = (0..5).map(|_| universe.cycle_cell_state(1, 2)).collect();
+        assert_eq!(cycled_states, vec![1, 2, 3, 0, 1]);
+
+        universe.set_cell_state(0, 0, WIREWORLD_CONDUCTOR);
+        universe.set_rule(Rule::default());
+        assert!(universe.rule_table().is_none());
+        assert_eq!(universe.get_cell_state(0, 0), CELL_IS_FREE);
+        assert_eq!(universe.cycle_cell_state(0, 0), CELL_IS_POPULATED);
+    }
+
+    fn run_stochastic_universe(seed: u64) -> Universe {
+        let mut universe = Universe::new_random_seeded(40, 30, Topology::Torus, seed, 0.3);
+        universe.set_transition_probabilities(TransitionProbabilities::new(0.7, 0.9));
+        universe.set_update_mode(UpdateMode::alpha_asynchronous(0.5));
+        for _ in 0..20 {
+            universe.next_generation();
+        }
+
+        universe
+    }
+
+    #[test]
+    fn same_seed_replays_the_same_run() {
+        let universe = Universe::new_random_seeded(40, 30, Topology::Torus, 7, 0.3);
+        assert_eq!(universe.seed(), 7);
+        assert_eq!(universe.next_generation, Universe::new_random_seeded(40, 30, Topology::Torus, 7, 0.3).next_generation);
+        assert_ne!(universe.next_generation, Universe::new_random_seeded(40, 30, Topology::Torus, 8, 0.3).next_generation);
+
+        assert_eq!(run_stochastic_universe(7).next_generation, run_stochastic_universe(7).next_generation);
+        assert_ne!(run_stochastic_universe(7).next_generation, run_stochastic_universe(8).next_generation);
+    }
+
+    #[test]
+    fn random_soup_has_requested_density() {
+        for initial_density in [0.0, 0.1, 0.5, 1.0] {
+            let universe = Universe::new_random_seeded(100, 100, Topology::Torus, 42, initial_density);
+            let populated_share = count_populated_cells(&universe) as f64 / 10_000.0;
+            assert!((populated_share - initial_density).abs() < 0.02);
+        }
+    }
+
+    #[test]
+    fn certain_probabilities_keep_rule_deterministic() {
+        let mut deterministic_universe = Universe::new_random_seeded(30, 30, Topology::Torus, 3, 0.4);
+        let mut stochastic_universe = Universe::new_random_seeded(30, 30, Topology::Torus, 3, 0.4);
+        stochastic_universe.set_transition_probabilities(TransitionProbabilities::new(1.0, 1.0));
+        stochastic_universe.set_update_mode(UpdateMode::alpha_asynchronous(1.0));
+
+        for _ in 0..10 {
+            deterministic_universe.next_generation();
+            stochastic_universe.next_generation();
+            assert_eq!(stochastic_universe.next_generation, deterministic_universe.next_generation);
+        }
+    }
+
+    #[test]
+    fn impossible_transitions_never_happen() {
+        let mut universe = Universe::new_seeded(10, 10, Topology::Torus, 1);
+        build_glider((3, 3), &mut universe);
+        universe.set_transition_probabilities(TransitionProbabilities::new(0.0, 1.0));
+        universe.next_generation();
+        assert_eq!(count_populated_cells(&universe), 3);
+
+        let mut universe = Universe::new_seeded(10, 10, Topology::Torus, 1);
+        build_glider((3, 3), &mut universe);
+        universe.set_update_mode(UpdateMode::alpha_asynchronous(0.0));
+        for _ in 0..5 {
+            universe.next_generation();
+        }
+        assert!(is_glider_detected((3, 3), &universe));
+    }
+
+    #[test]
+    fn checkerboard_updates_odd_squares_after_even_ones() {
+        let mut synchronous_universe = Universe::new(10, 10, Topology::Torus);
+        synchronous_universe.set_rule("B1/S012345678".parse().unwrap());
+        synchronous_universe.populate_cell(5, 5);
+
+        let mut checkerboard_universe = Universe::new(10, 10, Topology::Torus);
+        checkerboard_universe.set_rule("B1/S012345678".parse().unwrap());
+        checkerboard_universe.set_update_mode(UpdateMode::Checkerboard);
+        checkerboard_universe.populate_cell(5, 5);
+
+        synchronous_universe.next_generation();
+        checkerboard_universe.next_generation();
+
+        // Diagonal neighbours are born first and then crowd the orthogonal ones out of their single neighbour
+        assert_eq!(synchronous_universe.get_cell_state(4, 4), CELL_IS_POPULATED);
+        assert_eq!(checkerboard_universe.get_cell_state(4, 4), CELL_IS_POPULATED);
+        assert_eq!(synchronous_universe.get_cell_state(4, 5), CELL_IS_POPULATED);
+        assert_eq!(checkerboard_universe.get_cell_state(4, 5), CELL_IS_FREE);
+        assert_eq!(checkerboard_universe.get_cell_state(3, 4), CELL_IS_POPULATED);
+    }
+
+    #[test]
+    fn random_sequential_updates_are_reproducible() {
+        let run_random_sequential_universe = |seed: u64| {
+            let mut universe = Universe::new_random_seeded(30, 20, Topology::Torus, seed, 0.4);
+            universe.set_update_mode(UpdateMode::RandomSequential);
+            for _ in 0..10 {
+                universe.next_generation();
+            }
+
+            universe
+        };
+
+        assert_eq!(run_random_sequential_universe(11).next_generation, run_random_sequential_universe(11).next_generation);
+        assert_ne!(run_random_sequential_universe(11).next_generation, run_random_sequential_universe(12).next_generation);
+    }
+
+    #[test]
+    fn still_life_stays_under_any_update_mode() {
+        let block_cells = [(4, 4), (4, 5), (5, 4), (5, 5)];
+        for update_mode in [UpdateMode::alpha_asynchronous(0.5), UpdateMode::RandomSequential, UpdateMode::Checkerboard] {
+            let mut universe = Universe::new_seeded(10, 10, Topology::Torus, 9);
+            universe.set_update_mode(update_mode);
+            for (row_index, column_index) in block_cells {
+                universe.populate_cell(row_index, column_index);
+            }
+
+            for _ in 0..5 {
+                universe.next_generation();
+            }
+
+            assert_eq!(count_populated_cells(&universe), block_cells.len());
+            assert!(block_cells.iter().all(|(row_index, column_index)| universe.get_cell_state(*row_index, *column_index) == CELL_IS_POPULATED));
+        }
+    }
+
+    #[test]
+    fn billiard_ball_moves_diagonally_through_alternating_blocks() {
+        let mut universe = Universe::new(8, 8, Topology::Torus);
+        universe.set_update_mode(UpdateMode::Margolus(BlockRule::billiard_ball_machine()));
+        universe.populate_cell(0, 0);
+
+        for generation in 1..=12 {
+            universe.next_generation();
+            assert_eq!(count_populated_cells(&universe), 1);
+            assert_eq!(universe.get_cell_state(generation % 8, generation % 8), CELL_IS_POPULATED);
+        }
+    }
+
+    #[test]
+    fn billiard_balls_leave_through_plane_edges() {
+        let mut universe = Universe::new_random_seeded(20, 20, Topology::Torus, 4, 0.3);
+        let number_of_balls = count_populated_cells(&universe);
+        universe.set_update_mode(UpdateMode::Margolus(BlockRule::billiard_ball_machine()));
+        for _ in 0..20 {
+            universe.next_generation();
+            assert_eq!(count_populated_cells(&universe), number_of_balls);
+        }
+
+        let mut universe = Universe::new(5, 5, Topology::Plane);
+        universe.set_update_mode(UpdateMode::Margolus(BlockRule::billiard_ball_machine()));
+        universe.populate_cell(0, 0);
+        for _ in 0..4 {
+            universe.next_generation();
+        }
+        assert_eq!(universe.get_cell_state(4, 4), CELL_IS_POPULATED);
+
+        universe.next_generation();
+        assert_eq!(count_populated_cells(&universe), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn margolus_rejects_odd_torus_width() {
+        let mut universe = Universe::new(7, 8, Topology::Torus);
+        universe.set_update_mode(UpdateMode::Margolus(BlockRule::critters()));
+    }
+
+    #[test]
+    fn generations_and_edits_are_stepped_back_and_forth() {
+        let mut universe = Universe::new(10, 10, Topology::Torus);
+        universe.set_history_capacity(100);
+        build_glider((2, 2), &mut universe);
+        let glider_generation = universe.next_generation.clone();
+
+        for _ in 0..GLIDER_PERIOD {
+            universe.next_generation();
+        }
+        let moved_glider_generation = universe.next_generation.clone();
+        universe.populate_cell(8, 8);
+
+        // Pending edit goes first, then the generations, then the glider edit itself
+        assert!(universe.step_back());
+        assert_eq!(universe.next_generation, moved_glider_generation);
+        for _ in 0..GLIDER_PERIOD {
+            assert!(universe.step_back());
+        }
+        assert_eq!(universe.next_generation, glider_generation);
+        assert!(universe.step_back());
+        assert_eq!(count_populated_cells(&universe), 0);
+        assert!(!universe.step_back());
+
+        universe.rewind_to(1 + GLIDER_PERIOD);
+        assert_eq!(universe.next_generation, moved_glider_generation);
+        assert!(universe.step_forward());
+        assert_eq!(universe.get_cell_state(8, 8), CELL_IS_POPULATED);
+        assert!(!universe.step_forward());
+    }
+
+    #[test]
+    fn undone_margolus_generations_restore_block_partition() {
+        let mut universe = Universe::new_random_seeded(12, 12, Topology::Torus, 9, 0.3);
+        universe.set_update_mode(UpdateMode::Margolus(BlockRule::critters()));
+        universe.set_history_capacity(10);
+
+        let generations: Vec<UniversePlane> = (0..5)
+            .map(|_| {
+                universe.next_generation();
+                universe.next_generation.clone()
+            })
+            .collect();
+
+        for _ in 0..3 {
+            universe.step_back();
+        }
+        universe.next_generation();
+        assert_eq!(universe.next_generation, generations[2]);
+
+        universe.rewind_to(1);
+        universe.next_generation();
+        universe.next_generation();
+        assert_eq!(universe.next_generation, generations[2]);
+    }
+
+    #[test]
+    fn stochastic_run_is_replayed_from_history() {
+        let mut universe = Universe::new_random_seeded(20, 20, Topology::Torus, 5, 0.4);
+        universe.set_transition_probabilities(TransitionProbabilities::new(0.5, 0.5));
+        universe.set_history_capacity(8);
+        for _ in 0..12 {
+            universe.next_generation();
+        }
+
+        let last_generation = universe.next_generation.clone();
+        universe.rewind_to(0);
+        assert_eq!(universe.history().unwrap().len(), 8);
+        universe.rewind_to(8);
+        assert_eq!(universe.next_generation, last_generation);
+    }
+
+    #[test]
+    fn reversed_generation_evolves_back_and_is_undone() {
+        let mut universe = Universe::new(6, 6, Topology::Torus);
+        universe.set_history_capacity(4);
+        for (row, column) in [(1, 2), (2, 3), (3, 1), (3, 2), (3, 3)] {
+            universe.populate_cell(row, column);
+        }
+
+        let glider_generation = universe.next_generation.clone();
+        assert!(!universe.is_garden_of_eden());
+        assert!(universe.reverse_generation());
+        universe.next_generation();
+        assert_eq!(universe.next_generation, glider_generation);
+
+        universe.step_back();
+        assert!(universe.step_back());
+        assert_eq!(universe.next_generation, glider_generation);
+
+        let mut universe = Universe::new(3, 3, Topology::Torus);
+        universe.populate_cell(1, 1);
+        assert!(universe.is_garden_of_eden());
+        assert!(!universe.reverse_generation());
+        assert_eq!(universe.reverse_generation_within(1), PredecessorSearchResult::SearchLimitReached);
+        assert!(matches!(universe.find_region_predecessor(0..2, 0..2), PredecessorSearchResult::Predecessor(_)));
+    }
+
+    #[test]
+    #[should_panic(expected = "deterministic synchronous")]
+    fn predecessor_is_not_searched_through_update_modes() {
+        let mut universe = Universe::new(4, 4, Topology::Torus);
+        universe.set_update_mode(UpdateMode::Checkerboard);
+        universe.find_predecessor();
+    }
+
+    const BLINKER_PATTERN: SMatrix<u8, 1, 3> = matrix![CELL_IS_POPULATED, CELL_IS_POPULATED, CELL_IS_POPULATED];
+
+    const TOAD_PATTERN: SMatrix<u8, 2, 4> = matrix![CELL_IS_FREE,      CELL_IS_POPULATED, CELL_IS_POPULATED, CELL_IS_POPULATED;
+                                                    CELL_IS_POPULATED, CELL_IS_POPULATED, CELL_IS_POPULATED, CELL_IS_FREE];
+
+    const BLOCK_PATTERN: SMatrix<u8, 2, 2> = matrix![CELL_IS_POPULATED, CELL_IS_POPULATED;
+                                                     CELL_IS_POPULATED, CELL_IS_POPULATED];
+
+    #[test]
+    fn glider_is_detected_as_spaceship() {
+        let mut universe = create_free_universe();
+        build_glider((UNIVERSE_HEIGHT - GLIDER_HEIGHT, UNIVERSE_WIDTH - GLIDER_WIDTH), &mut universe);
+
+        let cycle = universe.find_cycle(3 * GLIDER_PERIOD).unwrap();
+        assert_eq!((cycle.period(), cycle.displacement(), cycle.start_generation()), (GLIDER_PERIOD, (1, 1), 0));
+        assert!(cycle.is_spaceship());
+        assert_eq!(cycle.to_string(), "p4 (1, 1)");
+
+        let mut universe = Universe::new(UNIVERSE_WIDTH, UNIVERSE_HEIGHT, Topology::Plane);
+        build_glider((0, 0), &mut universe);
+        assert_eq!(universe.find_cycle(2 * GLIDER_PERIOD).map(|cycle| cycle.displacement()), Some((1, 1)));
+    }
+
+    #[test]
+    fn oscillators_and_still_lifes_are_detected() {
+        let mut universes = [create_free_universe(), create_free_universe(), create_free_universe()];
+        build_pattern(&BLINKER_PATTERN, (10, 10), &mut universes[0]);
+        build_pattern(&TOAD_PATTERN, (10, 10), &mut universes[1]);
+        build_pattern(&BLOCK_PATTERN, (10, 10), &mut universes[2]);
+
+        for (universe, expected_period) in universes.iter_mut().zip([2, 2, 1]) {
+            let cycle = universe.find_cycle(10).unwrap();
+            assert_eq!((cycle.period(), cycle.displacement()), (expected_period, (0, 0)));
+            assert_eq!(cycle.is_still_life(), expected_period == 1);
+        }
+
+        let mut universe = create_free_universe();
+        universe.set_cycle_detection(4);
+        build_pattern(&BLINKER_PATTERN, (10, 10), &mut universe);
+        universe.next_generation();
+        universe.next_generation();
+        assert_eq!(universe.detected_cycle().map(|cycle| cycle.period()), Some(2));
+
+        universe.populate_cell(30, 30);
+        universe.next_generation();
+        assert_eq!(universe.detected_cycle(), None);
+    }
+
+    #[test]
+    fn billiard_ball_cycle_includes_block_partition() {
+        let mut universe = Universe::new(8, 8, Topology::Torus);
+        universe.set_update_mode(UpdateMode::Margolus(BlockRule::billiard_ball_machine()));
+        universe.populate_cell(0, 0);
+
+        let cycle = universe.find_cycle(10).unwrap();
+        assert_eq!((cycle.period(), cycle.displacement()), (2, (2, 2)));
+    }
+
+    #[test]
+    fn cycles_are_not_detected_for_random_updates() {
+        let mut universe = create_free_universe();
+        build_pattern(&BLOCK_PATTERN, (10, 10), &mut universe);
+        universe.set_update_mode(UpdateMode::alpha_asynchronous(0.5));
+
+        assert_eq!(universe.find_cycle(20), None);
+        assert_eq!(universe.detected_cycle(), None);
+    }
+
+    #[test]
+    fn isolated_object_is_detected_apart_from_the_rest() {
+        let mut universe = create_free_universe();
+        build_glider((5, 5), &mut universe);
+        build_pattern(&TOAD_PATTERN, (30, 20), &mut universe);
+        build_pattern(&BLINKER_PATTERN, (40, 40), &mut universe);
+
+        assert_eq!(universe.get_object_positions(6, 7).len(), 5);
+        assert!(universe.get_object_positions(0, 0).is_empty());
+        assert_eq!(universe.find_object_cycle(6, 7, 10).map(|cycle| (cycle.period(), cycle.displacement())), Some((GLIDER_PERIOD, (1, 1))));
+        assert_eq!(universe.find_object_cycle(30, 21, 10).map(|cycle| (cycle.period(), cycle.displacement())), Some((2, (0, 0))));
+        assert_eq!(universe.get_cell_state(5, 6), CELL_IS_POPULATED);
+    }
+
+    fn build_pattern_from_rows(pattern_rows: &[&str], upper_left_position: (usize, usize), universe: &mut Universe) {
+        for (row_index, pattern_row) in pattern_rows.iter().enumerate() {
+            for (column_index, _) in pattern_row.char_indices().filter(|(_, cell)| *cell == 'o') {
+                universe.populate_cell(upper_left_position.0 + row_index, upper_left_position.1 + column_index);
+            }
+        }
+    }
+
+    #[test]
+    fn objects_are_classified_by_their_codes() {
+        let objects: [(&[&str], &str); 12] = [
+            (&["oo", "oo"], "block"),
+            (&[".oo.", "o..o", ".oo."], "beehive"),
+            (&[".oo.", "o..o", ".o.o", "..o."], "loaf"),
+            (&["oo.", "o.o", ".o."], "boat"),
+            (&["oo.", "o.o", ".oo"], "ship"),
+            (&[".o.", "o.o", ".o."], "tub"),
+            (&[".oo.", "o..o", "o..o", ".oo."], "pond"),
+            (&["ooo"], "blinker"),
+            (&[".ooo", "ooo."], "toad"),
+            (&["oo..", "oo..", "..oo", "..oo"], "beacon"),
+            (&[".o.", "..o", "ooo"], "glider"),
+            (&[".o..o", "o....", "o...o", "oooo."], "lightweight spaceship"),
+        ];
+
+        for (pattern_rows, expected_name) in objects {
+            let mut universe = create_free_universe();
+            build_pattern_from_rows(pattern_rows, (20, 20), &mut universe);
+
+            let object_codes: Vec<String> = universe.get_objects().iter().filter_map(|object| universe.classify_object(object, 20)).collect();
+            assert_eq!(object_codes.len(), 1);
+            assert_eq!(get_object_name(&object_codes[0]), Some(expected_name), "{} was coded {}", expected_name, object_codes[0]);
+        }
+    }
+
+    #[test]
+    fn isolated_hexagonal_object_keeps_its_row_parity() {
+        let mut universe = Universe::new(20, 20, Topology::Torus);
+        universe.set_rule("B2/S34H".parse().unwrap());
+        for (row_index, column_index) in [(5, 4), (5, 5), (6, 6), (7, 5)] {
+            universe.populate_cell(row_index, column_index);
+        }
+
+        let objects = universe.get_objects();
+        assert_eq!(objects.len(), 1);
+        let mut object_universe = universe.isolate_object(&objects[0]);
+        for _ in 0..3 {
+            universe.next_generation();
+            object_universe.next_generation();
+            assert_eq!(crop_populated_cells(&object_universe.next_generation, Topology::Torus).1, crop_populated_cells(&universe.next_generation, Topology::Torus).1);
+        }
+    }
+
+    #[test]
+    fn isolated_billiard_ball_keeps_its_block_partition() {
+        let mut universe = Universe::new(12, 12, Topology::Torus);
+        universe.set_update_mode(UpdateMode::Margolus(BlockRule::billiard_ball_machine()));
+        universe.populate_cell(4, 4);
+        universe.next_generation();
+        assert_eq!(universe.get_cell_state(5, 5), CELL_IS_POPULATED);
+
+        let objects = universe.get_objects();
+        let mut object_universe = universe.isolate_object(&objects[0]);
+        let (ball_row, ball_column) = crop_populated_cells(&object_universe.next_generation, Topology::Torus).0;
+        object_universe.next_generation();
+        assert_eq!(object_universe.get_cell_state(ball_row + 1, ball_column + 1), CELL_IS_POPULATED);
+    }
+
+    #[test]
+    fn census_counts_objects_of_settled_soup() {
+        let mut universe = create_free_universe();
+        build_glider((UNIVERSE_HEIGHT - GLIDER_HEIGHT, UNIVERSE_WIDTH - GLIDER_WIDTH), &mut universe);
+        build_pattern(&BLOCK_PATTERN, (10, 10), &mut universe);
+        build_pattern(&BLOCK_PATTERN, (10, 30), &mut universe);
+        build_pattern(&BLINKER_PATTERN, (30, 10), &mut universe);
+        for _ in 0..GLIDER_PERIOD + 1 {
+            universe.next_generation();
+        }
+        assert_eq!(universe.get_cell_state(0, 0), CELL_IS_POPULATED);
+
+        let census = universe.take_census(DEFAULT_CENSUS_MAX_GENERATIONS);
+        assert_eq!(census.number_of_objects(), 4);
+        assert_eq!((census.count("xs4_33"), census.count("xp2_7"), census.count("xq4_153")), (2, 1, 1));
+
+        universe.populate_cell(40, 40);
+        universe.populate_cell(40, 41);
+        assert_eq!(universe.get_objects().len(), 5);
+        assert_eq!(universe.take_census(DEFAULT_CENSUS_MAX_GENERATIONS), census);
+
+        let mut universe = Universe::new_random_seeded(40, 40, Topology::Torus, 11, 0.35);
+        universe.find_cycle(2000);
+        let census = universe.take_census(DEFAULT_CENSUS_MAX_GENERATIONS);
+        assert_eq!(census.number_of_objects(), universe.get_objects().len());
+        assert_eq!(census.count(UNCLASSIFIED_OBJECT_CODE), 0);
+    }
+
+    #[test]
+    fn blinker_stats_are_collected_every_generation() {
+        let mut universe = create_free_universe();
+        build_pattern(&BLINKER_PATTERN, (10, 10), &mut universe);
+        universe.next_generation();
+        assert!(universe.generation_stats().is_none());
+
+        universe.set_stats_collection(true);
+        for generation in 1..=4 {
+            universe.next_generation();
+
+            let generation_stats = universe.generation_stats().unwrap();
+            assert_eq!(generation_stats.generation(), generation);
+            assert_eq!((generation_stats.population(), generation_stats.births(), generation_stats.deaths(), generation_stats.changed_cells()), (3, 2, 2, 4));
+            assert_eq!(generation_stats.density(), 3.0 / (UNIVERSE_WIDTH * UNIVERSE_HEIGHT) as f64);
+        }
+
+        let bounding_box = universe.generation_stats().unwrap().bounding_box().unwrap();
+        assert_eq!((bounding_box.first_row, bounding_box.first_column, bounding_box.number_of_rows, bounding_box.number_of_columns), (9, 11, 3, 1));
+    }
+
+    #[test]
+    fn still_life_ages_while_blinker_cells_die_young() {
+        let mut universe = create_free_universe();
+        build_pattern(&BLOCK_PATTERN, (10, 10), &mut universe);
+        build_pattern(&BLINKER_PATTERN, (30, 10), &mut universe);
+        assert_eq!(universe.get_cell_age(10, 10), None);
+
+        universe.set_age_tracking(true);
+        for _ in 0..5 {
+            universe.next_generation();
+        }
+
+        assert_eq!(universe.get_cell_age(10, 10), Some(5));
+        assert_eq!(universe.get_cell_age(30, 11), Some(5));
+        assert_eq!((universe.get_cell_age(29, 11), universe.get_cell_age(30, 10)), (Some(0), None));
+        assert_eq!((universe.get_time_since_death(30, 10), universe.get_time_since_death(29, 11)), (Some(0), None));
+        assert_eq!(universe.get_time_since_death(0, 0), None);
+
+        universe.free_cell(10, 10);
+        universe.populate_cell(0, 0);
+        assert_eq!((universe.get_time_since_death(10, 10), universe.get_cell_age(0, 0)), (Some(0), Some(0)));
+        universe.next_generation();
+        assert_eq!(universe.get_time_since_death(10, 10), None);
+        assert_eq!(universe.get_time_since_death(0, 0), Some(0));
+    }
+
+    #[test]
+    fn ages_restart_when_moving_through_history() {
+        let mut universe = create_free_universe();
+        universe.set_history_capacity(10);
+        build_pattern(&BLOCK_PATTERN, (10, 10), &mut universe);
+        build_pattern(&BLINKER_PATTERN, (30, 10), &mut universe);
+        universe.set_age_tracking(true);
+        for _ in 0..5 {
+            universe.next_generation();
+        }
+
+        assert!(universe.step_back());
+        assert_eq!(universe.get_cell_age(10, 10), Some(0));
+        assert_eq!((universe.get_cell_age(30, 10), universe.get_cell_age(30, 11)), (Some(0), Some(0)));
+        assert_eq!((universe.get_time_since_death(29, 11), universe.get_time_since_death(31, 11)), (None, None));
+
+        universe.next_generation();
+        assert_eq!(universe.get_cell_age(10, 10), Some(1));
+        assert_eq!((universe.get_cell_age(30, 11), universe.get_time_since_death(30, 10)), (Some(1), Some(0)));
+
+        assert!(universe.step_back());
+        assert!(universe.step_forward());
+        assert_eq!(universe.get_cell_age(10, 10), Some(0));
+
+        universe.rewind_to(2);
+        assert_eq!((universe.get_cell_age(10, 10), universe.get_cell_age(29, 11)), (Some(0), Some(0)));
+        assert_eq!(universe.get_time_since_death(30, 10), None);
+    }
 }
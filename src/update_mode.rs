@@ -0,0 +1,235 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use crate::stochastic::*;
+
+const SYNCHRONOUS_NAME: &str = "synchronous";
+const ALPHA_ASYNCHRONOUS_NAME: &str = "alpha-asynchronous";
+const RANDOM_SEQUENTIAL_NAME: &str = "random-sequential";
+const CHECKERBOARD_NAME: &str = "checkerboard";
+const MARGOLUS_NAME: &str = "margolus";
+const MODE_PARAMETER_SEPARATOR: char = ':';
+
+const CRITTERS_NAME: &str = "critters";
+const BILLIARD_BALL_MACHINE_NAME: &str = "billiard-ball";
+const BLOCK_RULE_PREFIX: &str = "MS,D";
+const BLOCK_RULE_SEPARATOR: char = ';';
+
+pub const NUMBER_OF_BLOCKS: usize = 16;
+pub const BLOCK_SIDE: usize = 2;
+
+// Blocks are numbered by their populated cells: upper left 1, upper right 2, lower left 4, lower right 8
+const CRITTERS_BLOCKS: [u8; NUMBER_OF_BLOCKS] = [15, 14, 13, 3, 11, 5, 6, 1, 7, 9, 10, 2, 12, 4, 8, 0];
+const BILLIARD_BALL_MACHINE_BLOCKS: [u8; NUMBER_OF_BLOCKS] = [0, 8, 4, 3, 2, 5, 9, 7, 1, 6, 10, 11, 12, 13, 14, 15];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateModeParseError(String);
+
+impl fmt::Display for UpdateModeParseError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "'{}' is not one of {}, {}{}<probability>, {}, {}, {}{}<{}|{}|{}...>", self.0,
+            SYNCHRONOUS_NAME, ALPHA_ASYNCHRONOUS_NAME, MODE_PARAMETER_SEPARATOR, RANDOM_SEQUENTIAL_NAME, CHECKERBOARD_NAME,
+            MARGOLUS_NAME, MODE_PARAMETER_SEPARATOR, CRITTERS_NAME, BILLIARD_BALL_MACHINE_NAME, BLOCK_RULE_PREFIX)
+    }
+}
+
+impl Error for UpdateModeParseError {}
+
+// Rule of a block cellular automaton telling which block of 2x2 cells replaces each of the 16 possible blocks,
+// written in MCell notation like `MS,D0;8;4;3;2;5;9;7;1;6;10;11;12;13;14;15`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockRule([u8; NUMBER_OF_BLOCKS]);
+
+impl BlockRule {
+    pub fn new(next_blocks: [u8; NUMBER_OF_BLOCKS]) -> Self {
+        if let Some(next_block) = next_blocks.iter().find(|next_block| **next_block as usize >= NUMBER_OF_BLOCKS) {
+            panic!("'BlockRule' expects blocks from 0 to {}, received {}.", NUMBER_OF_BLOCKS - 1, next_block);
+        }
+
+        BlockRule(next_blocks)
+    }
+
+    // Blocks of exactly two populated cells stay, the others are inverted,
+    // and blocks of three populated cells are also turned upside down
+    pub fn critters() -> Self {
+        BlockRule::new(CRITTERS_BLOCKS)
+    }
+
+    // Single cells move diagonally through the blocks like balls, two balls colliding head-on bounce off
+    pub fn billiard_ball_machine() -> Self {
+        BlockRule::new(BILLIARD_BALL_MACHINE_BLOCKS)
+    }
+
+    pub fn get_next_block(&self, block: u8) -> u8 {
+        self.0[block as usize]
+    }
+
+    // Reversible rules replace the blocks one to one, so each generation has a single predecessor
+    pub fn is_reversible(&self) -> bool {
+        let mut is_block_reached = [false; NUMBER_OF_BLOCKS];
+        for next_block in self.0 {
+            is_block_reached[next_block as usize] = true;
+        }
+
+        is_block_reached.iter().all(|is_reached| *is_reached)
+    }
+}
+
+impl FromStr for BlockRule {
+    type Err = UpdateModeParseError;
+
+    fn from_str(block_rule: &str) -> Result<Self, Self::Err> {
+        let parse_error = || UpdateModeParseError(String::from(block_rule));
+        let trimmed_block_rule = block_rule.trim();
+
+        match trimmed_block_rule.to_lowercase().as_str() {
+            CRITTERS_NAME => return Ok(BlockRule::critters()),
+            BILLIARD_BALL_MACHINE_NAME => return Ok(BlockRule::billiard_ball_machine()),
+            _ => (),
+        }
+
+        let next_blocks = trimmed_block_rule
+            .strip_prefix(BLOCK_RULE_PREFIX)
+            .ok_or_else(parse_error)?
+            .split(BLOCK_RULE_SEPARATOR)
+            .map(|next_block| match next_block.trim().parse::<u8>() {
+                Ok(next_block) if (next_block as usize) < NUMBER_OF_BLOCKS => Ok(next_block),
+                _ => Err(parse_error()),
+            })
+            .collect::<Result<Vec<u8>, _>>()?;
+
+        let next_blocks: [u8; NUMBER_OF_BLOCKS] = next_blocks.try_into().map_err(|_| parse_error())?;
+        Ok(BlockRule::new(next_blocks))
+    }
+}
+
+impl fmt::Display for BlockRule {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let next_blocks: Vec<String> = self.0.iter().map(|next_block| next_block.to_string()).collect();
+        write!(formatter, "{}{}", BLOCK_RULE_PREFIX, next_blocks.join(&BLOCK_RULE_SEPARATOR.to_string()))
+    }
+}
+
+// Order in which the cells get their next states:
+// - synchronous: all at once,
+// - α-asynchronous: each cell independently with the given probability while the others keep their states,
+// - random-sequential: one by one in random order, each cell seeing the states its neighbours got so far,
+// - checkerboard: cells of the even squares of a checkerboard first, then the odd ones,
+// - Margolus: whole blocks of 2x2 cells at once by the block rule instead of the rule of the universe,
+//   the partition into blocks shifts by one cell diagonally every generation
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum UpdateMode {
+    #[default]
+    Synchronous,
+    AlphaAsynchronous(f64),
+    RandomSequential,
+    Checkerboard,
+    Margolus(BlockRule),
+}
+
+impl UpdateMode {
+    pub fn alpha_asynchronous(update_probability: f64) -> Self {
+        check_probability("update_probability", update_probability);
+        UpdateMode::AlphaAsynchronous(update_probability)
+    }
+
+    pub fn update_probability(&self) -> f64 {
+        match self {
+            UpdateMode::AlphaAsynchronous(update_probability) => *update_probability,
+            _ => 1.0,
+        }
+    }
+}
+
+impl FromStr for UpdateMode {
+    type Err = UpdateModeParseError;
+
+    fn from_str(update_mode: &str) -> Result<Self, Self::Err> {
+        let parse_error = || UpdateModeParseError(String::from(update_mode));
+        let trimmed_update_mode = update_mode.trim();
+        let (mode_name, mode_parameter) = match trimmed_update_mode.split_once(MODE_PARAMETER_SEPARATOR) {
+            Some((mode_name, mode_parameter)) => (mode_name.to_lowercase(), Some(mode_parameter)),
+            None => (trimmed_update_mode.to_lowercase(), None),
+        };
+
+        match (mode_name.as_str(), mode_parameter) {
+            (SYNCHRONOUS_NAME, None) => Ok(UpdateMode::Synchronous),
+            (ALPHA_ASYNCHRONOUS_NAME, Some(update_probability)) => match update_probability.parse::<f64>() {
+                Ok(update_probability) if (0.0..=1.0).contains(&update_probability) => Ok(UpdateMode::AlphaAsynchronous(update_probability)),
+                _ => Err(parse_error()),
+            },
+            (RANDOM_SEQUENTIAL_NAME, None) => Ok(UpdateMode::RandomSequential),
+            (CHECKERBOARD_NAME, None) => Ok(UpdateMode::Checkerboard),
+            (MARGOLUS_NAME, Some(block_rule)) => Ok(UpdateMode::Margolus(block_rule.parse().map_err(|_| parse_error())?)),
+            _ => Err(parse_error()),
+        }
+    }
+}
+
+impl fmt::Display for UpdateMode {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UpdateMode::Synchronous => write!(formatter, "{}", SYNCHRONOUS_NAME),
+            UpdateMode::AlphaAsynchronous(update_probability) => write!(formatter, "{}{}{}", ALPHA_ASYNCHRONOUS_NAME, MODE_PARAMETER_SEPARATOR, update_probability),
+            UpdateMode::RandomSequential => write!(formatter, "{}", RANDOM_SEQUENTIAL_NAME),
+            UpdateMode::Checkerboard => write!(formatter, "{}", CHECKERBOARD_NAME),
+            UpdateMode::Margolus(block_rule) => write!(formatter, "{}{}{}", MARGOLUS_NAME, MODE_PARAMETER_SEPARATOR, block_rule),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_mode_is_parsed_from_its_name() {
+        let update_modes = [
+            UpdateMode::Synchronous,
+            UpdateMode::alpha_asynchronous(0.25),
+            UpdateMode::RandomSequential,
+            UpdateMode::Checkerboard,
+            UpdateMode::Margolus(BlockRule::critters()),
+        ];
+        for update_mode in update_modes {
+            assert_eq!(update_mode.to_string().parse::<UpdateMode>(), Ok(update_mode));
+        }
+
+        assert_eq!("Alpha-Asynchronous:1".parse::<UpdateMode>(), Ok(UpdateMode::AlphaAsynchronous(1.0)));
+        assert_eq!("margolus:billiard-ball".parse::<UpdateMode>(), Ok(UpdateMode::Margolus(BlockRule::billiard_ball_machine())));
+        assert_eq!("margolus:MS,D0;8;4;3;2;5;9;7;1;6;10;11;12;13;14;15".parse::<UpdateMode>(), Ok(UpdateMode::Margolus(BlockRule::billiard_ball_machine())));
+        assert!("alpha-asynchronous".parse::<UpdateMode>().is_err());
+        assert!("alpha-asynchronous:1.5".parse::<UpdateMode>().is_err());
+        assert!("synchronous:0.5".parse::<UpdateMode>().is_err());
+        assert!("margolus:MS,D0;1;2".parse::<UpdateMode>().is_err());
+        assert!("margolus:MS,D16;1;2;3;4;5;6;7;8;9;10;11;12;13;14;15".parse::<UpdateMode>().is_err());
+    }
+
+    #[test]
+    fn critters_follows_its_definition() {
+        let critters = BlockRule::critters();
+        let rotate_upside_down = |block: u8| (0..4).filter(|cell| block & (1 << cell) != 0).map(|cell| 1 << (3 - cell)).sum::<u8>();
+
+        for block in 0..NUMBER_OF_BLOCKS as u8 {
+            let expected_block = match block.count_ones() {
+                2 => block,
+                3 => rotate_upside_down(!block & 0b1111),
+                _ => !block & 0b1111,
+            };
+            assert_eq!(critters.get_next_block(block), expected_block);
+        }
+
+        assert!(critters.is_reversible());
+    }
+
+    #[test]
+    fn billiard_balls_are_conserved() {
+        let billiard_ball_machine = BlockRule::billiard_ball_machine();
+        for block in 0..NUMBER_OF_BLOCKS as u8 {
+            assert_eq!(billiard_ball_machine.get_next_block(block).count_ones(), block.count_ones());
+        }
+
+        assert!(billiard_ball_machine.is_reversible());
+        assert!(!BlockRule::new([0; NUMBER_OF_BLOCKS]).is_reversible());
+    }
+}
@@ -0,0 +1,298 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use nalgebra::DMatrix;
+use rand::distributions::Bernoulli;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use crate::stochastic::*;
+use crate::topology::*;
+use crate::universe::*;
+
+pub const NUMBER_OF_VOLUME_NEIGHBOURS: u32 = 26;
+
+const VOLUME_RULE_SEPARATOR: char = ',';
+const NUMBER_OF_VOLUME_RULE_BOUNDS: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VolumeRuleParseError(String);
+
+impl fmt::Display for VolumeRuleParseError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "'{}' is not a rule like 4555 or 4,5,5,5 with ordered bounds up to {}", self.0, NUMBER_OF_VOLUME_NEIGHBOURS)
+    }
+}
+
+impl Error for VolumeRuleParseError {}
+
+// Three-dimensional totalistic rule in Bays' notation `ElEuFlFu`: a populated cell survives
+// with El to Eu populated neighbours out of its 26, a free cell gets populated with Fl to Fu of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VolumeRule {
+    survival_minimum: u32,
+    survival_maximum: u32,
+    birth_minimum: u32,
+    birth_maximum: u32,
+}
+
+impl VolumeRule {
+    pub fn new(survival_minimum: u32, survival_maximum: u32, birth_minimum: u32, birth_maximum: u32) -> Self {
+        if survival_minimum > survival_maximum || birth_minimum > birth_maximum || survival_maximum.max(birth_maximum) > NUMBER_OF_VOLUME_NEIGHBOURS {
+            panic!("'VolumeRule' expects ordered bounds up to {}, received {}, {}, {}, {}.",
+                NUMBER_OF_VOLUME_NEIGHBOURS, survival_minimum, survival_maximum, birth_minimum, birth_maximum);
+        }
+
+        VolumeRule { survival_minimum, survival_maximum, birth_minimum, birth_maximum }
+    }
+
+    pub fn is_survival(&self, alive_neighbourhood: u32) -> bool {
+        (self.survival_minimum..=self.survival_maximum).contains(&alive_neighbourhood)
+    }
+
+    pub fn is_birth(&self, alive_neighbourhood: u32) -> bool {
+        (self.birth_minimum..=self.birth_maximum).contains(&alive_neighbourhood)
+    }
+
+    fn bounds(&self) -> [u32; NUMBER_OF_VOLUME_RULE_BOUNDS] {
+        [self.survival_minimum, self.survival_maximum, self.birth_minimum, self.birth_maximum]
+    }
+}
+
+// Bays' Life 4555, which has a glider of ten cells
+impl Default for VolumeRule {
+    fn default() -> Self {
+        VolumeRule::new(4, 5, 5, 5)
+    }
+}
+
+// Bounds are written digit by digit, or separated by commas once some of them exceed 9
+impl FromStr for VolumeRule {
+    type Err = VolumeRuleParseError;
+
+    fn from_str(rulestring: &str) -> Result<Self, Self::Err> {
+        let parse_error = || VolumeRuleParseError(String::from(rulestring));
+        let rulestring = rulestring.trim();
+
+        let bounds: Vec<u32> = if rulestring.contains(VOLUME_RULE_SEPARATOR) {
+            rulestring.split(VOLUME_RULE_SEPARATOR).map(|bound| bound.trim().parse::<u32>().map_err(|_| parse_error())).collect::<Result<_, _>>()?
+        } else {
+            rulestring.chars().map(|bound| bound.to_digit(10).ok_or_else(parse_error)).collect::<Result<_, _>>()?
+        };
+
+        match bounds[..] {
+            [survival_minimum, survival_maximum, birth_minimum, birth_maximum]
+                if survival_minimum <= survival_maximum && birth_minimum <= birth_maximum && survival_maximum.max(birth_maximum) <= NUMBER_OF_VOLUME_NEIGHBOURS =>
+                Ok(VolumeRule::new(survival_minimum, survival_maximum, birth_minimum, birth_maximum)),
+            _ => Err(parse_error()),
+        }
+    }
+}
+
+impl fmt::Display for VolumeRule {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let bounds: Vec<String> = self.bounds().iter().map(|bound| bound.to_string()).collect();
+        let separator = if self.bounds().iter().any(|bound| *bound > 9) { VOLUME_RULE_SEPARATOR.to_string() } else { String::new() };
+
+        write!(formatter, "{}", bounds.join(&separator))
+    }
+}
+
+// Universe of cubic cells stacked in layers, which wraps around along all three axes (3D torus)
+pub struct VolumeUniverse {
+    universe_depth: usize,
+    universe_height: usize,
+    universe_width: usize,
+    rule: VolumeRule,
+    layers: Vec<UniversePlane>,
+}
+
+impl VolumeUniverse {
+    fn check_shape(universe_width: usize, universe_height: usize, universe_depth: usize) {
+        if universe_width == 0 || universe_height == 0 || universe_depth == 0 {
+            panic!("'VolumeUniverse' expects non-zero dimensions, received {}x{}x{}.", universe_width, universe_height, universe_depth);
+        }
+    }
+
+    pub fn new(universe_width: usize, universe_height: usize, universe_depth: usize) -> Self {
+        VolumeUniverse::check_shape(universe_width, universe_height, universe_depth);
+
+        VolumeUniverse {
+            universe_depth,
+            universe_height,
+            universe_width,
+            rule: VolumeRule::default(),
+            layers: vec![UniversePlane::from_element(universe_height, universe_width, CELL_IS_FREE); universe_depth],
+        }
+    }
+
+    pub fn new_random(universe_width: usize, universe_height: usize, universe_depth: usize) -> Self {
+        VolumeUniverse::new_random_seeded(universe_width, universe_height, universe_depth, rand::random(), DEFAULT_INITIAL_DENSITY)
+    }
+
+    pub fn new_random_seeded(universe_width: usize, universe_height: usize, universe_depth: usize, seed: u64, initial_density: f64) -> Self {
+        let mut volume_universe = VolumeUniverse::new(universe_width, universe_height, universe_depth);
+
+        let mut random_generator = StdRng::seed_from_u64(seed);
+        let density_distribution = Bernoulli::new(initial_density)
+            .unwrap_or_else(|_| panic!("'VolumeUniverse' expects an initial density from 0 to 1, received {}.", initial_density));
+        for layer in volume_universe.layers.iter_mut() {
+            *layer = DMatrix::<bool>::from_distribution(universe_height, universe_width, &density_distribution, &mut random_generator)
+                .map(|is_populated| is_populated as u8);
+        }
+
+        volume_universe
+    }
+
+    pub fn rule(&self) -> &VolumeRule {
+        &self.rule
+    }
+
+    pub fn set_rule(&mut self, rule: VolumeRule) {
+        self.rule = rule;
+    }
+
+    // Depth goes first like the layers which hold the rows and columns
+    pub fn shape(&self) -> (usize, usize, usize) {
+        (self.universe_depth, self.universe_height, self.universe_width)
+    }
+
+    fn get_volume_position(&self, cell_layer: usize, cell_row: usize, cell_column: usize) -> (usize, (usize, usize)) {
+        if cell_layer >= self.universe_depth || cell_row >= self.universe_height || cell_column >= self.universe_width {
+            panic!("Cell ({}, {}, {}) is out of the universe of shape {:?}.", cell_layer, cell_row, cell_column, self.shape());
+        }
+
+        (cell_layer, (cell_row, cell_column))
+    }
+
+    pub fn populate_cell(&mut self, cell_layer: usize, cell_row: usize, cell_column: usize) {
+        let (layer, position) = self.get_volume_position(cell_layer, cell_row, cell_column);
+        self.layers[layer][position] = CELL_IS_POPULATED;
+    }
+
+    pub fn free_cell(&mut self, cell_layer: usize, cell_row: usize, cell_column: usize) {
+        let (layer, position) = self.get_volume_position(cell_layer, cell_row, cell_column);
+        self.layers[layer][position] = CELL_IS_FREE;
+    }
+
+    pub fn get_cell_state(&self, cell_layer: usize, cell_row: usize, cell_column: usize) -> u8 {
+        let (layer, position) = self.get_volume_position(cell_layer, cell_row, cell_column);
+        self.layers[layer][position]
+    }
+
+    pub fn layer(&self, cell_layer: usize) -> &UniversePlane {
+        &self.layers[self.get_volume_position(cell_layer, 0, 0).0]
+    }
+
+    pub fn count_populated_cells(&self) -> usize {
+        self.layers.iter().map(|layer| layer.iter().filter(|cell_state| **cell_state == CELL_IS_POPULATED).count()).sum()
+    }
+
+    // Neighbours of the cell fill the 3x3 squares of its own layer and the two layers around it,
+    // so the layers are summed in threes and convolved with the 3x3 kernel on the torus, and the cell itself is taken away
+    fn get_alive_neighbourhoods(&self) -> Vec<DMatrix<u32>> {
+        let alive_layers: Vec<DMatrix<u32>> = self.layers.iter().map(|layer| layer.map(|cell_state| (cell_state == CELL_IS_POPULATED) as u32)).collect();
+        let square_kernel = DMatrix::from_element(3, 3, 1_u32);
+
+        (0..self.universe_depth)
+            .map(|layer_index| {
+                let upper_layer = &alive_layers[(layer_index + self.universe_depth - 1) % self.universe_depth];
+                let lower_layer = &alive_layers[(layer_index + 1) % self.universe_depth];
+                let stacked_layers = upper_layer + &alive_layers[layer_index] + lower_layer;
+
+                convolve_on_topology(&stacked_layers, &square_kernel, Topology::Torus) - &alive_layers[layer_index]
+            })
+            .collect()
+    }
+
+    pub fn next_generation(&mut self) {
+        let alive_neighbourhoods = self.get_alive_neighbourhoods();
+
+        let rule = &self.rule;
+        for (layer, alive_neighbourhood) in self.layers.iter_mut().zip(alive_neighbourhoods) {
+            layer.zip_apply(&alive_neighbourhood, |cell_state, alive_neighbourhood| {
+                let is_populated = if *cell_state == CELL_IS_POPULATED { rule.is_survival(alive_neighbourhood) } else { rule.is_birth(alive_neighbourhood) };
+                *cell_state = is_populated as u8;
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count_alive_neighbourhood_naively(volume_universe: &VolumeUniverse, cell_layer: usize, cell_row: usize, cell_column: usize) -> u32 {
+        let (universe_depth, universe_height, universe_width) = volume_universe.shape();
+        let mut alive_neighbourhood = 0;
+        for layer_offset in [universe_depth - 1, 0, 1] {
+            for row_offset in [universe_height - 1, 0, 1] {
+                for column_offset in [universe_width - 1, 0, 1] {
+                    if layer_offset == 0 && row_offset == 0 && column_offset == 0 {
+                        continue;
+                    }
+
+                    let cell_state = volume_universe.get_cell_state(
+                        (cell_layer + layer_offset) % universe_depth,
+                        (cell_row + row_offset) % universe_height,
+                        (cell_column + column_offset) % universe_width,
+                    );
+                    alive_neighbourhood += (cell_state == CELL_IS_POPULATED) as u32;
+                }
+            }
+        }
+
+        alive_neighbourhood
+    }
+
+    #[test]
+    fn neighbours_are_counted_across_wrapped_layers() {
+        let volume_universe = VolumeUniverse::new_random_seeded(7, 6, 5, 21, 0.4);
+        let alive_neighbourhoods = volume_universe.get_alive_neighbourhoods();
+
+        for (cell_layer, alive_neighbourhood) in alive_neighbourhoods.iter().enumerate() {
+            for cell_row in 0..6 {
+                for cell_column in 0..7 {
+                    let expected_neighbourhood = count_alive_neighbourhood_naively(&volume_universe, cell_layer, cell_row, cell_column);
+                    assert_eq!(alive_neighbourhood[(cell_row, cell_column)], expected_neighbourhood);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cube_is_still_life_of_5766() {
+        let mut volume_universe = VolumeUniverse::new(8, 8, 8);
+        volume_universe.set_rule("5766".parse().unwrap());
+        for cell_layer in 7..9 {
+            for cell_row in 3..5 {
+                for cell_column in 0..2 {
+                    volume_universe.populate_cell(cell_layer % 8, cell_row, cell_column);
+                }
+            }
+        }
+
+        for _ in 0..4 {
+            volume_universe.next_generation();
+            assert_eq!(volume_universe.count_populated_cells(), 8);
+            assert_eq!(volume_universe.get_cell_state(0, 4, 1), CELL_IS_POPULATED);
+            assert_eq!(volume_universe.get_cell_state(7, 3, 0), CELL_IS_POPULATED);
+        }
+
+        // Each cell of the cube has seven neighbours which is too many to survive in 4555
+        volume_universe.set_rule(VolumeRule::default());
+        volume_universe.next_generation();
+        assert_eq!(volume_universe.count_populated_cells(), 0);
+    }
+
+    #[test]
+    fn volume_rule_is_parsed_in_bays_notation() {
+        assert_eq!("4555".parse::<VolumeRule>(), Ok(VolumeRule::new(4, 5, 5, 5)));
+        assert_eq!("5,7,6,6".parse::<VolumeRule>(), Ok(VolumeRule::new(5, 7, 6, 6)));
+        assert_eq!("0,26,13,14".parse::<VolumeRule>().unwrap().to_string(), "0,26,13,14");
+        assert_eq!(VolumeRule::new(5, 7, 6, 6).to_string(), "5766");
+
+        for invalid_rulestring in ["455", "45555", "5455", "4,5,5,27", "B45/S5", ""] {
+            assert!(invalid_rulestring.parse::<VolumeRule>().is_err());
+        }
+    }
+}